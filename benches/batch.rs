@@ -0,0 +1,38 @@
+//! Compare rolling 1,000 checks one at a time against rolling them as
+//! one batch with `Roller::d_many`.
+//!
+//! Run with `cargo bench --bench batch`.
+
+use rollforgrue::dice::{Advantage, Dice, DieSize, RollSpec, Roller};
+use std::time::{Duration, Instant};
+
+/// How many rolls each pass makes.
+const ROLLS: usize = 1000;
+
+/// How many passes to time, taking the fastest of each.
+const PASSES: usize = 50;
+
+/// Time the fastest of several passes of some rolling.
+/// * `roll` - One pass of rolls, given fresh dice.
+fn fastest<F: Fn(&Dice)>(roll: F) -> Duration {
+    (0..PASSES).map(|_| {
+        let dice: Dice = Dice::seeded(1);
+        let start: Instant = Instant::now();
+        roll(&dice);
+        start.elapsed()
+    }).min().unwrap_or_default()
+}
+
+fn main() {
+    let specs: Vec<RollSpec> = (0..ROLLS).map(|_| RollSpec::new(DieSize::D20, 3, Advantage::Advantage)).collect();
+    let single: Duration = fastest(|dice| {
+        for spec in &specs {
+            dice.d_with_options(spec.sides, spec.modifier, spec.advantage, &spec.options);
+        }
+    });
+    let batched: Duration = fastest(|dice| {
+        dice.d_many(&specs);
+    });
+    println!("{} rolls one at a time: {:?}", ROLLS, single);
+    println!("{} rolls as one batch:  {:?}", ROLLS, batched);
+}
@@ -2,21 +2,342 @@
 //!
 //! This provides tools for rolling that are removed from the
 //! higher-level game constructs.
-use rand::{thread_rng, Rng};
-use rand::rngs::ThreadRng;
-use std::cmp::{max, min};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rand_chacha::ChaCha20Rng;
 use std::cell::RefCell;
-use std::ops::Add;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+use std::collections::{HashMap, VecDeque};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign};
+use std::str::FromStr;
 
 /// Rolls dice within given parameters.
 ///
-/// This struct will only function in a single-threaded context.
-/// To use it with multithreading, make a clone for each thread.
+/// Dice may be shared between threads. Each roll locks the dice
+/// while it is made, so concurrent rolls happen one after another
+/// rather than interleaving their faces.
 pub struct Dice {
-    rng: RefCell<ThreadRng>,
+    /// Where the dice get their faces from.
+    rng: Mutex<RngBackend>,
+    /// The most recent rolls, oldest first.
+    history: Mutex<VecDeque<HistoryEntry>>,
+    /// Foretold d20 faces waiting to replace the next d20 rolls.
+    portents: Mutex<VecDeque<u8>>,
+    /// Listeners told about every roll.
+    observers: Mutex<Vec<Observer>>,
+    /// The rerolls left to spend with `reroll_last`.
+    rerolls: Mutex<u32>,
+    /// The most recent d20 roll, for `reroll_last` to repeat.
+    last_d20: Mutex<Option<LastRoll>>,
+    /// Whether dice are rolled or their average taken.
+    mode: Mutex<RollMode>,
+    /// How often each face has come up this session, by die size.
+    stats: Mutex<HashMap<DieSize, Vec<u64>>>,
+}
+
+/// The largest die whose faces are counted for `Dice::face_stats`.
+pub const STATS_MAX_SIDES: u16 = 100;
+
+/// How a set of dice decides its faces.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RollMode {
+    /// Roll each die at random.
+    #[default]
+    Random,
+    /// Take each die's average face instead of rolling, for quick prep.
+    Average {
+        /// Whether to take the higher of the two middle faces of a die
+        /// with an even number of sides, such as 11 rather than 10 on a d20.
+        round_up: bool,
+    },
+}
+
+impl RollMode {
+    /// The face to use in place of rolling, if any.
+    ///
+    /// * `sides` - The number of sides on the die.
+    fn fixed_face(&self, sides: DieSize) -> Option<u16> {
+        match self {
+            RollMode::Random => None,
+            RollMode::Average {round_up: false} => Some(sides.sides().div_ceil(2)),
+            RollMode::Average {round_up: true} => Some(sides.sides() / 2 + 1),
+        }
+    }
+}
+
+/// The parameters of a d20 roll, kept so that it can be rerolled.
+struct LastRoll {
+    modifier: i32,
+    advantage: Advantage,
+    options: RollOptions,
+}
+
+/// Where a set of dice gets its faces from.
+enum RngBackend {
+    /// A generator seeded from the operating system, for normal play.
+    Entropy(StdRng),
+    /// A ChaCha generator with a known seed, so rolls can be replayed.
+    Seeded(u64, ChaCha20Rng),
+    /// Faces handed out in order, for tests.
+    Scripted(VecDeque<u16>),
+}
+
+impl RngBackend {
+    /// Produce the next face of a die.
+    ///
+    /// Scripted faces too large for the die are lowered to its
+    /// highest face. Panics if a script has run out.
+    /// * `sides` - The number of sides on the die.
+    fn roll(&mut self, sides: DieSize) -> u16 {
+        match self {
+            RngBackend::Entropy(rng) => rng.gen_range(1..=sides.sides()),
+            RngBackend::Seeded(_, rng) => rng.gen_range(1..=sides.sides()),
+            RngBackend::Scripted(faces) => match faces.pop_front() {
+                Some(face) => face.min(sides.sides()),
+                None => panic!("Scripted dice ran out of faces rolling 1{}", sides),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for RngBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RngBackend::Entropy(_) => write!(f, "entropy from the operating system"),
+            RngBackend::Seeded(seed, _) => write!(f, "ChaCha seeded with {}", seed),
+            RngBackend::Scripted(faces) => write!(f, "a script with {} faces left", faces.len()),
+        }
+    }
+}
+
+/// A listener told about every roll made with a set of dice.
+type Observer = Arc<dyn Fn(&RollEvent) + Send + Sync>;
+
+/// A notice, sent to observers, that a roll was made.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RollEvent {
+    /// The roll that was made.
+    pub entry: HistoryEntry,
+    /// When the roll was made.
+    pub timestamp: SystemTime,
+}
+
+// Dice must stay usable from other threads and async tasks.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Dice>();
+};
+
+/// Lock a mutex, carrying on past any panic that poisoned it.
+///
+/// Nothing the dice guard is left half-updated by a panic, so the
+/// contents are always safe to use.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Count rolled faces towards `Dice::face_stats`.
+///
+/// * `stats` - The counts to add to.
+/// * `sides` - The number of sides on the dice.
+/// * `faces` - The faces rolled.
+fn tally(stats: &Mutex<HashMap<DieSize, Vec<u64>>>, sides: DieSize, faces: &[u16]) {
+    if sides.sides() > STATS_MAX_SIDES {
+        return;
+    }
+    let mut stats: MutexGuard<HashMap<DieSize, Vec<u64>>> = lock(stats);
+    let counts: &mut Vec<u64> = stats.entry(sides).or_insert_with(|| vec![0; sides.sides() as usize]);
+    for &face in faces {
+        counts[face as usize - 1] += 1;
+    }
+}
+
+/// The number of sides on a die.
+///
+/// Any size from `DieSize::MIN` to `DieSize::MAX` can be rolled,
+/// including odd sizes like d3 and d7. Sizes display as "d20".
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DieSize(u16);
+
+impl DieSize {
+    /// The fewest sides a die may have.
+    pub const MIN: u16 = 2;
+    /// The most sides a die may have.
+    pub const MAX: u16 = 1000;
+    /// A three-sided die.
+    pub const D3: DieSize = DieSize(3);
+    /// A four-sided die.
+    pub const D4: DieSize = DieSize(4);
+    /// A six-sided die.
+    pub const D6: DieSize = DieSize(6);
+    /// An eight-sided die.
+    pub const D8: DieSize = DieSize(8);
+    /// A ten-sided die.
+    pub const D10: DieSize = DieSize(10);
+    /// A twelve-sided die.
+    pub const D12: DieSize = DieSize(12);
+    /// A twenty-sided die.
+    pub const D20: DieSize = DieSize(20);
+    /// A hundred-sided die, as rolled by percentile dice.
+    pub const D100: DieSize = DieSize(100);
+
+    /// Check that a number of sides can be rolled.
+    ///
+    /// * `sides` - The number of sides on the die.
+    pub fn try_new(sides: u16) -> Result<DieSize, DiceError> {
+        if (DieSize::MIN..=DieSize::MAX).contains(&sides) {
+            Ok(DieSize(sides))
+        } else {
+            Err(DiceError::InvalidDieSize(sides))
+        }
+    }
+
+    /// The number of sides on the die.
+    pub fn sides(&self) -> u16 {
+        self.0
+    }
+}
+
+impl TryFrom<u16> for DieSize {
+    type Error = DiceError;
+
+    fn try_from(sides: u16) -> Result<DieSize, Self::Error> {
+        DieSize::try_new(sides)
+    }
+}
+
+impl std::fmt::Display for DieSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "d{}", self.0)
+    }
+}
+
+/// Reasons a roll cannot be made.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DiceError {
+    /// A die was asked for with a number of sides outside
+    /// `DieSize::MIN..=DieSize::MAX`.
+    InvalidDieSize(u16),
+    /// The modifier is so large that the total cannot be represented.
+    Overflow,
+    /// A roll expression could not be understood; holds the offending text.
+    BadExpression(String),
+}
+
+impl std::fmt::Display for DiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DiceError::InvalidDieSize(sides) => write!(f, "cannot roll a die with {} sides", sides),
+            DiceError::Overflow => write!(f, "the roll's total is too large to represent"),
+            DiceError::BadExpression(text) => write!(f, "cannot understand roll expression \"{}\"", text),
+        }
+    }
+}
+
+impl std::error::Error for DiceError {}
+
+/// The number of Portent faces that can be waiting at once.
+pub const PORTENT_LIMIT: usize = 2;
+
+/// Reasons a Portent face can be refused.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PortentError {
+    /// The face is not on a d20.
+    OutOfRange(u8),
+    /// `PORTENT_LIMIT` faces are already waiting.
+    Full,
+}
+
+impl std::fmt::Display for PortentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PortentError::OutOfRange(value) => write!(f, "portent value {} is not between 1 and 20", value),
+            PortentError::Full => write!(f, "already holding {} portent values", PORTENT_LIMIT),
+        }
+    }
+}
+
+impl std::error::Error for PortentError {}
+
+/// Reasons a reroll can be refused.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RerollError {
+    /// The reroll budget has been spent.
+    NoRerollsLeft,
+    /// No d20 has been rolled yet.
+    NothingToReroll,
+}
+
+impl std::fmt::Display for RerollError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RerollError::NoRerollsLeft => write!(f, "no rerolls left to spend"),
+            RerollError::NothingToReroll => write!(f, "no d20 roll to reroll"),
+        }
+    }
+}
+
+impl std::error::Error for RerollError {}
+
+/// Reasons an inspiration die cannot be added to a pending roll.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InspirationDieError {
+    /// No inspiration die is held to spend.
+    NotHeld,
+    /// The roll failed automatically, so no die can rescue it.
+    AutomaticFailure,
+}
+
+impl std::fmt::Display for InspirationDieError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            InspirationDieError::NotHeld => write!(f, "no inspiration die to spend"),
+            InspirationDieError::AutomaticFailure => write!(f, "an automatic failure cannot be rescued"),
+        }
+    }
+}
+
+impl std::error::Error for InspirationDieError {}
+
+/// The label an inspiration die is shown under among a roll's bonuses.
+pub const INSPIRATION_DIE_LABEL: &str = "Bardic Inspiration";
+
+/// The number of rolls that `Dice` remembers.
+pub const HISTORY_LIMIT: usize = 500;
+
+/// A record of one logical roll, kept for auditing.
+///
+/// Both dice of an advantage roll, or every die of a pool, are
+/// grouped in a single entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// The number of sides on each die.
+    pub sides: DieSize,
+    /// Every face rolled, in order.
+    pub faces: Vec<u16>,
+    /// The advantage level the roll was made with.
+    pub advantage: Advantage,
+    /// The number added to the roll.
+    pub modifier: i32,
+    /// The final value of the roll.
+    pub total: i32,
+    /// Whether the roll was a reroll of an earlier one.
+    pub reroll: bool,
 }
 
 /// Represents every advantage state in which a roll can be made.
+///
+/// Advantage states combine with `+`, which is commutative. `Fail`
+/// takes precedence over everything, then `Canceled`; advantage
+/// (of either strength) and disadvantage together cancel;
+/// super-advantage outranks advantage; and `None` changes nothing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Advantage {
     /// Neither advantage nor disadvantage.
     None,
@@ -28,6 +349,8 @@ pub enum Advantage {
     Advantage,
     /// Roll two, take the lower.
     Disadvantage,
+    /// Roll three, take the highest (Elven Accuracy).
+    SuperAdvantage,
     /// The roll with fail, regardless of what happens.
     Fail,
 }
@@ -38,7 +361,11 @@ impl Add<Advantage> for Advantage {
     fn add(self, other: Advantage) -> Advantage {
         match self {
             Advantage::None => other,
-            Advantage::Canceled => Advantage::Canceled,
+            Advantage::Canceled =>
+                match other {
+                    Advantage::Fail => Advantage::Fail,
+                    _ => Advantage::Canceled,
+                },
             Advantage::Fail => Advantage::Fail,
             Advantage::Advantage => 
                 match other {
@@ -46,6 +373,7 @@ impl Add<Advantage> for Advantage {
                     Advantage::None => Advantage::Advantage,
                     Advantage::Canceled => Advantage::Canceled,
                     Advantage::Disadvantage => Advantage::Canceled,
+                    Advantage::SuperAdvantage => Advantage::SuperAdvantage,
                     Advantage::Fail => Advantage::Fail,
                 },
             Advantage::Disadvantage => 
@@ -54,49 +382,2679 @@ impl Add<Advantage> for Advantage {
                     Advantage::None => Advantage::Disadvantage,
                     Advantage::Canceled => Advantage::Canceled,
                     Advantage::Disadvantage => Advantage::Disadvantage,
+                    Advantage::SuperAdvantage => Advantage::Canceled,
+                    Advantage::Fail => Advantage::Fail,
+                },
+            Advantage::SuperAdvantage => 
+                match other {
+                    Advantage::Advantage => Advantage::SuperAdvantage,
+                    Advantage::None => Advantage::SuperAdvantage,
+                    Advantage::Canceled => Advantage::Canceled,
+                    Advantage::Disadvantage => Advantage::Canceled,
+                    Advantage::SuperAdvantage => Advantage::SuperAdvantage,
                     Advantage::Fail => Advantage::Fail,
                 },
         }
     }
 }
 
-impl Dice {
-    /// Generate a new thread-locked set of dice.
-    pub fn new() -> Dice {
-        Dice {rng: RefCell::new(thread_rng())}
+impl FromStr for Advantage {
+    type Err = ();
+    fn from_str(input: &str) -> Result<Advantage, Self::Err> {
+        match input {
+            "none" => Ok(Advantage::None),
+            "canceled" => Ok(Advantage::Canceled),
+            "advantage" => Ok(Advantage::Advantage),
+            "disadvantage" => Ok(Advantage::Disadvantage),
+            "super_advantage" => Ok(Advantage::SuperAdvantage),
+            "fail" => Ok(Advantage::Fail),
+            _ => Err(()),
+        }
     }
+}
 
-    /// Roll flat, with neither advantage or disadvantage.
+impl std::fmt::Display for Advantage {
+    /// Name the advantage state as `from_str` reads it.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Advantage::None => write!(f, "none"),
+            Advantage::Canceled => write!(f, "canceled"),
+            Advantage::Advantage => write!(f, "advantage"),
+            Advantage::Disadvantage => write!(f, "disadvantage"),
+            Advantage::SuperAdvantage => write!(f, "super_advantage"),
+            Advantage::Fail => write!(f, "fail"),
+        }
+    }
+}
+
+impl AddAssign<Advantage> for Advantage {
+    /// Combine another advantage state into this one.
+    fn add_assign(&mut self, other: Advantage) {
+        *self = *self + other;
+    }
+}
+
+impl Sum<Advantage> for Advantage {
+    /// Combine any number of advantage states. With none at all, the
+    /// result is `Advantage::None`.
+    fn sum<I: Iterator<Item = Advantage>>(iter: I) -> Advantage {
+        iter.fold(Advantage::None, |total, advantage| total + advantage)
+    }
+}
+
+impl<'a> Sum<&'a Advantage> for Advantage {
+    fn sum<I: Iterator<Item = &'a Advantage>>(iter: I) -> Advantage {
+        iter.copied().sum()
+    }
+}
+
+/// Every source of advantage or disadvantage on a roll, with reasons.
+///
+/// The sources resolve to a single `Advantage` by addition, which
+/// is commutative, so the order they are added in does not matter.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AdvantageSet {
+    /// Each source's advantage state and the reason it applies.
+    sources: Vec<(Advantage, String)>,
+}
+
+impl AdvantageSet {
+    /// Start a set with no sources, which resolves to `Advantage::None`.
+    pub fn new() -> AdvantageSet {
+        AdvantageSet {sources: Vec::new()}
+    }
+
+    /// Add a source of advantage or disadvantage.
     ///
-    /// Generally, Dice::d() should be used instead.
+    /// Sources of `Advantage::None` change nothing and are left out.
     ///
-    /// * `d` -  The number of sides on the die.
-    /// * `modifier` - The number to add to the roll.
-    fn d_flat(&self, d: u8, modifier: i8) -> i8 {
-        let mut borrowed_rng: std::cell::RefMut<ThreadRng> = self.rng.borrow_mut();
-        let result: i8 = borrowed_rng.gen_range(1..=d) as i8 + modifier;
-        log::info!("Rolling 1d{} + {} = {}", d, modifier, result);
-        result
+    /// * `advantage` - The advantage state the source contributes.
+    /// * `reason` - Why the source applies, for display.
+    pub fn add(&mut self, advantage: Advantage, reason: &str) {
+        if advantage != Advantage::None {
+            self.sources.push((advantage, String::from(reason)));
+        }
     }
 
-    /// Roll a die with the specified advantage level.
+    /// Add a source, builder-style.
+    ///
+    /// * `advantage` - The advantage state the source contributes.
+    /// * `reason` - Why the source applies, for display.
+    pub fn with(mut self, advantage: Advantage, reason: &str) -> AdvantageSet {
+        self.add(advantage, reason);
+        self
+    }
+
+    /// Combine every source into a single advantage state.
+    pub fn resolve(&self) -> Advantage {
+        self.sources.iter().map(|&(advantage, _)| advantage).sum()
+    }
+
+    /// Each source's advantage state and the reason it applies.
+    pub fn sources(&self) -> &[(Advantage, String)] {
+        &self.sources
+    }
+
+    /// The reasons for each source, in the order they were added.
+    pub fn reasons(&self) -> Vec<&str> {
+        self.sources.iter().map(|(_, reason)| reason.as_str()).collect()
+    }
+}
+
+impl std::fmt::Display for AdvantageSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self.resolve())?;
+        if !self.sources.is_empty() {
+            let sources: Vec<String> = self.sources.iter().map(|(advantage, reason)| format!("{:?} from {}", advantage, reason)).collect();
+            write!(f, " ({})", sources.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// The outcome of a single roll.
+///
+/// Besides the total, this keeps the natural face of the die that
+/// was used, so that critical successes and natural 1s can be told
+/// apart after the modifier has been added.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RollResult {
+    /// The number of sides on the die.
+    sides: DieSize,
+    /// The face showing on the kept die, or 0 if none was rolled.
+    natural: u16,
+    /// Every die rolled, in order, including any not kept.
+    faces: Vec<u16>,
+    /// The position in `faces` of the kept die, if one was rolled.
+    kept_index: Option<usize>,
+    /// The face the kept die showed before it was rerolled, if it was.
+    rerolled_from: Option<u16>,
+    /// The face the kept die showed before being raised to a floor, if it was.
+    floored_from: Option<u16>,
+    /// The number added to the natural face.
+    modifier: i32,
+    /// The advantage level the roll was made with.
+    advantage: Advantage,
+    /// Whether the face was foretold by Portent rather than rolled.
+    portent: bool,
+    /// Extra dice rolled once and added to the total.
+    bonuses: Vec<BonusRoll>,
+    /// The tens and ones dice, if this was a percentile roll.
+    percentile_dice: Option<PercentileDice>,
+    /// The total before it was clamped to the roll's bounds, if it was.
+    unclamped: Option<i32>,
+    /// Whether the face is the die's average rather than rolled.
+    average: bool,
+    /// The final value of the roll.
+    total: i32,
+}
+
+/// The two d10s that make up a percentile roll.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PercentileDice {
+    /// The tens die, from 0 ("00") to 90.
+    pub tens: u8,
+    /// The ones die, from 0 to 9.
+    pub ones: u8,
+}
+
+impl RollResult {
+    /// Record a flat roll of a single die.
+    ///
+    /// * `sides` - The number of sides on the die.
+    /// * `natural` - The face that came up.
+    /// * `modifier` - The number added to the face.
+    pub fn new(sides: DieSize, natural: u16, modifier: i32) -> RollResult {
+        RollResult {sides, natural, faces: vec![natural], kept_index: Some(0), rerolled_from: None, floored_from: None, modifier, advantage: Advantage::None, portent: false, bonuses: Vec::new(), percentile_dice: None, unclamped: None, average: false, total: natural as i32 + modifier}
+    }
+
+    /// The number of sides on the die.
+    pub fn sides(&self) -> DieSize {
+        self.sides
+    }
+
+    /// The face showing on the kept die.
     ///
-    /// Two dice are rolled and the value used depends upon
-    /// the advantage level.
+    /// This is 0 for an automatic failure, where no die is rolled.
+    pub fn natural(&self) -> u16 {
+        self.natural
+    }
+
+    /// Every die rolled, in order, including any not kept.
     ///
-    /// * `d` - The number of sides on the die.
-    /// * `modifier` - The number to add to the roll.
-    /// * `advantage` - The advantage level to apply.
-    pub fn d(&self, d: u8, modifier: i8, advantage: Advantage) -> i8 {
-        // Roll two regardless and figure out which to use later.
-        let roll_1: i8 = self.d_flat(d, modifier);
-        let roll_2: i8 = self.d_flat(d, modifier);
-        match advantage {
-            Advantage::None => roll_1,
-            Advantage::Canceled => roll_1,
-            Advantage::Advantage => max(roll_1, roll_2),
-            Advantage::Disadvantage => min(roll_1, roll_2),
-            Advantage::Fail => 0,
-        }
-    }
-}
\ No newline at end of file
+    /// A flat roll has one face, and an automatic failure none.
+    pub fn faces(&self) -> &[u16] {
+        &self.faces
+    }
+
+    /// The position in `faces` of the kept die, if one was rolled.
+    pub fn kept_index(&self) -> Option<usize> {
+        self.kept_index
+    }
+
+    /// The faces of the dice rolled but not kept, in order.
+    pub fn discarded(&self) -> Vec<u16> {
+        self.faces.iter().enumerate().filter(|&(index, _)| Some(index) != self.kept_index).map(|(_, &face)| face).collect()
+    }
+
+    /// The face the kept die showed before being rerolled.
+    ///
+    /// This is `None` unless a reroll option replaced the first face.
+    pub fn rerolled_from(&self) -> Option<u16> {
+        self.rerolled_from
+    }
+
+    /// The face the kept die showed before being raised to a floor.
+    ///
+    /// This is `None` unless the die came up below the roll's floor.
+    pub fn floored_from(&self) -> Option<u16> {
+        self.floored_from
+    }
+
+    /// The number added to the natural face.
+    pub fn modifier(&self) -> i32 {
+        self.modifier
+    }
+
+    /// The advantage level the roll was made with.
+    pub fn advantage(&self) -> Advantage {
+        self.advantage
+    }
+
+    /// The final value of the roll.
+    ///
+    /// This is 0 for an automatic failure, which an ordinary roll
+    /// with a large penalty can also total; check
+    /// `is_automatic_failure` before treating it as a number.
+    pub fn total(&self) -> i32 {
+        self.total
+    }
+
+    /// The final value of the roll, or `None` for an automatic failure.
+    pub fn rolled_total(&self) -> Option<i32> {
+        if self.is_automatic_failure() {
+            None
+        } else {
+            Some(self.total)
+        }
+    }
+
+    /// Whether the roll failed without any dice being rolled, as
+    /// with `Advantage::Fail`.
+    pub fn is_automatic_failure(&self) -> bool {
+        self.advantage == Advantage::Fail
+    }
+
+    /// The extra dice added to the roll.
+    pub fn bonuses(&self) -> &[BonusRoll] {
+        &self.bonuses
+    }
+
+    /// The tens and ones dice, if this was a percentile roll.
+    pub fn percentile_dice(&self) -> Option<PercentileDice> {
+        self.percentile_dice
+    }
+
+    /// Whether the face was foretold by Portent rather than rolled.
+    pub fn is_portent(&self) -> bool {
+        self.portent
+    }
+
+    /// Whether the face is the die's average rather than rolled, so
+    /// the roll should not be passed off as a real one.
+    pub fn is_average(&self) -> bool {
+        self.average
+    }
+
+    /// Whether the kept die was a natural 20.
+    ///
+    /// An automatic failure is never a natural 20.
+    pub fn is_nat20(&self) -> bool {
+        !self.is_automatic_failure() && self.sides == DieSize::D20 && self.natural == 20
+    }
+
+    /// Whether the kept die was a natural 1.
+    ///
+    /// An automatic failure is not counted as a natural 1, since
+    /// no die decided it.
+    pub fn is_nat1(&self) -> bool {
+        !self.is_automatic_failure() && self.sides == DieSize::D20 && self.natural == 1
+    }
+}
+
+/// How a roll fared against a difficulty class.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckOutcome {
+    /// A natural 20 on a d20 which met the DC. Ability checks have no
+    /// automatic success, so a natural 20 short of the DC is a failure.
+    CriticalSuccess,
+    /// The total met or beat the DC.
+    Success {
+        /// How far the total exceeded the DC; 0 when it was met exactly.
+        by: i32,
+    },
+    /// The total fell short of the DC.
+    Failure {
+        /// How far the total fell short of the DC.
+        by: i32,
+    },
+    /// The roll failed without dice being rolled, as with `Advantage::Fail`.
+    AutomaticFailure,
+}
+
+impl CheckOutcome {
+    /// Judge a roll against a difficulty class.
+    ///
+    /// * `result` - The roll to judge.
+    /// * `dc` - The total needed to succeed.
+    pub fn of(result: &RollResult, dc: i32) -> CheckOutcome {
+        if result.is_automatic_failure() {
+            CheckOutcome::AutomaticFailure
+        } else if result.total >= dc && result.is_nat20() {
+            CheckOutcome::CriticalSuccess
+        } else if result.total >= dc {
+            CheckOutcome::Success {by: result.total - dc}
+        } else {
+            CheckOutcome::Failure {by: dc - result.total}
+        }
+    }
+
+    /// Whether the check succeeded.
+    pub fn is_success(&self) -> bool {
+        matches!(self, CheckOutcome::CriticalSuccess | CheckOutcome::Success {..})
+    }
+}
+
+/// The outcome of an exploding roll.
+///
+/// Each time a die shows its highest face it is rolled again
+/// and the new face added, up to a cap.
+pub struct ExplodingResult {
+    /// The number of sides on the die.
+    sides: DieSize,
+    /// Every face rolled, in order.
+    faces: Vec<u16>,
+    /// The number added to the sum of the faces.
+    modifier: i32,
+    /// The final value of the roll.
+    total: i32,
+}
+
+impl ExplodingResult {
+    /// The number of sides on the die.
+    pub fn sides(&self) -> DieSize {
+        self.sides
+    }
+
+    /// Every face rolled, in order.
+    pub fn faces(&self) -> &[u16] {
+        &self.faces
+    }
+
+    /// The number added to the sum of the faces.
+    pub fn modifier(&self) -> i32 {
+        self.modifier
+    }
+
+    /// How many times the die exploded.
+    pub fn explosions(&self) -> usize {
+        self.faces.len() - 1
+    }
+
+    /// The final value of the roll.
+    pub fn total(&self) -> i32 {
+        self.total
+    }
+}
+
+/// The outcome of rolling several dice of the same size together.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoolResult {
+    /// The number of sides on each die.
+    sides: DieSize,
+    /// Every face rolled.
+    faces: Vec<u16>,
+    /// For each face, the face it replaced if that die was rerolled.
+    rerolled_from: Vec<Option<u16>>,
+    /// The faces which count towards the total.
+    kept: Vec<u16>,
+    /// The number added to the sum of the kept faces.
+    modifier: i32,
+    /// The final value of the roll.
+    total: i32,
+}
+
+impl PoolResult {
+    /// Total up a pool from its faces.
+    ///
+    /// * `sides` - The number of sides on each die.
+    /// * `faces` - Every face rolled.
+    /// * `kept` - The faces which count towards the total.
+    /// * `modifier` - The number to add to the sum of the kept faces.
+    fn new(sides: DieSize, faces: Vec<u16>, kept: Vec<u16>, modifier: i32) -> PoolResult {
+        let total: i32 = kept.iter().map(|&face| face as i32).sum::<i32>() + modifier;
+        let rerolled_from: Vec<Option<u16>> = vec![None; faces.len()];
+        PoolResult {sides, faces, rerolled_from, kept, modifier, total}
+    }
+
+    /// The number of sides on each die.
+    pub fn sides(&self) -> DieSize {
+        self.sides
+    }
+
+    /// Every face rolled.
+    pub fn faces(&self) -> &[u16] {
+        &self.faces
+    }
+
+    /// For each face, the face it replaced if that die was rerolled.
+    pub fn rerolled_from(&self) -> &[Option<u16>] {
+        &self.rerolled_from
+    }
+
+    /// The faces which count towards the total.
+    pub fn kept(&self) -> &[u16] {
+        &self.kept
+    }
+
+    /// The number added to the sum of the kept faces.
+    pub fn modifier(&self) -> i32 {
+        self.modifier
+    }
+
+    /// The final value of the roll.
+    pub fn total(&self) -> i32 {
+        self.total
+    }
+}
+
+/// The outcome of a damage roll.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DamageResult {
+    /// The dice rolled for each group, in the order given.
+    groups: Vec<PoolResult>,
+    /// The number added once to the sum of every group.
+    modifier: i32,
+    /// Whether the dice were doubled for a critical hit.
+    critical: bool,
+    /// The final value of the roll.
+    total: i32,
+}
+
+impl DamageResult {
+    /// The dice rolled for each group, in the order given.
+    pub fn groups(&self) -> &[PoolResult] {
+        &self.groups
+    }
+
+    /// The number added once to the sum of every group.
+    pub fn modifier(&self) -> i32 {
+        self.modifier
+    }
+
+    /// Whether the dice were doubled for a critical hit.
+    pub fn is_critical(&self) -> bool {
+        self.critical
+    }
+
+    /// The final value of the roll.
+    pub fn total(&self) -> i32 {
+        self.total
+    }
+}
+
+/// One face of a Fate (or Fudge) die.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FateFace {
+    /// Counts as -1.
+    Minus,
+    /// Counts as 0.
+    Blank,
+    /// Counts as +1.
+    Plus,
+}
+
+impl FateFace {
+    /// The amount the face adds to a roll.
+    pub fn value(&self) -> i32 {
+        match self {
+            FateFace::Minus => -1,
+            FateFace::Blank => 0,
+            FateFace::Plus => 1,
+        }
+    }
+}
+
+impl std::fmt::Display for FateFace {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FateFace::Minus => write!(f, "-"),
+            FateFace::Blank => write!(f, " "),
+            FateFace::Plus => write!(f, "+"),
+        }
+    }
+}
+
+/// The outcome of a Fate roll of four dF.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FateResult {
+    /// The four faces rolled.
+    faces: [FateFace; 4],
+    /// The number added to the faces.
+    modifier: i32,
+    /// The final value of the roll.
+    total: i32,
+}
+
+impl FateResult {
+    /// The four faces rolled.
+    pub fn faces(&self) -> &[FateFace; 4] {
+        &self.faces
+    }
+
+    /// The number added to the faces.
+    pub fn modifier(&self) -> i32 {
+        self.modifier
+    }
+
+    /// The final value of the roll, from -4 to +4 before the modifier.
+    pub fn total(&self) -> i32 {
+        self.total
+    }
+}
+
+/// When the dice of a pool are rerolled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RerollPolicy {
+    /// Keep every face as rolled.
+    #[default]
+    Never,
+    /// Reroll each die showing less than this once, keeping the new
+    /// face even if it is also low. Great Weapon Fighting is
+    /// `OnceBelow(3)`.
+    OnceBelow(u16),
+}
+
+/// The most times a die may explode when no other cap is given.
+pub const DEFAULT_EXPLOSION_CAP: u8 = 10;
+
+impl HistoryEntry {
+    /// Describe a roll of several dice made without advantage.
+    ///
+    /// * `sides` - The number of sides on each die.
+    /// * `faces` - Every face rolled.
+    /// * `modifier` - The number added to the roll.
+    /// * `total` - The final value of the roll.
+    fn pool(sides: DieSize, faces: &[u16], modifier: i32, total: i32) -> HistoryEntry {
+        HistoryEntry {sides, faces: faces.to_vec(), advantage: Advantage::None, modifier, total, reroll: false}
+    }
+}
+
+/// An extra die added to a roll by some effect, such as guidance.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bonus {
+    /// The name of the effect granting the die.
+    pub label: String,
+    /// The number of sides on the die.
+    pub sides: DieSize,
+}
+
+impl Bonus {
+    /// Describe an extra die.
+    ///
+    /// * `label` - The name of the effect granting the die.
+    /// * `sides` - The number of sides on the die.
+    pub fn new(label: &str, sides: DieSize) -> Bonus {
+        Bonus {label: String::from(label), sides}
+    }
+}
+
+/// An extra die after it has been rolled.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BonusRoll {
+    /// The name of the effect granting the die.
+    pub label: String,
+    /// The number of sides on the die.
+    pub sides: DieSize,
+    /// The face that came up.
+    pub face: u16,
+}
+
+/// The parameters of one roll in a batch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RollSpec {
+    /// The number of sides on the die.
+    pub sides: DieSize,
+    /// The number to add to the roll.
+    pub modifier: i32,
+    /// The advantage level to apply.
+    pub advantage: Advantage,
+    /// Any additional rules for the roll.
+    pub options: RollOptions,
+}
+
+impl RollSpec {
+    /// Describe an ordinary roll.
+    ///
+    /// * `sides` - The number of sides on the die.
+    /// * `modifier` - The number to add to the roll.
+    /// * `advantage` - The advantage level to apply.
+    pub fn new(sides: DieSize, modifier: i32, advantage: Advantage) -> RollSpec {
+        RollSpec {sides, modifier, advantage, options: RollOptions::default()}
+    }
+
+    /// The same roll, under additional rules.
+    ///
+    /// * `options` - The rules for the roll.
+    pub fn with_options(self, options: RollOptions) -> RollSpec {
+        RollSpec {options, ..self}
+    }
+}
+
+/// Limits on the total of a roll, applied after every modifier.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Bounds {
+    /// The lowest total a roll may come to.
+    pub min: Option<i32>,
+    /// The highest total a roll may come to.
+    pub max: Option<i32>,
+}
+
+impl Bounds {
+    /// Fit a total within the bounds.
+    ///
+    /// * `total` - The total to fit.
+    pub fn clamp(&self, total: i32) -> i32 {
+        let raised: i32 = match self.min {
+            Some(min) => total.max(min),
+            None => total,
+        };
+        match self.max {
+            Some(max) => raised.min(max),
+            None => raised,
+        }
+    }
+}
+
+/// Optional rules which change how a roll is made.
+///
+/// The default options make an ordinary roll.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RollOptions {
+    /// Reroll a natural 1 once, keeping the new face (Halfling Luck).
+    pub reroll_ones: bool,
+    /// Treat any face below this value as this value, before the
+    /// modifier is added (Reliable Talent).
+    pub floor: Option<u16>,
+    /// Extra dice to roll once each and add to the total, however
+    /// many dice the advantage level calls for.
+    pub bonuses: Vec<Bonus>,
+    /// Limits on the final total. Automatic failures ignore these.
+    pub bounds: Bounds,
+    /// What the roll is for, such as "Perception check for Aria", to
+    /// show in the log.
+    pub label: Option<String>,
+}
+
+/// A source of dice rolls.
+///
+/// `PC` rolls through this trait rather than through `Dice`
+/// directly, so that a scripted roller can stand in for real
+/// randomness.
+pub trait Roller {
+    /// Roll flat, with neither advantage or disadvantage.
+    ///
+    /// Generally, Roller::d() should be used instead.
+    ///
+    /// * `d` -  The number of sides on the die.
+    /// * `modifier` - The number to add to the roll.
+    fn d_flat(&self, d: DieSize, modifier: i32) -> RollResult;
+
+    /// Roll several dice of the same size and return their faces.
+    ///
+    /// Nothing is logged; callers report the roll as a whole.
+    /// Implementations should roll the whole set at once where
+    /// they can.
+    ///
+    /// * `count` - The number of dice to roll.
+    /// * `sides` - The number of sides on each die.
+    fn faces(&self, count: u8, sides: DieSize) -> Vec<u16> {
+        (0..count).map(|_| self.d_flat(sides, 0).natural).collect()
+    }
+
+    /// Note a completed roll.
+    ///
+    /// Every roll other than a bare `d_flat` passes through here
+    /// once. Rollers which keep no records can ignore it.
+    ///
+    /// * `entry` - A description of the roll.
+    fn record(&self, _entry: HistoryEntry) {}
+
+    /// Roll a die with the specified advantage level.
+    ///
+    /// Two dice are rolled with advantage or disadvantage, and the
+    /// higher or lower kept; three with super-advantage. An automatic failure rolls no dice. The
+    /// natural face reported is that of the kept die.
+    ///
+    /// * `d` - The number of sides on the die.
+    /// * `modifier` - The number to add to the roll.
+    /// * `advantage` - The advantage level to apply.
+    fn d(&self, d: DieSize, modifier: i32, advantage: Advantage) -> RollResult {
+        self.d_with_options(d, modifier, advantage, &RollOptions::default())
+    }
+
+    /// Roll a die with the specified advantage level and options.
+    ///
+    /// Panics if the total overflows; see `try_d_with_options`.
+    ///
+    /// Rerolls apply to each die individually, before the higher
+    /// or lower die is chosen. A floor applies to the kept die, and
+    /// never rescues an automatic failure.
+    ///
+    /// * `d` - The number of sides on the die.
+    /// * `modifier` - The number to add to the roll.
+    /// * `advantage` - The advantage level to apply.
+    /// * `options` - Any additional rules for the roll.
+    fn d_with_options(&self, d: DieSize, modifier: i32, advantage: Advantage, options: &RollOptions) -> RollResult {
+        roll_with_options(self, d, modifier, advantage, options)
+    }
+
+    /// Make several rolls together.
+    ///
+    /// The results are the same as making each roll with
+    /// `d_with_options` in turn, but are logged as one group, and
+    /// rollers which can make the whole batch at once do so.
+    ///
+    /// * `specs` - The rolls to make, in order.
+    fn d_many(&self, specs: &[RollSpec]) -> Vec<RollResult> {
+        let results: Vec<RollResult> = specs.iter().map(|spec| self.d_with_options(spec.sides, spec.modifier, spec.advantage, &spec.options)).collect();
+        log_batch(&results);
+        results
+    }
+
+    /// Make several rolls together, refusing the batch if any roll is
+    /// invalid. No dice are rolled unless every roll can be made.
+    ///
+    /// * `specs` - The rolls to make, in order.
+    fn try_d_many(&self, specs: &[RollSpec]) -> Result<Vec<RollResult>, DiceError> {
+        for spec in specs {
+            validate_roll(spec.sides, spec.modifier, &spec.options)?;
+        }
+        Ok(self.d_many(specs))
+    }
+
+    /// Roll a die with the specified advantage level, refusing invalid rolls.
+    ///
+    /// Unlike `d`, this does not panic on a modifier which would
+    /// overflow the total.
+    ///
+    /// * `d` - The number of sides on the die.
+    /// * `modifier` - The number to add to the roll.
+    /// * `advantage` - The advantage level to apply.
+    fn try_d(&self, d: DieSize, modifier: i32, advantage: Advantage) -> Result<RollResult, DiceError> {
+        self.try_d_with_options(d, modifier, advantage, &RollOptions::default())
+    }
+
+    /// Roll a die with advantage and options, refusing invalid rolls.
+    ///
+    /// * `d` - The number of sides on the die.
+    /// * `modifier` - The number to add to the roll.
+    /// * `advantage` - The advantage level to apply.
+    /// * `options` - Any additional rules for the roll.
+    fn try_d_with_options(&self, d: DieSize, modifier: i32, advantage: Advantage, options: &RollOptions) -> Result<RollResult, DiceError> {
+        validate_roll(d, modifier, options)?;
+        Ok(self.d_with_options(d, modifier, advantage, options))
+    }
+
+    /// Roll a die with extra dice added by effects such as guidance.
+    ///
+    /// Each bonus die is rolled once, even with advantage.
+    ///
+    /// * `d` - The number of sides on the die.
+    /// * `modifier` - The number to add to the roll.
+    /// * `advantage` - The advantage level to apply.
+    /// * `bonuses` - The extra dice to add.
+    fn d_with_bonuses(&self, d: DieSize, modifier: i32, advantage: Advantage, bonuses: &[Bonus]) -> RollResult {
+        let options: RollOptions = RollOptions {bonuses: bonuses.to_vec(), ..Default::default()};
+        self.d_with_options(d, modifier, advantage, &options)
+    }
+
+    /// Roll a die against a difficulty class.
+    ///
+    /// * `sides` - The number of sides on the die.
+    /// * `modifier` - The number to add to the roll.
+    /// * `advantage` - The advantage level to apply.
+    /// * `dc` - The total needed to succeed.
+    fn check_against_dc(&self, sides: DieSize, modifier: i32, advantage: Advantage, dc: i32) -> CheckOutcome {
+        let result: RollResult = self.d(sides, modifier, advantage);
+        let outcome: CheckOutcome = CheckOutcome::of(&result, dc);
+        log::info!("Checking {} against DC {}: {:?}", result.total, dc, outcome);
+        outcome
+    }
+
+    /// Roll a die which can be rerolled once seen, as with the Lucky feat.
+    ///
+    /// * `d` - The number of sides on the die.
+    /// * `modifier` - The number to add to the roll.
+    /// * `advantage` - The advantage level to apply.
+    fn d_pending(&self, d: DieSize, modifier: i32, advantage: Advantage) -> PendingRoll<'_>
+    where Self: Sized {
+        PendingRoll::roll(self, d, modifier, advantage, &RollOptions::default())
+    }
+
+    /// Roll percentile dice for a result from 1 to 100.
+    ///
+    /// A tens die (00 to 90) and a ones die (0 to 9) are rolled and
+    /// added, with 00 and 0 together counting as 100.
+    fn percentile(&self) -> RollResult {
+        // A d10's 10 face reads as 0 on both dice.
+        let tens_die: RollResult = self.d_flat(DieSize::D10, 0);
+        let ones_die: RollResult = self.d_flat(DieSize::D10, 0);
+        let tens: u8 = (tens_die.natural % 10 * 10) as u8;
+        let ones: u8 = (ones_die.natural % 10) as u8;
+        let natural: u16 = if tens + ones == 0 { 100 } else { (tens + ones) as u16 };
+        let mut result: RollResult = RollResult::new(DieSize::D100, natural, 0);
+        result.percentile_dice = Some(PercentileDice {tens, ones});
+        result.average = tens_die.average && ones_die.average;
+        log::info!("Rolling percentile: {:02} + {} = {}", tens, ones, natural);
+        self.record(HistoryEntry::pool(DieSize::D100, &[natural], 0, result.total));
+        result
+    }
+
+    /// Roll four Fate dice (4dF), each showing -1, 0, or +1.
+    ///
+    /// Fate rolls have no notion of advantage, so this stands apart
+    /// from `d`. Each dF is rolled as a d3, with 1 as minus, 2 as
+    /// blank, and 3 as plus.
+    ///
+    /// * `modifier` - The number to add to the roll.
+    fn fate(&self, modifier: i32) -> FateResult {
+        let rolled: Vec<u16> = self.faces(4, DieSize::D3);
+        let mut faces: [FateFace; 4] = [FateFace::Blank; 4];
+        for (face, &roll) in faces.iter_mut().zip(rolled.iter()) {
+            *face = match roll {
+                1 => FateFace::Minus,
+                2 => FateFace::Blank,
+                _ => FateFace::Plus,
+            };
+        }
+        let total: i32 = faces.iter().map(FateFace::value).sum::<i32>() + modifier;
+        let shown: String = faces.iter().map(|face| format!("[{}]", face)).collect();
+        log::info!("Rolling 4dF + {}: {} = {}", modifier, shown, total);
+        self.record(HistoryEntry::pool(DieSize::D3, &rolled, modifier, total));
+        FateResult {faces, modifier, total}
+    }
+
+    /// Roll an exploding die.
+    ///
+    /// Whenever the die shows its highest face, it is rolled again
+    /// and the new face is added, up to `DEFAULT_EXPLOSION_CAP` times.
+    ///
+    /// * `d` - The number of sides on the die.
+    /// * `modifier` - The number to add to the roll.
+    fn d_exploding(&self, d: DieSize, modifier: i32) -> ExplodingResult {
+        self.d_exploding_capped(d, modifier, DEFAULT_EXPLOSION_CAP)
+    }
+
+    /// Roll an exploding die with a limit on the number of explosions.
+    ///
+    /// * `d` - The number of sides on the die.
+    /// * `modifier` - The number to add to the roll.
+    /// * `cap` - The most times the die may explode.
+    fn d_exploding_capped(&self, d: DieSize, modifier: i32, cap: u8) -> ExplodingResult {
+        let mut faces: Vec<u16> = vec![self.d_flat(d, 0).natural];
+        while faces[faces.len() - 1] == d.sides() && faces.len() <= cap as usize {
+            faces.push(self.d_flat(d, 0).natural);
+        }
+        let total: i32 = faces.iter().map(|&face| face as i32).sum::<i32>() + modifier;
+        let result: ExplodingResult = ExplodingResult {sides: d, faces, modifier, total};
+        self.record(HistoryEntry::pool(d, &result.faces, modifier, total));
+        log::info!("Exploding 1{} + {} = {} ({} explosions)", d, modifier, total, result.explosions());
+        result
+    }
+
+    /// Roll several dice of the same size and add them together.
+    ///
+    /// * `count` - The number of dice to roll.
+    /// * `sides` - The number of sides on each die.
+    /// * `modifier` - The number to add to the sum of the dice.
+    fn pool(&self, count: u8, sides: DieSize, modifier: i32) -> PoolResult {
+        self.pool_with_reroll(count, sides, modifier, RerollPolicy::Never)
+    }
+
+    /// Roll several dice of the same size, rerolling low dice, and add them together.
+    ///
+    /// * `count` - The number of dice to roll.
+    /// * `sides` - The number of sides on each die.
+    /// * `modifier` - The number to add to the sum of the dice.
+    /// * `reroll` - Which dice to reroll.
+    fn pool_with_reroll(&self, count: u8, sides: DieSize, modifier: i32, reroll: RerollPolicy) -> PoolResult {
+        let mut faces: Vec<u16> = self.faces(count, sides);
+        let mut rerolled_from: Vec<Option<u16>> = vec![None; faces.len()];
+        if let RerollPolicy::OnceBelow(threshold) = reroll {
+            let low: Vec<usize> = (0..faces.len()).filter(|&index| faces[index] < threshold).collect();
+            let rerolls: Vec<u16> = self.faces(low.len() as u8, sides);
+            for (&index, &face) in low.iter().zip(rerolls.iter()) {
+                rerolled_from[index] = Some(faces[index]);
+                faces[index] = face;
+            }
+        }
+        let mut result: PoolResult = PoolResult::new(sides, faces.clone(), faces, modifier);
+        result.rerolled_from = rerolled_from;
+        self.record(HistoryEntry::pool(sides, &result.faces, modifier, result.total));
+        let shown: Vec<String> = result.faces.iter().zip(result.rerolled_from.iter()).map(|(face, from)| match from {
+            Some(from) => format!("{}→{}", from, face),
+            None => face.to_string(),
+        }).collect();
+        log::info!("Rolling {}{} + {}: [{}] = {}", count, sides, modifier, shown.join(", "), result.total);
+        result
+    }
+
+    /// Roll damage from groups of dice plus a modifier.
+    ///
+    /// A critical hit doubles the number of dice in each group, but
+    /// the modifier is still added only once.
+    ///
+    /// * `groups` - The number and size of dice in each group, such as `(2, DieSize::D6)`.
+    /// * `modifier` - The number to add to the sum of the dice.
+    /// * `critical` - Whether the attack was a critical hit.
+    fn damage(&self, groups: &[(u8, DieSize)], modifier: i32, critical: bool) -> DamageResult {
+        self.damage_with_reroll(groups, modifier, critical, RerollPolicy::Never)
+    }
+
+    /// Roll damage, rerolling low dice as with Great Weapon Fighting.
+    ///
+    /// * `groups` - The number and size of dice in each group, such as `(2, DieSize::D6)`.
+    /// * `modifier` - The number to add to the sum of the dice.
+    /// * `critical` - Whether the attack was a critical hit.
+    /// * `reroll` - Which dice to reroll.
+    fn damage_with_reroll(&self, groups: &[(u8, DieSize)], modifier: i32, critical: bool, reroll: RerollPolicy) -> DamageResult {
+        let multiplier: u8 = if critical { 2 } else { 1 };
+        let pools: Vec<PoolResult> = groups.iter()
+            .map(|&(count, sides)| self.pool_with_reroll(count.saturating_mul(multiplier), sides, 0, reroll))
+            .collect();
+        let total: i32 = pools.iter().map(|pool| pool.total).sum::<i32>() + modifier;
+        log::info!("Rolling {}damage + {} = {}", if critical { "critical " } else { "" }, modifier, total);
+        DamageResult {groups: pools, modifier, critical, total}
+    }
+
+    /// Roll several dice and keep the highest few.
+    ///
+    /// For example, `keep(4, DieSize::D6, 3)` is the usual 4d6-drop-lowest
+    /// method of generating an ability score. The faces are reported
+    /// sorted from highest to lowest.
+    ///
+    /// * `count` - The number of dice to roll.
+    /// * `sides` - The number of sides on each die.
+    /// * `keep_highest` - The number of dice to keep.
+    fn keep(&self, count: u8, sides: DieSize, keep_highest: u8) -> PoolResult {
+        let mut faces: Vec<u16> = self.faces(count, sides);
+        faces.sort_unstable_by(|a, b| b.cmp(a));
+        let kept: Vec<u16> = faces.iter().copied().take(keep_highest as usize).collect();
+        let result: PoolResult = PoolResult::new(sides, faces, kept, 0);
+        self.record(HistoryEntry::pool(sides, &result.faces, 0, result.total));
+        log::info!("Rolling {}{} keep highest {}: {:?} = {}", count, sides, keep_highest, result.faces, result.total);
+        result
+    }
+
+    /// Roll several dice and keep the lowest few.
+    ///
+    /// The faces are reported sorted from lowest to highest.
+    ///
+    /// * `count` - The number of dice to roll.
+    /// * `sides` - The number of sides on each die.
+    /// * `keep_lowest` - The number of dice to keep.
+    fn keep_lowest(&self, count: u8, sides: DieSize, keep_lowest: u8) -> PoolResult {
+        let mut faces: Vec<u16> = self.faces(count, sides);
+        faces.sort_unstable();
+        let kept: Vec<u16> = faces.iter().copied().take(keep_lowest as usize).collect();
+        let result: PoolResult = PoolResult::new(sides, faces, kept, 0);
+        self.record(HistoryEntry::pool(sides, &result.faces, 0, result.total));
+        log::info!("Rolling {}{} keep lowest {}: {:?} = {}", count, sides, keep_lowest, result.faces, result.total);
+        result
+    }
+}
+
+/// A d20 roll which has been seen but not yet settled.
+///
+/// The Lucky feat lets a player look at a roll and then spend a luck
+/// point to roll another die and keep whichever they prefer. Where
+/// the roll is tied to a luck-point counter, rerolling spends from
+/// it; otherwise rerolls are unlimited.
+///
+/// Bardic Inspiration likewise lets a player add a die once they have
+/// seen the roll, where the roll is tied to an inspiration die.
+pub struct PendingRoll<'p> {
+    /// The roller to draw a reroll from.
+    roller: &'p dyn Roller,
+    /// The roll as it stands.
+    result: RollResult,
+    /// The luck points available to spend on a reroll, if limited.
+    luck_points: Option<&'p mut u8>,
+    /// The inspiration die available to add, if the roll is tied to one.
+    inspiration_die: Option<&'p mut Option<DieSize>>,
+    /// The rules the roll was made under, which also apply to a reroll.
+    options: RollOptions,
+}
+
+impl<'p> PendingRoll<'p> {
+    /// Make a roll which can be rerolled before it is settled.
+    ///
+    /// * `roller` - The roller to draw faces from.
+    /// * `d` - The number of sides on the die.
+    /// * `modifier` - The number to add to the roll.
+    /// * `advantage` - The advantage level to apply.
+    /// * `options` - Any additional rules for the roll.
+    pub fn roll(roller: &'p dyn Roller, d: DieSize, modifier: i32, advantage: Advantage, options: &RollOptions) -> PendingRoll<'p> {
+        let result: RollResult = roller.d_with_options(d, modifier, advantage, options);
+        PendingRoll {roller, result, luck_points: None, inspiration_die: None, options: options.clone()}
+    }
+
+    /// Tie rerolls of this roll to a luck-point counter.
+    ///
+    /// * `luck_points` - The counter to spend from.
+    pub fn with_luck_points(mut self, luck_points: &'p mut u8) -> PendingRoll<'p> {
+        self.luck_points = Some(luck_points);
+        self
+    }
+
+    /// Tie this roll to an inspiration die, which can be spent on it.
+    ///
+    /// * `inspiration_die` - The die held, which spending takes.
+    pub fn with_inspiration_die(mut self, inspiration_die: &'p mut Option<DieSize>) -> PendingRoll<'p> {
+        self.inspiration_die = Some(inspiration_die);
+        self
+    }
+
+    /// The roll as it stands.
+    pub fn result(&self) -> &RollResult {
+        &self.result
+    }
+
+    /// Spend the inspiration die the roll is tied to, adding it to the
+    /// total under `INSPIRATION_DIE_LABEL`.
+    ///
+    /// Nothing is spent if the roll failed automatically.
+    pub fn spend_inspiration(&mut self) -> Result<(), InspirationDieError> {
+        let held: &mut Option<DieSize> = self.inspiration_die.as_deref_mut().ok_or(InspirationDieError::NotHeld)?;
+        let sides: DieSize = held.ok_or(InspirationDieError::NotHeld)?;
+        if self.result.is_automatic_failure() {
+            return Err(InspirationDieError::AutomaticFailure);
+        }
+        *held = None;
+        let face: u16 = self.roller.d_flat(sides, 0).natural();
+        self.result.bonuses.push(BonusRoll {label: String::from(INSPIRATION_DIE_LABEL), sides, face});
+        // Bounds apply to the total with the die, not before it.
+        self.result.total = self.result.unclamped.take().unwrap_or(self.result.total) + face as i32;
+        apply_bounds(&mut self.result, &self.options.bounds);
+        log::info!("{} on 1{}: {}, for a total of {}", INSPIRATION_DIE_LABEL, sides, face, self.result.total);
+        Ok(())
+    }
+
+    /// Whether a luck point is available to reroll with.
+    pub fn can_reroll(&self) -> bool {
+        match &self.luck_points {
+            Some(points) => **points > 0,
+            None => true,
+        }
+    }
+
+    /// Settle the roll as it stands.
+    pub fn accept(self) -> RollResult {
+        self.result
+    }
+
+    /// Spend a luck point to roll another die and settle on one of the two.
+    ///
+    /// An automatic failure cannot be rescued, so it is settled
+    /// without spending anything. If no luck points remain, the
+    /// pending roll is handed back untouched.
+    ///
+    /// * `keep_reroll` - Given the original and the reroll, whether to keep the reroll.
+    // Handing the pending roll back whole is the point of the error.
+    #[allow(clippy::result_large_err)]
+    pub fn reroll_and_choose<F>(self, keep_reroll: F) -> Result<RollResult, PendingRoll<'p>>
+    where F: FnOnce(&RollResult, &RollResult) -> bool {
+        if self.result.is_automatic_failure() {
+            return Ok(self.result);
+        }
+        if !self.can_reroll() {
+            return Err(self);
+        }
+        if let Some(points) = self.luck_points {
+            *points -= 1;
+        }
+        let mut reroll: RollResult = roll_die(self.roller, self.result.sides, self.result.modifier, &self.options);
+        apply_floor(&mut reroll, self.options.floor);
+        reroll.advantage = self.result.advantage;
+        // Bonus dice were added to the roll as a whole, so they stay.
+        reroll.total += self.result.bonuses.iter().map(|bonus| bonus.face as i32).sum::<i32>();
+        reroll.bonuses = self.result.bonuses.clone();
+        apply_bounds(&mut reroll, &self.options.bounds);
+        if keep_reroll(&self.result, &reroll) {
+            log::info!("Lucky reroll on 1{}: keeping {} over {}", reroll.sides, reroll.total, self.result.total);
+            Ok(reroll)
+        } else {
+            log::info!("Lucky reroll on 1{}: keeping {} over {}", reroll.sides, self.result.total, reroll.total);
+            Ok(self.result)
+        }
+    }
+}
+
+/// How often each face of a die has come up.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FaceStats {
+    /// The die counted.
+    sides: DieSize,
+    /// The number of times each face came up, from 1 up.
+    counts: Vec<u64>,
+}
+
+impl FaceStats {
+    /// The die counted.
+    pub fn sides(&self) -> DieSize {
+        self.sides
+    }
+
+    /// The number of times each face came up, from 1 up.
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// The number of dice counted.
+    pub fn rolls(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Pearson's chi-square statistic for how far the counts stray
+    /// from a fair die.
+    ///
+    /// A fair die scores around one less than its number of sides;
+    /// much higher suggests a cursed one. This is 0.0 before any
+    /// dice are counted.
+    pub fn chi_square(&self) -> f64 {
+        let rolls: u64 = self.rolls();
+        if rolls == 0 {
+            return 0.0;
+        }
+        let expected: f64 = rolls as f64 / self.counts.len() as f64;
+        self.counts.iter().map(|&count| (count as f64 - expected).powi(2) / expected).sum()
+    }
+}
+
+/// How often each total came up over many simulated rolls.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Histogram {
+    /// The total counted by the first bucket.
+    lowest: i32,
+    /// The number of rolls which came to each total, from `lowest` up.
+    buckets: Vec<u64>,
+    /// The number of rolls which failed automatically.
+    failures: u64,
+}
+
+impl Histogram {
+    /// Make an empty histogram for totals in the given range.
+    ///
+    /// * `lowest` - The lowest possible total.
+    /// * `highest` - The highest possible total.
+    fn new(lowest: i32, highest: i32) -> Histogram {
+        Histogram {lowest, buckets: vec![0; (highest - lowest + 1) as usize], failures: 0}
+    }
+
+    /// The number of rolls which came to the given total.
+    ///
+    /// * `total` - The total to look up.
+    pub fn count(&self, total: i32) -> u64 {
+        match usize::try_from(total - self.lowest) {
+            Ok(index) => self.buckets.get(index).copied().unwrap_or(0),
+            Err(_) => 0,
+        }
+    }
+
+    /// Every total that came up at least once, with its count, lowest first.
+    pub fn counts(&self) -> Vec<(i32, u64)> {
+        (self.lowest..).zip(self.buckets.iter().copied()).filter(|&(_, count)| count > 0).collect()
+    }
+
+    /// The number of rolls which failed automatically.
+    pub fn failures(&self) -> u64 {
+        self.failures
+    }
+
+    /// The number of rolls simulated.
+    pub fn rolls(&self) -> u64 {
+        self.buckets.iter().sum::<u64>() + self.failures
+    }
+
+    /// The average total, ignoring automatic failures.
+    ///
+    /// This is 0.0 if every roll failed.
+    pub fn mean(&self) -> f64 {
+        let counted: u64 = self.buckets.iter().sum();
+        if counted == 0 {
+            return 0.0;
+        }
+        let sum: f64 = (self.lowest..).zip(self.buckets.iter()).map(|(total, &count)| total as f64 * count as f64).sum();
+        sum / counted as f64
+    }
+
+    /// The lowest total which at least `p` percent of rolls came to or below.
+    ///
+    /// Automatic failures are ignored. Returns `None` if every roll failed.
+    ///
+    /// * `p` - The percentile, from 0 to 100.
+    pub fn percentile(&self, p: f64) -> Option<i32> {
+        let counted: u64 = self.buckets.iter().sum();
+        if counted == 0 {
+            return None;
+        }
+        let target: f64 = p.clamp(0.0, 100.0) / 100.0 * counted as f64;
+        let mut seen: u64 = 0;
+        for (total, &count) in (self.lowest..).zip(self.buckets.iter()) {
+            seen += count;
+            if count > 0 && seen as f64 >= target {
+                return Some(total);
+            }
+        }
+        None
+    }
+
+    /// The fraction of all rolls which met or beat a DC.
+    ///
+    /// Automatic failures count against this.
+    ///
+    /// * `dc` - The total needed to succeed.
+    pub fn at_least(&self, dc: i32) -> f64 {
+        let rolls: u64 = self.rolls();
+        if rolls == 0 {
+            return 0.0;
+        }
+        let successes: u64 = (self.lowest..).zip(self.buckets.iter()).filter(|&(total, _)| total >= dc).map(|(_, &count)| count).sum();
+        successes as f64 / rolls as f64
+    }
+}
+
+/// One term of a roll expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Term {
+    /// A group of dice of one size, such as 2d4.
+    Dice {
+        /// The number of dice to roll.
+        count: u8,
+        /// The number of sides on each die.
+        sides: DieSize,
+    },
+    /// A fixed number.
+    Constant(i32),
+    /// A term to subtract rather than add.
+    Negated(Box<Term>),
+}
+
+impl Term {
+    /// Roll the term, returning the faces rolled and its value.
+    ///
+    /// * `roller` - The roller to draw faces from.
+    fn eval(&self, roller: &dyn Roller) -> (Vec<u16>, i32) {
+        match self {
+            Term::Dice {count, sides} => {
+                let pool: PoolResult = roller.pool(*count, *sides, 0);
+                (pool.faces, pool.total)
+            },
+            Term::Constant(value) => (Vec::new(), *value),
+            Term::Negated(term) => {
+                let (faces, value) = term.eval(roller);
+                (faces, -value)
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for Term {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Term::Dice {count, sides} => write!(f, "{}{}", count, sides),
+            Term::Constant(value) => write!(f, "{}", value),
+            Term::Negated(term) => write!(f, "-{}", term),
+        }
+    }
+}
+
+impl FromStr for Term {
+    type Err = DiceError;
+
+    /// Parse a single unsigned term, such as "2d4", "d20", or "3".
+    fn from_str(input: &str) -> Result<Term, Self::Err> {
+        let bad = || DiceError::BadExpression(String::from(input));
+        match input.split_once('d') {
+            Some((count, sides)) => {
+                let count: u8 = if count.is_empty() { 1 } else { count.parse().map_err(|_| bad())? };
+                let sides: DieSize = DieSize::try_new(sides.parse().map_err(|_| bad())?)?;
+                Ok(Term::Dice {count, sides})
+            },
+            None => Ok(Term::Constant(input.parse().map_err(|_| bad())?)),
+        }
+    }
+}
+
+/// A sum of dice groups and constants, such as "1d20 + 2d4 - 1".
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Expression {
+    /// The terms to add together, in order.
+    terms: Vec<Term>,
+}
+
+impl Expression {
+    /// Build an expression from its terms.
+    ///
+    /// * `terms` - The terms to add together, in order.
+    pub fn new(terms: Vec<Term>) -> Expression {
+        Expression {terms}
+    }
+
+    /// The terms to add together, in order.
+    pub fn terms(&self) -> &[Term] {
+        &self.terms
+    }
+
+    /// Roll every term and add them up.
+    ///
+    /// Each group of dice is rolled as a single pool.
+    ///
+    /// * `roller` - The roller to draw faces from.
+    pub fn eval(&self, roller: &dyn Roller) -> ExpressionResult {
+        let mut results: Vec<TermResult> = Vec::new();
+        let mut total: i64 = 0;
+        for term in &self.terms {
+            let (faces, value) = term.eval(roller);
+            total += value as i64;
+            results.push(TermResult {term: term.clone(), faces, value});
+        }
+        log::info!("Evaluating {} = {}", self, total);
+        ExpressionResult {terms: results, total}
+    }
+}
+
+impl std::fmt::Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (index, term) in self.terms.iter().enumerate() {
+            match (index, term) {
+                (0, term) => write!(f, "{}", term)?,
+                (_, Term::Negated(term)) => write!(f, " - {}", term)?,
+                (_, term) => write!(f, " + {}", term)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Expression {
+    type Err = DiceError;
+
+    /// Parse terms joined by "+" and "-", ignoring whitespace.
+    fn from_str(input: &str) -> Result<Expression, Self::Err> {
+        let compact: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut terms: Vec<Term> = Vec::new();
+        let mut negated: bool = false;
+        let mut current: String = String::new();
+        for c in compact.chars().chain(std::iter::once('+')) {
+            if c == '+' || c == '-' {
+                if current.is_empty() {
+                    // Only a leading sign may stand without a term before it.
+                    if !terms.is_empty() || negated || c == '+' {
+                        return Err(DiceError::BadExpression(String::from(input)));
+                    }
+                } else {
+                    let term: Term = current.parse()?;
+                    terms.push(if negated { Term::Negated(Box::new(term)) } else { term });
+                    current.clear();
+                }
+                negated = c == '-';
+            } else {
+                current.push(c);
+            }
+        }
+        Ok(Expression {terms})
+    }
+}
+
+/// One term of an expression after it has been rolled.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TermResult {
+    /// The term that was rolled.
+    pub term: Term,
+    /// The faces rolled for the term, if it has dice.
+    pub faces: Vec<u16>,
+    /// The term's contribution to the total, negative if subtracted.
+    pub value: i32,
+}
+
+/// The outcome of rolling an expression, term by term.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpressionResult {
+    /// Each term's result, in order.
+    terms: Vec<TermResult>,
+    /// The sum of every term.
+    total: i64,
+}
+
+impl ExpressionResult {
+    /// Each term's result, in order.
+    pub fn terms(&self) -> &[TermResult] {
+        &self.terms
+    }
+
+    /// The sum of every term.
+    pub fn total(&self) -> i64 {
+        self.total
+    }
+}
+
+/// Calculate the chance that a roll meets or beats a target.
+///
+/// This is worked out exactly from the faces of the die rather
+/// than by sampling.
+///
+/// * `sides` - The number of sides on the die.
+/// * `modifier` - The number added to the roll.
+/// * `advantage` - The advantage level of the roll.
+/// * `dc` - The total needed to succeed.
+pub fn success_probability(sides: DieSize, modifier: i32, advantage: Advantage, dc: i32) -> f64 {
+    // The lowest face which succeeds, clamped to the faces available.
+    let faces: i32 = sides.sides() as i32;
+    let lowest_success: i32 = (dc - modifier).clamp(1, faces + 1);
+    let successes: i32 = faces + 1 - lowest_success;
+    let single: f64 = successes as f64 / faces as f64;
+    match advantage {
+        Advantage::None => single,
+        Advantage::Canceled => single,
+        Advantage::Advantage => 1.0 - (1.0 - single).powi(2),
+        Advantage::SuperAdvantage => 1.0 - (1.0 - single).powi(3),
+        Advantage::Disadvantage => single * single,
+        Advantage::Fail => 0.0,
+    }
+}
+
+/// Check that a roll can be made and its total represented.
+///
+/// * `d` - The number of sides on the die.
+/// * `modifier` - The number to add to the roll.
+/// * `options` - Any additional rules for the roll.
+fn validate_roll(d: DieSize, modifier: i32, options: &RollOptions) -> Result<(), DiceError> {
+    // The largest possible total must fit, as must the smallest.
+    let highest_faces: i32 = d.sides().max(options.floor.unwrap_or(0)) as i32
+        + options.bonuses.iter().map(|bonus| bonus.sides.sides() as i32).sum::<i32>();
+    match (modifier.checked_add(highest_faces), modifier.checked_add(1)) {
+        (Some(_), Some(_)) => Ok(()),
+        _ => Err(DiceError::Overflow),
+    }
+}
+
+/// Roll a die with advantage and options, as `Roller::d_with_options`.
+///
+/// * `roller` - The roller to draw faces from.
+/// * `d` - The number of sides on the die.
+/// * `modifier` - The number to add to the roll.
+/// * `advantage` - The advantage level to apply.
+/// * `options` - Any additional rules for the roll.
+fn roll_with_options<R: Roller + ?Sized>(roller: &R, d: DieSize, modifier: i32, advantage: Advantage, options: &RollOptions) -> RollResult {
+    // Only advantage and disadvantage need a second die, and an
+    // automatic failure needs none at all.
+    let (mut kept, faces): (RollResult, Vec<u16>) = match advantage {
+        Advantage::None | Advantage::Canceled => {
+            let roll: RollResult = roll_die(roller, d, modifier, options);
+            let faces: Vec<u16> = vec![roll.natural];
+            (roll, faces)
+        },
+        Advantage::Advantage | Advantage::Disadvantage | Advantage::SuperAdvantage => {
+            let count: usize = if advantage == Advantage::SuperAdvantage { 3 } else { 2 };
+            let mut kept: RollResult = roll_die(roller, d, modifier, options);
+            let mut kept_index: usize = 0;
+            let mut faces: Vec<u16> = vec![kept.natural];
+            // Ties keep the earlier die.
+            for index in 1..count {
+                let roll: RollResult = roll_die(roller, d, modifier, options);
+                faces.push(roll.natural);
+                let replace: bool = match advantage {
+                    Advantage::Disadvantage => roll.total < kept.total,
+                    _ => roll.total > kept.total,
+                };
+                if replace {
+                    kept = roll;
+                    kept_index = index;
+                }
+            }
+            kept.faces = faces.clone();
+            kept.kept_index = Some(kept_index);
+            (kept, faces)
+        },
+        Advantage::Fail => {
+            (RollResult {total: 0, faces: Vec::new(), kept_index: None, ..RollResult::new(d, 0, modifier)}, Vec::new())
+        },
+    };
+    if advantage != Advantage::Fail {
+        apply_floor(&mut kept, options.floor);
+        add_bonuses(roller, &mut kept, &options.bonuses);
+        apply_bounds(&mut kept, &options.bounds);
+    }
+    kept.advantage = advantage;
+    log_roll(&kept, options.label.as_deref());
+    roller.record(HistoryEntry {
+        sides: d,
+        faces,
+        advantage,
+        modifier,
+        total: kept.total,
+        reroll: false,
+    });
+    kept
+}
+
+/// Log a single roll as one entry, with every die rolled.
+///
+/// A roll with advantage logs as "1d20 adv: [17, 4] keep 17 + 3 = 20".
+///
+/// * `result` - The finished roll.
+/// * `label` - What the roll is for, if the caller said.
+fn log_roll(result: &RollResult, label: Option<&str>) {
+    let prefix: String = match label {
+        Some(label) => format!("{}: ", label),
+        None => String::new(),
+    };
+    let advantage: &str = match result.advantage {
+        Advantage::None => "",
+        Advantage::Canceled => " canceled",
+        Advantage::Advantage => " adv",
+        Advantage::Disadvantage => " dis",
+        Advantage::SuperAdvantage => " super adv",
+        Advantage::Fail => " fail",
+    };
+    if result.is_automatic_failure() {
+        log::info!("{}1{}{}: automatic failure", prefix, result.sides, advantage);
+        return;
+    }
+    let bonuses: String = result.bonuses.iter().map(|bonus| format!(" + {} {}", bonus.label, bonus.face)).collect();
+    let source: &str = if result.portent { " (portent)" } else if result.average { " (average)" } else { "" };
+    log::info!(
+        "{}1{}{}: {:?} keep {}{} + {}{} = {}",
+        prefix, result.sides, advantage, result.faces, result.natural, source, result.modifier, bonuses, result.total,
+    );
+}
+
+/// Log a batch of rolls as a single entry.
+///
+/// * `results` - The rolls in the batch.
+fn log_batch(results: &[RollResult]) {
+    let totals: Vec<i32> = results.iter().map(|result| result.total).collect();
+    log::info!("Rolled a batch of {}: {:?}", results.len(), totals);
+}
+
+/// Roll extra dice once each and add them to a roll.
+///
+/// * `roller` - The roller to draw faces from.
+/// * `result` - The roll to add to.
+/// * `bonuses` - The extra dice to add.
+fn add_bonuses<R: Roller + ?Sized>(roller: &R, result: &mut RollResult, bonuses: &[Bonus]) {
+    for bonus in bonuses {
+        let face: u16 = roller.d_flat(bonus.sides, 0).natural;
+        log::debug!("Adding {} 1{}: {}", bonus.label, bonus.sides, face);
+        result.total += face as i32;
+        result.bonuses.push(BonusRoll {label: bonus.label.clone(), sides: bonus.sides, face});
+    }
+}
+
+/// Raise a roll's face to a floor, before any bonus dice are added.
+///
+/// * `result` - The roll to raise.
+/// * `floor` - The lowest face the roll can keep, if any.
+fn apply_floor(result: &mut RollResult, floor: Option<u16>) {
+    if let Some(floor) = floor {
+        if result.natural < floor {
+            log::debug!("Raising 1{} from {} to the floor of {}", result.sides, result.natural, floor);
+            result.floored_from = Some(result.natural);
+            result.natural = floor;
+            result.total = floor as i32 + result.modifier;
+        }
+    }
+}
+
+/// Clamp a roll's total to its bounds, remembering the original.
+///
+/// * `result` - The roll to clamp.
+/// * `bounds` - The limits on the total.
+fn apply_bounds(result: &mut RollResult, bounds: &Bounds) {
+    let clamped: i32 = bounds.clamp(result.total);
+    if clamped != result.total {
+        log::debug!("Clamping a total of {} to {}", result.total, clamped);
+        result.unclamped = Some(result.total);
+        result.total = clamped;
+    }
+}
+
+/// Roll a single die, applying any per-die options.
+///
+/// * `roller` - The roller to draw faces from.
+/// * `d` - The number of sides on the die.
+/// * `modifier` - The number to add to the roll.
+/// * `options` - Any additional rules for the roll.
+fn roll_die<R: Roller + ?Sized>(roller: &R, d: DieSize, modifier: i32, options: &RollOptions) -> RollResult {
+    let first: RollResult = roller.d_flat(d, modifier);
+    if options.reroll_ones && first.natural == 1 {
+        let mut second: RollResult = roller.d_flat(d, modifier);
+        second.rerolled_from = Some(first.natural);
+        log::debug!("Rerolling a natural 1 on 1{}: {} -> {}", d, first.natural, second.natural);
+        second
+    } else {
+        first
+    }
+}
+
+impl Dice {
+    /// Generate a new set of dice, seeded from the operating system.
+    pub fn new() -> Dice {
+        Dice::with_backend(RngBackend::Entropy(StdRng::from_entropy()))
+    }
+
+    /// Generate a set of dice which rolls the same faces whenever it
+    /// is given the same seed, for replaying a session.
+    ///
+    /// * `seed` - The seed for the generator.
+    pub fn seeded(seed: u64) -> Dice {
+        Dice::with_backend(RngBackend::Seeded(seed, ChaCha20Rng::seed_from_u64(seed)))
+    }
+
+    /// Generate a set of dice which rolls the given faces in order.
+    ///
+    /// Rolling after the faces have run out panics.
+    /// * `faces` - The natural die faces to return.
+    pub fn scripted(faces: Vec<u16>) -> Dice {
+        Dice::with_backend(RngBackend::Scripted(faces.into()))
+    }
+
+    /// Generate a set of dice drawing faces from a backend.
+    /// * `backend` - Where the dice get their faces from.
+    fn with_backend(backend: RngBackend) -> Dice {
+        log::info!("Rolling dice with {}", backend);
+        Dice {
+            rng: Mutex::new(backend),
+            history: Mutex::new(VecDeque::new()),
+            portents: Mutex::new(VecDeque::new()),
+            observers: Mutex::new(Vec::new()),
+            rerolls: Mutex::new(0),
+            last_d20: Mutex::new(None),
+            mode: Mutex::new(RollMode::Random),
+            stats: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// How often each face of a die has come up this session.
+    ///
+    /// Every die rolled counts, including both dice of an advantage
+    /// roll and every die of a pool. Foretold and average faces are
+    /// not rolled, so they are not counted, and neither are dice
+    /// larger than `STATS_MAX_SIDES`.
+    ///
+    /// * `sides` - The die to look up.
+    pub fn face_stats(&self, sides: DieSize) -> FaceStats {
+        let counts: Vec<u64> = match lock(&self.stats).get(&sides) {
+            Some(counts) => counts.clone(),
+            None => vec![0; sides.sides() as usize],
+        };
+        FaceStats {sides, counts}
+    }
+
+    /// Forget every face counted so far.
+    pub fn reset_stats(&self) {
+        lock(&self.stats).clear();
+    }
+
+    /// Choose whether later rolls are random or take the average.
+    ///
+    /// * `mode` - How the dice decide their faces.
+    pub fn set_mode(&self, mode: RollMode) {
+        *lock(&self.mode) = mode;
+    }
+
+    /// Whether the dice are rolled or their average taken.
+    pub fn mode(&self) -> RollMode {
+        *lock(&self.mode)
+    }
+
+    /// Describe where the dice get their faces from, for logging.
+    pub fn backend(&self) -> String {
+        lock(&self.rng).to_string()
+    }
+
+    /// Set the number of rerolls that `reroll_last` may spend.
+    ///
+    /// * `n` - The rerolls available, replacing any left over.
+    pub fn set_reroll_budget(&self, n: u32) {
+        *lock(&self.rerolls) = n;
+    }
+
+    /// The rerolls left to spend.
+    pub fn remaining_rerolls(&self) -> u32 {
+        *lock(&self.rerolls)
+    }
+
+    /// Spend a reroll to repeat the most recent d20 roll.
+    ///
+    /// The roll is made again with the same modifier, advantage, and
+    /// options, rolling every die afresh, and is marked as a reroll in
+    /// the history. Portent faces are not used. Rolls made together
+    /// with `d_many` cannot be rerolled.
+    pub fn reroll_last(&self) -> Result<RollResult, RerollError> {
+        let (modifier, advantage, options): (i32, Advantage, RollOptions) = match lock(&self.last_d20).as_ref() {
+            Some(last) => (last.modifier, last.advantage, last.options.clone()),
+            None => return Err(RerollError::NothingToReroll),
+        };
+        {
+            let mut rerolls: MutexGuard<u32> = lock(&self.rerolls);
+            if *rerolls == 0 {
+                return Err(RerollError::NoRerollsLeft);
+            }
+            *rerolls -= 1;
+        }
+        log::info!("Spending a reroll on 1{} + {}", DieSize::D20, modifier);
+        Ok(roll_with_options(&Rerolling {dice: self}, DieSize::D20, modifier, advantage, &options))
+    }
+
+    /// Register a listener to be told about every roll.
+    ///
+    /// Observers are called once per roll, after it is made, so both
+    /// dice of an advantage roll arrive as one event. An observer
+    /// which panics is logged and otherwise ignored.
+    ///
+    /// * `observer` - The function to call with each roll.
+    pub fn add_observer<F>(&self, observer: F)
+    where F: Fn(&RollEvent) + Send + Sync + 'static {
+        lock(&self.observers).push(Arc::new(observer));
+    }
+
+    /// Stop telling any listeners about rolls.
+    pub fn clear_observers(&self) {
+        lock(&self.observers).clear();
+    }
+
+    /// Foretell the face of an upcoming d20 (Portent).
+    ///
+    /// The next d20 roll uses the oldest foretold face instead of
+    /// rolling, whatever its advantage. Automatic failures still fail
+    /// and leave the face waiting.
+    ///
+    /// * `value` - The face to foretell, from 1 to 20.
+    pub fn queue_portent(&self, value: u8) -> Result<(), PortentError> {
+        if !(1..=20).contains(&value) {
+            return Err(PortentError::OutOfRange(value));
+        }
+        let mut portents: MutexGuard<VecDeque<u8>> = lock(&self.portents);
+        if portents.len() >= PORTENT_LIMIT {
+            return Err(PortentError::Full);
+        }
+        portents.push_back(value);
+        Ok(())
+    }
+
+    /// The foretold faces not yet used, oldest first.
+    pub fn portents(&self) -> Vec<u8> {
+        lock(&self.portents).iter().copied().collect()
+    }
+
+    /// Simulate a roll many times and count how often each total comes up.
+    ///
+    /// The simulated rolls are not logged, recorded in the history,
+    /// or affected by Portent, and are random whatever the roll mode.
+    ///
+    /// * `n` - The number of rolls to simulate.
+    /// * `sides` - The number of sides on the die.
+    /// * `modifier` - The number to add to each roll.
+    /// * `advantage` - The advantage level of each roll.
+    pub fn simulate(&self, n: usize, sides: DieSize, modifier: i32, advantage: Advantage) -> Histogram {
+        let mut histogram: Histogram = Histogram::new(1 + modifier, sides.sides() as i32 + modifier);
+        let dice: usize = match advantage {
+            Advantage::None | Advantage::Canceled => 1,
+            Advantage::Advantage | Advantage::Disadvantage => 2,
+            Advantage::SuperAdvantage => 3,
+            Advantage::Fail => {
+                histogram.failures = n as u64;
+                return histogram;
+            },
+        };
+        let mut locked_rng: MutexGuard<RngBackend> = lock(&self.rng);
+        for _ in 0..n {
+            let mut kept: u16 = locked_rng.roll(sides);
+            for _ in 1..dice {
+                let face: u16 = locked_rng.roll(sides);
+                kept = if advantage == Advantage::Disadvantage { kept.min(face) } else { kept.max(face) };
+            }
+            histogram.buckets[kept as usize - 1] += 1;
+        }
+        log::info!("Simulated {} rolls of 1{} + {} with {:?}: mean {:.2}", n, sides, modifier, advantage, histogram.mean());
+        histogram
+    }
+
+    /// The rolls made this session, oldest first.
+    ///
+    /// Only the most recent `HISTORY_LIMIT` rolls are kept.
+    pub fn history(&self) -> Vec<HistoryEntry> {
+        lock(&self.history).iter().cloned().collect()
+    }
+
+    /// Forget every roll made so far.
+    pub fn clear_history(&self) {
+        lock(&self.history).clear();
+    }
+}
+
+impl Default for Dice {
+    fn default() -> Dice {
+        Dice::new()
+    }
+}
+
+impl Roller for Dice {
+    /// Make several rolls under a single lock of the RNG.
+    ///
+    /// While Portent faces are waiting, the rolls are made one at a
+    /// time instead, so that each foretold face is used as it would be
+    /// by `d_with_options`.
+    fn d_many(&self, specs: &[RollSpec]) -> Vec<RollResult> {
+        if !lock(&self.portents).is_empty() {
+            let results: Vec<RollResult> = specs.iter().map(|spec| self.d_with_options(spec.sides, spec.modifier, spec.advantage, &spec.options)).collect();
+            log_batch(&results);
+            return results;
+        }
+        let (results, entries): (Vec<RollResult>, Vec<HistoryEntry>) = {
+            let mut locked_rng: MutexGuard<RngBackend> = lock(&self.rng);
+            let batch: LockedRng = LockedRng {rng: RefCell::new(&mut locked_rng), mode: self.mode(), stats: &self.stats, entries: RefCell::new(Vec::new())};
+            let results: Vec<RollResult> = specs.iter().map(|spec| batch.d_with_options(spec.sides, spec.modifier, spec.advantage, &spec.options)).collect();
+            (results, batch.entries.into_inner())
+        };
+        for entry in entries {
+            self.record(entry);
+        }
+        log_batch(&results);
+        results
+    }
+
+    /// Roll a die, using a foretold face in place of a d20 if one is waiting.
+    fn d_with_options(&self, d: DieSize, modifier: i32, advantage: Advantage, options: &RollOptions) -> RollResult {
+        if d == DieSize::D20 {
+            *lock(&self.last_d20) = Some(LastRoll {modifier, advantage, options: options.clone()});
+        }
+        if d == DieSize::D20 && advantage != Advantage::Fail {
+            let portent: Option<u8> = lock(&self.portents).pop_front();
+            if let Some(face) = portent {
+                let mut result: RollResult = RollResult::new(d, face as u16, modifier);
+                result.advantage = advantage;
+                result.portent = true;
+                apply_floor(&mut result, options.floor);
+                add_bonuses(self, &mut result, &options.bonuses);
+                apply_bounds(&mut result, &options.bounds);
+                log_roll(&result, options.label.as_deref());
+                self.record(HistoryEntry {sides: d, faces: vec![face as u16], advantage, modifier, total: result.total, reroll: false});
+                return result;
+            }
+        }
+        roll_with_options(self, d, modifier, advantage, options)
+    }
+
+    fn d_flat(&self, d: DieSize, modifier: i32) -> RollResult {
+        if let Some(face) = self.mode().fixed_face(d) {
+            let mut result: RollResult = RollResult::new(d, face, modifier);
+            result.average = true;
+            log::debug!("Taking the average of 1{} + {} = {}", d, modifier, result.total);
+            return result;
+        }
+        let face: u16 = lock(&self.rng).roll(d);
+        tally(&self.stats, d, &[face]);
+        let result: RollResult = RollResult::new(d, face, modifier);
+        log::debug!("Rolling 1{} + {} = {}", d, modifier, result.total);
+        result
+    }
+
+    fn record(&self, entry: HistoryEntry) {
+        let event: RollEvent = RollEvent {entry, timestamp: SystemTime::now()};
+        {
+            let mut history: MutexGuard<VecDeque<HistoryEntry>> = lock(&self.history);
+            if history.len() == HISTORY_LIMIT {
+                history.pop_front();
+            }
+            history.push_back(event.entry.clone());
+        }
+
+        // Call observers without holding any locks, so they may roll.
+        let observers: Vec<Observer> = lock(&self.observers).clone();
+        for observer in observers {
+            if panic::catch_unwind(AssertUnwindSafe(|| observer(&event))).is_err() {
+                log::warn!("A roll observer panicked handling 1{}", event.entry.sides);
+            }
+        }
+    }
+
+    fn faces(&self, count: u8, sides: DieSize) -> Vec<u16> {
+        if let Some(face) = self.mode().fixed_face(sides) {
+            return vec![face; count as usize];
+        }
+        let faces: Vec<u16> = {
+            let mut locked_rng: MutexGuard<RngBackend> = lock(&self.rng);
+            (0..count).map(|_| locked_rng.roll(sides)).collect()
+        };
+        tally(&self.stats, sides, &faces);
+        faces
+    }
+}
+
+/// Rolls from an RNG which is already locked, so a batch of rolls
+/// needs only one lock.
+struct LockedRng<'r> {
+    rng: RefCell<&'r mut RngBackend>,
+    /// Whether dice are rolled or their average taken.
+    mode: RollMode,
+    /// The face counts to add each die rolled to.
+    stats: &'r Mutex<HashMap<DieSize, Vec<u64>>>,
+    /// Rolls made, to be recorded once the lock is released.
+    entries: RefCell<Vec<HistoryEntry>>,
+}
+
+impl Roller for LockedRng<'_> {
+    fn d_flat(&self, d: DieSize, modifier: i32) -> RollResult {
+        match self.mode.fixed_face(d) {
+            Some(face) => {
+                let mut result: RollResult = RollResult::new(d, face, modifier);
+                result.average = true;
+                result
+            },
+            None => {
+                let face: u16 = self.rng.borrow_mut().roll(d);
+                tally(self.stats, d, &[face]);
+                RollResult::new(d, face, modifier)
+            },
+        }
+    }
+
+    fn record(&self, entry: HistoryEntry) {
+        self.entries.borrow_mut().push(entry);
+    }
+}
+
+/// Rolls with a set of dice, marking each roll made as a reroll.
+struct Rerolling<'d> {
+    dice: &'d Dice,
+}
+
+impl Roller for Rerolling<'_> {
+    fn d_flat(&self, d: DieSize, modifier: i32) -> RollResult {
+        self.dice.d_flat(d, modifier)
+    }
+
+    fn faces(&self, count: u8, sides: DieSize) -> Vec<u16> {
+        self.dice.faces(count, sides)
+    }
+
+    fn record(&self, mut entry: HistoryEntry) {
+        entry.reroll = true;
+        self.dice.record(entry);
+    }
+}
+
+/// A roller which returns scripted faces instead of random ones.
+///
+/// Faces are handed out in the order given, with the modifier
+/// added as usual. This is intended for tests; see `LoadedDice`
+/// for forcing outcomes in the running app.
+#[cfg(test)]
+pub struct FixedRoller {
+    faces: RefCell<VecDeque<u16>>,
+}
+
+#[cfg(test)]
+impl FixedRoller {
+    /// Create a roller which will produce the given faces in order.
+    ///
+    /// * `faces` - The natural die faces to return.
+    pub fn new(faces: &[u16]) -> FixedRoller {
+        FixedRoller {faces: RefCell::new(faces.iter().copied().collect())}
+    }
+
+    /// The number of scripted faces not yet rolled.
+    pub fn remaining(&self) -> usize {
+        self.faces.borrow().len()
+    }
+}
+
+#[cfg(test)]
+impl Roller for FixedRoller {
+    /// Roll the next scripted face.
+    ///
+    /// Panics if the script has run out, since that means the
+    /// caller rolled more dice than it planned for.
+    fn d_flat(&self, d: DieSize, modifier: i32) -> RollResult {
+        let face: u16 = match self.faces.borrow_mut().pop_front() {
+            Some(face) => face,
+            None => panic!("FixedRoller ran out of faces rolling 1{}", d),
+        };
+        RollResult::new(d, face, modifier)
+    }
+}
+
+/// Dice which roll preloaded faces before falling back.
+///
+/// Loaded faces are used in order, one per die, so both dice of
+/// an advantage roll can be chosen by loading two faces. Once the
+/// loaded faces run out, the dice either roll for real or keep
+/// returning a default face.
+pub struct LoadedDice {
+    /// Faces waiting to be rolled.
+    faces: RefCell<VecDeque<u16>>,
+    /// The face to return once the loaded faces are gone, if any.
+    default: Option<u16>,
+    /// The dice to roll once the loaded faces are gone.
+    fallback: Dice,
+}
+
+impl LoadedDice {
+    /// Create dice which roll the given faces, then roll for real.
+    ///
+    /// * `faces` - The natural die faces to return first.
+    pub fn new(faces: &[u16]) -> LoadedDice {
+        LoadedDice {faces: RefCell::new(faces.iter().copied().collect()), default: None, fallback: Dice::new()}
+    }
+
+    /// Return the given face, rather than rolling, once the loaded faces run out.
+    ///
+    /// Faces above the size of the die being rolled are lowered to fit.
+    ///
+    /// * `face` - The face to return.
+    pub fn with_default(mut self, face: u16) -> LoadedDice {
+        self.default = Some(face);
+        self
+    }
+
+    /// Add faces to the end of the loaded queue.
+    ///
+    /// * `faces` - The natural die faces to return.
+    pub fn load(&self, faces: &[u16]) {
+        self.faces.borrow_mut().extend(faces.iter().copied());
+    }
+
+    /// The number of loaded faces not yet rolled.
+    pub fn remaining(&self) -> usize {
+        self.faces.borrow().len()
+    }
+}
+
+impl Roller for LoadedDice {
+    fn d_flat(&self, d: DieSize, modifier: i32) -> RollResult {
+        let loaded: Option<u16> = self.faces.borrow_mut().pop_front();
+        match loaded.or(self.default) {
+            Some(face) => {
+                let result: RollResult = RollResult::new(d, face.min(d.sides()), modifier);
+                log::debug!("Rolling loaded 1{} + {} = {}", d, modifier, result.total);
+                result
+            },
+            None => self.fallback.d_flat(d, modifier),
+        }
+    }
+
+    fn record(&self, entry: HistoryEntry) {
+        self.fallback.record(entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_roller_rolls_faces_in_order() {
+        let roller: FixedRoller = FixedRoller::new(&[4, 17]);
+        assert_eq!(roller.d(DieSize::D20, 2, Advantage::None).total(), 6);
+        assert_eq!(roller.d(DieSize::D20, 2, Advantage::None).total(), 19);
+        assert_eq!(roller.remaining(), 0);
+    }
+
+    #[test]
+    fn natural_20_and_1_are_read_from_the_kept_die() {
+        let roller: FixedRoller = FixedRoller::new(&[20, 1, 1, 20, 20, 1, 1, 20]);
+        assert!(roller.d(DieSize::D20, 3, Advantage::Advantage).is_nat20());
+        assert!(roller.d(DieSize::D20, 3, Advantage::Advantage).is_nat20());
+        assert!(roller.d(DieSize::D20, 3, Advantage::Disadvantage).is_nat1());
+        assert!(roller.d(DieSize::D20, 3, Advantage::Disadvantage).is_nat1());
+        let failed: RollResult = roller.d(DieSize::D20, 3, Advantage::Fail);
+        assert!(!failed.is_nat20() && !failed.is_nat1());
+    }
+
+    #[test]
+    fn natural_1_needs_a_d20() {
+        let roller: FixedRoller = FixedRoller::new(&[1, 1]);
+        assert!(!roller.d(DieSize::D4, 0, Advantage::None).is_nat1());
+        assert!(roller.d(DieSize::D20, 0, Advantage::None).is_nat1());
+    }
+
+    #[test]
+    fn reroll_ones_rerolls_each_die_once() {
+        let roller: FixedRoller = FixedRoller::new(&[1, 1, 1, 7, 5]);
+        let options: RollOptions = RollOptions {reroll_ones: true, ..Default::default()};
+        let result: RollResult = roller.d_with_options(DieSize::D20, 2, Advantage::None, &options);
+        assert_eq!((result.natural(), result.rerolled_from(), result.total()), (1, Some(1), 3));
+        let result: RollResult = roller.d_with_options(DieSize::D20, 0, Advantage::Advantage, &options);
+        assert_eq!(result.faces(), &[7, 5]);
+        assert_eq!((result.natural(), result.rerolled_from()), (7, Some(1)));
+        assert_eq!(roller.remaining(), 0);
+    }
+
+    #[test]
+    fn floor_raises_the_kept_die_before_the_modifier() {
+        let roller: FixedRoller = FixedRoller::new(&[3, 2, 3]);
+        let options: RollOptions = RollOptions {floor: Some(10), ..Default::default()};
+        let result: RollResult = roller.d_with_options(DieSize::D20, 2, Advantage::None, &options);
+        assert_eq!((result.total(), result.floored_from()), (12, Some(3)));
+        let result: RollResult = roller.d_with_options(DieSize::D20, 2, Advantage::Disadvantage, &options);
+        assert_eq!((result.total(), result.floored_from()), (12, Some(2)));
+        let result: RollResult = roller.d_with_options(DieSize::D20, 2, Advantage::Fail, &options);
+        assert!(result.is_automatic_failure());
+        assert_eq!(result.floored_from(), None);
+    }
+
+    #[test]
+    fn exploding_dice_roll_again_on_the_highest_face() {
+        let roller: FixedRoller = FixedRoller::new(&[6, 6, 2, 6, 6, 6, 6]);
+        let result: ExplodingResult = roller.d_exploding(DieSize::D6, 1);
+        assert_eq!((result.faces(), result.explosions(), result.total()), (&[6, 6, 2][..], 2, 15));
+        let result: ExplodingResult = roller.d_exploding_capped(DieSize::D6, 0, 3);
+        assert_eq!((result.explosions(), result.total()), (3, 24));
+        assert_eq!(roller.remaining(), 0);
+    }
+
+    #[test]
+    fn keep_takes_the_highest_or_lowest_dice() {
+        let roller: FixedRoller = FixedRoller::new(&[3, 6, 1, 5, 3, 6, 1, 5]);
+        let result: PoolResult = roller.keep(4, DieSize::D6, 3);
+        assert_eq!((result.faces(), result.kept(), result.total()), (&[6, 5, 3, 1][..], &[6, 5, 3][..], 14));
+        let result: PoolResult = roller.keep_lowest(4, DieSize::D6, 2);
+        assert_eq!((result.faces(), result.kept(), result.total()), (&[1, 3, 5, 6][..], &[1, 3][..], 4));
+    }
+
+    #[test]
+    fn pool_rolls_every_die_and_sums_them() {
+        let roller: FixedRoller = FixedRoller::new(&[2, 4, 6]);
+        let result: PoolResult = roller.pool(3, DieSize::D6, 5);
+        assert_eq!((result.faces(), result.total()), (&[2, 4, 6][..], 17));
+        let result: PoolResult = Dice::seeded(1).pool(100, DieSize::D6, 5);
+        assert_eq!(result.faces().len(), 100);
+        assert_eq!(result.total(), result.faces().iter().map(|&face| face as i32).sum::<i32>() + 5);
+    }
+
+    #[test]
+    fn advantage_addition_table() {
+        use Advantage::{Advantage as Adv, Canceled, Disadvantage as Dis, Fail, None};
+        let table: [(Advantage, [Advantage; 5]); 5] = [
+            (None, [None, Canceled, Adv, Dis, Fail]),
+            (Canceled, [Canceled, Canceled, Canceled, Canceled, Fail]),
+            (Adv, [Adv, Canceled, Adv, Canceled, Fail]),
+            (Dis, [Dis, Canceled, Canceled, Dis, Fail]),
+            (Fail, [Fail, Fail, Fail, Fail, Fail]),
+        ];
+        for (left, sums) in table {
+            for (right, sum) in [None, Canceled, Adv, Dis, Fail].into_iter().zip(sums) {
+                assert_eq!(left + right, sum, "{:?} + {:?}", left, right);
+            }
+        }
+    }
+
+    #[test]
+    fn totals_are_not_narrowed() {
+        let roller: FixedRoller = FixedRoller::new(&[100, 1]);
+        assert_eq!(roller.d(DieSize::D100, 30, Advantage::None).total(), 130);
+        let result: RollResult = roller.d(DieSize::D20, -5, Advantage::None);
+        assert_eq!((result.total(), result.rolled_total()), (-4, Some(-4)));
+    }
+
+    #[test]
+    fn advantage_addition_is_commutative_and_associative() {
+        let all: [Advantage; 6] = [
+            Advantage::None,
+            Advantage::Canceled,
+            Advantage::Advantage,
+            Advantage::Disadvantage,
+            Advantage::SuperAdvantage,
+            Advantage::Fail,
+        ];
+        for a in all {
+            for b in all {
+                assert_eq!(a + b, b + a, "{:?} + {:?}", a, b);
+                for c in all {
+                    assert_eq!((a + b) + c, a + (b + c), "{:?} + {:?} + {:?}", a, b, c);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn history_groups_each_roll_and_keeps_the_latest() {
+        let dice: Dice = Dice::seeded(1);
+        dice.d(DieSize::D20, 1, Advantage::Advantage);
+        dice.pool(3, DieSize::D6, 0);
+        let history: Vec<HistoryEntry> = dice.history();
+        assert_eq!(history.len(), 2);
+        assert_eq!((history[0].faces.len(), history[0].advantage, history[0].modifier), (2, Advantage::Advantage, 1));
+        assert_eq!((history[1].sides, history[1].faces.len()), (DieSize::D6, 3));
+        for _ in 0..HISTORY_LIMIT {
+            dice.d(DieSize::D20, 0, Advantage::None);
+        }
+        assert_eq!(dice.history().len(), HISTORY_LIMIT);
+        assert_eq!(dice.history()[0].advantage, Advantage::None);
+        dice.clear_history();
+        assert!(dice.history().is_empty());
+    }
+
+    #[test]
+    fn success_probability_is_exact() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        assert!(close(success_probability(DieSize::D20, 5, Advantage::None, 15), 0.55));
+        assert!(close(success_probability(DieSize::D20, 5, Advantage::Canceled, 15), 0.55));
+        assert!(close(success_probability(DieSize::D20, 5, Advantage::Advantage, 15), 0.7975));
+        assert!(close(success_probability(DieSize::D20, 5, Advantage::Disadvantage, 15), 0.3025));
+        assert_eq!(success_probability(DieSize::D20, 5, Advantage::Fail, 1), 0.0);
+        assert_eq!(success_probability(DieSize::D20, 5, Advantage::None, 40), 0.0);
+        assert_eq!(success_probability(DieSize::D20, 5, Advantage::None, -4), 1.0);
+    }
+
+    #[test]
+    fn loaded_dice_roll_loaded_faces_then_the_default() {
+        let dice: LoadedDice = LoadedDice::new(&[3, 18]).with_default(25);
+        assert_eq!(dice.d(DieSize::D20, 0, Advantage::Advantage).total(), 18);
+        assert_eq!(dice.remaining(), 0);
+        assert_eq!(dice.d(DieSize::D20, 1, Advantage::None).total(), 21);
+        dice.load(&[2]);
+        assert_eq!(dice.d(DieSize::D6, 0, Advantage::None).total(), 2);
+        assert_eq!(dice.d(DieSize::D6, 0, Advantage::None).total(), 6);
+    }
+
+    #[test]
+    fn pending_reroll_keeps_the_roll_options() {
+        let options: RollOptions = RollOptions {reroll_ones: true, floor: Some(10), ..RollOptions::default()};
+        let roller: FixedRoller = FixedRoller::new(&[12, 1, 3]);
+        let pending: PendingRoll = PendingRoll::roll(&roller, DieSize::D20, 2, Advantage::None, &options);
+        let result: RollResult = pending.reroll_and_choose(|_, _| true).ok().unwrap();
+        assert_eq!((result.natural(), result.total()), (10, 12));
+        assert_eq!(roller.remaining(), 0);
+    }
+
+    #[test]
+    fn portent_faces_are_raised_to_the_floor() {
+        let dice: Dice = Dice::seeded(1);
+        dice.queue_portent(4).unwrap();
+        let options: RollOptions = RollOptions {floor: Some(10), ..RollOptions::default()};
+        let result: RollResult = dice.d_with_options(DieSize::D20, 3, Advantage::None, &options);
+        assert!(result.is_portent());
+        assert_eq!(result.total(), 13);
+    }
+
+    #[test]
+    fn pending_rolls_spend_luck_points_on_rerolls() {
+        let roller: FixedRoller = FixedRoller::new(&[15, 9, 4]);
+        let mut luck: u8 = 1;
+        let pending: PendingRoll = PendingRoll::roll(&roller, DieSize::D20, 0, Advantage::None, &RollOptions::default()).with_luck_points(&mut luck);
+        let result: RollResult = pending.reroll_and_choose(|original, reroll| reroll.total() > original.total()).ok().unwrap();
+        assert_eq!(result.total(), 15);
+        assert_eq!(luck, 0);
+        let pending: PendingRoll = PendingRoll::roll(&roller, DieSize::D20, 0, Advantage::None, &RollOptions::default()).with_luck_points(&mut luck);
+        assert!(!pending.can_reroll());
+        let pending: PendingRoll = pending.reroll_and_choose(|_, _| true).err().unwrap();
+        assert_eq!(pending.accept().total(), 4);
+        assert_eq!(roller.remaining(), 0);
+    }
+
+    #[test]
+    fn portent_faces_replace_the_next_d20() {
+        let dice: Dice = Dice::seeded(1);
+        assert_eq!(dice.queue_portent(0), Err(PortentError::OutOfRange(0)));
+        assert_eq!(dice.queue_portent(21), Err(PortentError::OutOfRange(21)));
+        dice.queue_portent(17).unwrap();
+        dice.queue_portent(2).unwrap();
+        assert_eq!(dice.queue_portent(3), Err(PortentError::Full));
+        let result: RollResult = dice.d(DieSize::D20, 1, Advantage::Disadvantage);
+        assert!(result.is_portent());
+        assert_eq!(result.total(), 18);
+        assert_eq!(dice.portents(), vec![2]);
+        assert!(!dice.d(DieSize::D6, 0, Advantage::None).is_portent());
+        assert_eq!(dice.portents(), vec![2]);
+    }
+
+    #[test]
+    fn only_advantage_and_disadvantage_roll_a_second_die() {
+        for (advantage, rolled) in [(Advantage::None, 1), (Advantage::Canceled, 1), (Advantage::Advantage, 2), (Advantage::Disadvantage, 2), (Advantage::Fail, 0)] {
+            let roller: FixedRoller = FixedRoller::new(&[7, 7]);
+            roller.d(DieSize::D20, 0, advantage);
+            assert_eq!(2 - roller.remaining(), rolled, "{:?}", advantage);
+        }
+    }
+
+    #[test]
+    fn super_advantage_keeps_the_highest_of_three() {
+        let roller: FixedRoller = FixedRoller::new(&[3, 19, 7]);
+        let result: RollResult = roller.d(DieSize::D20, 0, Advantage::SuperAdvantage);
+        assert_eq!(result.faces(), &[3, 19, 7]);
+        assert_eq!((result.total(), result.kept_index()), (19, Some(1)));
+        assert_eq!(roller.remaining(), 0);
+        assert_eq!(Advantage::SuperAdvantage + Advantage::Disadvantage, Advantage::Canceled);
+    }
+
+    #[test]
+    fn dice_can_be_shared_between_threads() {
+        let dice: Arc<Dice> = Arc::new(Dice::seeded(1));
+        let handles: Vec<std::thread::JoinHandle<()>> = (0..4).map(|_| {
+            let dice: Arc<Dice> = Arc::clone(&dice);
+            std::thread::spawn(move || {
+                for _ in 0..25 {
+                    let total: i32 = dice.d(DieSize::D20, 0, Advantage::None).total();
+                    assert!((1..=20).contains(&total));
+                }
+            })
+        }).collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(dice.history().len(), 100);
+    }
+
+    #[test]
+    fn bonus_dice_are_rolled_once_under_advantage() {
+        let roller: FixedRoller = FixedRoller::new(&[3, 19, 4]);
+        let result: RollResult = roller.d_with_bonuses(DieSize::D20, 1, Advantage::Advantage, &[Bonus::new("guidance", DieSize::D4)]);
+        assert_eq!(result.total(), 24);
+        assert_eq!((result.bonuses()[0].label.as_str(), result.bonuses()[0].face), ("guidance", 4));
+        assert_eq!(roller.remaining(), 0);
+    }
+
+    #[test]
+    fn percentile_reads_double_zero_as_100() {
+        let roller: FixedRoller = FixedRoller::new(&[10, 10, 9, 7, 10, 3]);
+        assert_eq!(roller.percentile().total(), 100);
+        let result: RollResult = roller.percentile();
+        assert_eq!((result.total(), result.percentile_dice()), (97, Some(PercentileDice {tens: 90, ones: 7})));
+        assert_eq!(roller.percentile().total(), 3);
+    }
+
+    #[test]
+    fn percentile_history_holds_the_combined_face() {
+        let dice: Dice = Dice::scripted(vec![10, 10, 9, 7]);
+        dice.percentile();
+        dice.percentile();
+        let faces: Vec<Vec<u16>> = dice.history().iter().map(|entry| entry.faces.clone()).collect();
+        assert_eq!(faces, vec![vec![100], vec![97]]);
+        assert!(dice.history().iter().all(|entry| entry.sides == DieSize::D100));
+    }
+
+    #[test]
+    fn simulation_matches_the_expected_distribution() {
+        let dice: Dice = Dice::seeded(1);
+        let histogram: Histogram = dice.simulate(200_000, DieSize::D20, 3, Advantage::None);
+        assert!((histogram.mean() - 13.5).abs() < 0.05, "{}", histogram.mean());
+        assert!((histogram.at_least(18) - 0.3).abs() < 0.01);
+        assert!(matches!(histogram.percentile(50.0), Some(13) | Some(14)));
+    }
+
+    #[test]
+    fn invalid_dice_and_overflowing_totals_are_errors() {
+        assert_eq!(DieSize::try_new(0), Err(DiceError::InvalidDieSize(0)));
+        assert_eq!(DieSize::try_new(1001), Err(DiceError::InvalidDieSize(1001)));
+        assert!("3d1+1".parse::<Expression>().is_err());
+        let dice: Dice = Dice::seeded(1);
+        assert_eq!(dice.try_d(DieSize::D20, i32::MAX - 5, Advantage::None), Err(DiceError::Overflow));
+        assert!(dice.try_d(DieSize::D20, i32::MIN, Advantage::None).is_ok());
+    }
+
+    #[test]
+    fn observers_see_every_roll_despite_a_panicking_one() {
+        let dice: Dice = Dice::seeded(1);
+        let seen: Arc<Mutex<Vec<HistoryEntry>>> = Arc::new(Mutex::new(Vec::new()));
+        let sink: Arc<Mutex<Vec<HistoryEntry>>> = Arc::clone(&seen);
+        dice.add_observer(move |event: &RollEvent| sink.lock().unwrap().push(event.entry.clone()));
+        dice.add_observer(|_: &RollEvent| panic!("observer failed"));
+        dice.d(DieSize::D20, 0, Advantage::Advantage);
+        dice.d(DieSize::D20, 0, Advantage::None);
+        let seen: Vec<HistoryEntry> = seen.lock().unwrap().clone();
+        assert_eq!(seen.len(), 2);
+        assert_eq!((seen[0].faces.len(), seen[1].faces.len()), (2, 1));
+    }
+
+    #[test]
+    fn fate_dice_read_low_faces_as_minus_and_high_as_plus() {
+        let roller: FixedRoller = FixedRoller::new(&[1, 1, 1, 1, 3, 3, 3, 3, 1, 2, 3, 2]);
+        assert_eq!(roller.fate(0).total(), -4);
+        let result: FateResult = roller.fate(1);
+        assert_eq!(result.total(), 5);
+        assert_eq!(result.faces()[0].to_string(), "+");
+        let result: FateResult = roller.fate(0);
+        let shown: Vec<String> = result.faces().iter().map(FateFace::to_string).collect();
+        assert_eq!(shown, vec!["-", " ", "+", " "]);
+        assert_eq!(result.total(), 0);
+    }
+
+    #[test]
+    fn great_weapon_fighting_rerolls_low_dice_once() {
+        let roller: FixedRoller = FixedRoller::new(&[1, 6, 2, 1, 5]);
+        let result: PoolResult = roller.pool_with_reroll(3, DieSize::D6, 0, RerollPolicy::OnceBelow(3));
+        assert_eq!(result.faces(), &[1, 6, 5]);
+        assert_eq!(result.rerolled_from(), &[Some(1), None, Some(2)]);
+        assert_eq!(result.total(), 12);
+        assert_eq!(roller.remaining(), 0);
+    }
+
+    #[test]
+    fn advantage_set_resolves_and_explains_its_sources() {
+        let set: AdvantageSet = AdvantageSet::new()
+            .with(Advantage::Advantage, "help")
+            .with(Advantage::None, "nothing")
+            .with(Advantage::Disadvantage, "poisoned");
+        assert_eq!(set.resolve(), Advantage::Canceled);
+        assert_eq!(set.reasons(), vec!["help", "poisoned"]);
+        assert_eq!(set.to_string(), "Canceled (Advantage from help, Disadvantage from poisoned)");
+        assert_eq!(AdvantageSet::new().resolve(), Advantage::None);
+    }
+
+    #[test]
+    fn expressions_parse_print_and_evaluate_by_term() {
+        let expression: Expression = "1d20 + 2d4 - 1".parse().unwrap();
+        assert_eq!(expression.to_string(), "1d20 + 2d4 - 1");
+        let roller: FixedRoller = FixedRoller::new(&[15, 2, 3]);
+        let result: ExpressionResult = expression.eval(&roller);
+        assert_eq!(result.total(), 19);
+        assert_eq!(result.terms()[1].faces, vec![2, 3]);
+        assert_eq!(result.terms()[2].value, -1);
+        assert!("-d6+3".parse::<Expression>().is_ok());
+        for bad in ["", "1d20 ++ 2", "1d20+", "2d"] {
+            assert!(bad.parse::<Expression>().is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn batches_roll_every_spec_and_record_each() {
+        let dice: Dice = Dice::scripted(vec![3, 19, 8]);
+        let specs: [RollSpec; 2] = [
+            RollSpec::new(DieSize::D20, 2, Advantage::Advantage),
+            RollSpec::new(DieSize::D8, 0, Advantage::None),
+        ];
+        let results: Vec<RollResult> = dice.d_many(&specs);
+        assert_eq!((results[0].total(), results[1].total()), (21, 8));
+        let history: Vec<HistoryEntry> = dice.history();
+        assert_eq!((history.len(), history[0].faces.len()), (2, 2));
+    }
+
+    #[test]
+    fn bounds_clamp_totals_but_not_automatic_failures() {
+        let options: RollOptions = RollOptions {bounds: Bounds {min: Some(1), max: Some(20)}, ..RollOptions::default()};
+        let roller: FixedRoller = FixedRoller::new(&[1, 20]);
+        let low: RollResult = roller.d_with_options(DieSize::D20, -3, Advantage::None, &options);
+        assert_eq!((low.total(), low.unclamped), (1, Some(-2)));
+        let high: RollResult = roller.d_with_options(DieSize::D20, 5, Advantage::None, &options);
+        assert_eq!((high.total(), high.unclamped), (20, Some(25)));
+        let failed: RollResult = roller.d_with_options(DieSize::D20, 5, Advantage::Fail, &options);
+        assert_eq!(failed.total(), 0);
+        assert_eq!(Bounds::default().clamp(-40), -40);
+    }
+
+    #[test]
+    fn advantage_sums_like_it_adds() {
+        assert_eq!(Vec::<Advantage>::new().into_iter().sum::<Advantage>(), Advantage::None);
+        let mut advantage: Advantage = Advantage::Advantage;
+        advantage += Advantage::Disadvantage;
+        assert_eq!(advantage, Advantage::Canceled);
+        assert_eq!([Advantage::Advantage, Advantage::Fail].iter().sum::<Advantage>(), Advantage::Fail);
+        assert_eq!([Advantage::Advantage, Advantage::Advantage].into_iter().sum::<Advantage>(), Advantage::Advantage);
+    }
+
+    #[test]
+    fn any_die_from_d2_to_d1000_is_valid() {
+        assert_eq!(DieSize::try_new(0), Err(DiceError::InvalidDieSize(0)));
+        assert_eq!(DieSize::try_new(1), Err(DiceError::InvalidDieSize(1)));
+        assert_eq!(DieSize::try_new(1001), Err(DiceError::InvalidDieSize(1001)));
+        assert_eq!(DieSize::try_new(2).unwrap().sides(), 2);
+        assert_eq!(DieSize::try_new(1000).unwrap().sides(), 1000);
+        let d7: DieSize = DieSize::try_from(7).unwrap();
+        assert_eq!(d7.to_string(), "d7");
+        assert!("3d7+1".parse::<Expression>().is_ok());
+        let roller: FixedRoller = FixedRoller::new(&[7]);
+        assert_eq!(roller.d(d7, 0, Advantage::None).total(), 7);
+    }
+
+    #[test]
+    fn checks_against_a_dc_report_the_margin() {
+        let roller: FixedRoller = FixedRoller::new(&[10, 20, 20, 5]);
+        assert_eq!(roller.check_against_dc(DieSize::D20, 5, Advantage::None, 15), CheckOutcome::Success {by: 0});
+        assert_eq!(roller.check_against_dc(DieSize::D20, -10, Advantage::None, 30), CheckOutcome::Failure {by: 20});
+        assert_eq!(roller.check_against_dc(DieSize::D20, 0, Advantage::None, 15), CheckOutcome::CriticalSuccess);
+        assert_eq!(roller.check_against_dc(DieSize::D20, 0, Advantage::Fail, 1), CheckOutcome::AutomaticFailure);
+        assert_eq!(roller.check_against_dc(DieSize::D20, 0, Advantage::None, 8), CheckOutcome::Failure {by: 3});
+        assert_eq!(roller.remaining(), 0);
+    }
+
+    #[test]
+    fn rerolling_the_last_d20_spends_the_budget() {
+        let dice: Dice = Dice::seeded(1);
+        assert_eq!(dice.reroll_last(), Err(RerollError::NothingToReroll));
+        dice.d(DieSize::D20, 2, Advantage::Advantage);
+        assert_eq!(dice.reroll_last(), Err(RerollError::NoRerollsLeft));
+        dice.set_reroll_budget(1);
+        let result: RollResult = dice.reroll_last().unwrap();
+        assert_eq!((result.advantage(), result.modifier()), (Advantage::Advantage, 2));
+        let history: Vec<HistoryEntry> = dice.history();
+        assert_eq!(history.len(), 2);
+        assert!(history[1].reroll && !history[0].reroll);
+        assert_eq!(history[1].faces.len(), 2);
+        assert_eq!(dice.remaining_rerolls(), 0);
+        assert_eq!(dice.reroll_last(), Err(RerollError::NoRerollsLeft));
+    }
+
+    #[test]
+    fn average_mode_takes_each_dies_mean_face() {
+        let dice: Dice = Dice::seeded(1);
+        dice.set_mode(RollMode::Average {round_up: false});
+        let result: RollResult = dice.d(DieSize::D20, 2, Advantage::Advantage);
+        assert_eq!(result.total(), 12);
+        assert!(result.is_average());
+        assert_eq!(dice.d(DieSize::D20, 2, Advantage::Fail).total(), 0);
+        dice.set_mode(RollMode::Average {round_up: true});
+        assert_eq!(dice.d(DieSize::D20, 0, Advantage::None).total(), 11);
+        assert_eq!(dice.d(DieSize::D3, 0, Advantage::None).total(), 2);
+        assert_eq!(dice.d_many(&[RollSpec::new(DieSize::D6, 0, Advantage::None)])[0].total(), 4);
+        assert!(dice.percentile().is_average());
+        // Taking averages leaves the RNG where it was.
+        dice.set_mode(RollMode::Random);
+        let next: RollResult = dice.d(DieSize::D20, 0, Advantage::None);
+        assert!(!next.is_average());
+        assert_eq!(next.natural(), Dice::seeded(1).d(DieSize::D20, 0, Advantage::None).natural());
+    }
+
+    #[test]
+    fn critical_damage_doubles_the_dice_but_not_the_modifier() {
+        let roller: FixedRoller = FixedRoller::new(&[1, 2, 3, 4, 5]);
+        let critical: DamageResult = roller.damage(&[(2, DieSize::D6)], 3, true);
+        assert_eq!(critical.groups()[0].faces().len(), 4);
+        assert_eq!(critical.total(), 13);
+        assert_eq!(roller.damage(&[(1, DieSize::D8)], 3, false).total(), 8);
+        let roller: FixedRoller = FixedRoller::new(&[1, 6, 4, 3]);
+        let rerolled: DamageResult = roller.damage_with_reroll(&[(1, DieSize::D6), (1, DieSize::D8)], 0, false, RerollPolicy::OnceBelow(3));
+        assert_eq!(rerolled.total(), 10);
+    }
+
+    #[test]
+    fn seeded_and_scripted_backends_repeat_their_faces() {
+        let faces = |dice: &Dice| -> Vec<u16> {
+            (0..50).map(|_| dice.d(DieSize::D20, 0, Advantage::None).natural()).collect()
+        };
+        let first: Dice = Dice::seeded(7);
+        assert_eq!(faces(&first), faces(&Dice::seeded(7)));
+        assert!(first.backend().contains('7'));
+        let scripted: Dice = Dice::scripted(vec![3, 19, 25]);
+        assert_eq!(scripted.d(DieSize::D20, 0, Advantage::Advantage).natural(), 19);
+        assert_eq!(scripted.d(DieSize::D20, 0, Advantage::None).natural(), 20);
+        assert!(faces(&Dice::new()).iter().all(|face| (1..=20).contains(face)));
+    }
+
+    #[test]
+    fn automatic_failures_differ_from_rolls_totalling_0() {
+        let roller: FixedRoller = FixedRoller::new(&[1]);
+        let rolled: RollResult = roller.d(DieSize::D20, -1, Advantage::None);
+        let failed: RollResult = roller.d(DieSize::D20, -1, Advantage::Fail);
+        assert_eq!(rolled.total(), failed.total());
+        assert_eq!((rolled.rolled_total(), failed.rolled_total()), (Some(0), None));
+        assert!(failed.is_automatic_failure() && !rolled.is_automatic_failure());
+    }
+
+    #[test]
+    fn face_stats_count_real_faces_per_die() {
+        let dice: Dice = Dice::seeded(1);
+        for _ in 0..5000 {
+            dice.d(DieSize::D20, 0, Advantage::Advantage);
+        }
+        dice.pool(10, DieSize::D6, 0);
+        dice.d_many(&[RollSpec::new(DieSize::D6, 0, Advantage::None)]);
+        let stats: FaceStats = dice.face_stats(DieSize::D20);
+        assert_eq!(stats.rolls(), 10000);
+        assert!(stats.chi_square() < 60.0, "{}", stats.chi_square());
+        assert_eq!(dice.face_stats(DieSize::D6).rolls(), 11);
+        dice.set_mode(RollMode::Average {round_up: false});
+        dice.d(DieSize::D20, 0, Advantage::None);
+        assert_eq!(dice.face_stats(DieSize::D20).rolls(), 10000);
+        dice.reset_stats();
+        assert_eq!(dice.face_stats(DieSize::D20).rolls(), 0);
+    }
+
+    #[test]
+    fn every_die_is_reported_with_the_kept_one_marked() {
+        let roller: FixedRoller = FixedRoller::new(&[4, 17, 4, 17, 9]);
+        let advantage: RollResult = roller.d(DieSize::D20, 3, Advantage::Advantage);
+        assert_eq!(advantage.faces(), &[4, 17]);
+        assert_eq!((advantage.kept_index(), advantage.discarded(), advantage.total()), (Some(1), vec![4], 20));
+        let disadvantage: RollResult = roller.d(DieSize::D20, 3, Advantage::Disadvantage);
+        assert_eq!((disadvantage.kept_index(), disadvantage.discarded(), disadvantage.natural()), (Some(0), vec![17], 4));
+        let flat: RollResult = roller.d(DieSize::D20, 0, Advantage::None);
+        assert_eq!(flat.faces(), &[9]);
+        assert!(flat.discarded().is_empty());
+        assert!(roller.d(DieSize::D20, 0, Advantage::Fail).faces().is_empty());
+    }
+
+    #[test]
+    fn outcomes_agree_with_success_probability() {
+        for modifier in [-10, 0, 5] {
+            for dc in [1, 10, 25, 30] {
+                let successes: usize = (1..=20).filter(|&face| {
+                    let roller: FixedRoller = FixedRoller::new(&[face]);
+                    roller.check_against_dc(DieSize::D20, modifier, Advantage::None, dc).is_success()
+                }).count();
+                assert_eq!(successes as f64 / 20.0, success_probability(DieSize::D20, modifier, Advantage::None, dc), "{} vs DC {}", modifier, dc);
+            }
+        }
+    }
+
+    #[test]
+    fn batches_match_rolling_each_spec_in_turn() {
+        let options: RollOptions = RollOptions {reroll_ones: true, floor: Some(5), ..RollOptions::default()};
+        let specs: Vec<RollSpec> = vec![
+            RollSpec::new(DieSize::D20, 3, Advantage::Advantage),
+            RollSpec::new(DieSize::D20, 0, Advantage::None).with_options(options),
+            RollSpec::new(DieSize::D8, -1, Advantage::Disadvantage),
+            RollSpec::new(DieSize::D20, 2, Advantage::Fail),
+        ];
+        let batched: Dice = Dice::seeded(7);
+        let single: Dice = Dice::seeded(7);
+        for _ in 0..50 {
+            let each: Vec<RollResult> = specs.iter().map(|spec| single.d_with_options(spec.sides, spec.modifier, spec.advantage, &spec.options)).collect();
+            assert_eq!(batched.d_many(&specs), each);
+        }
+    }
+
+    #[test]
+    fn batches_use_portent_faces() {
+        let dice: Dice = Dice::scripted(vec![4, 9]);
+        dice.queue_portent(17).unwrap();
+        let specs: [RollSpec; 2] = [RollSpec::new(DieSize::D20, 1, Advantage::None), RollSpec::new(DieSize::D20, 1, Advantage::None)];
+        let results: Vec<RollResult> = dice.d_many(&specs);
+        assert_eq!((results[0].natural(), results[1].natural()), (17, 4));
+        assert!(results[0].is_portent());
+    }
+
+    #[test]
+    fn invalid_batches_roll_nothing() {
+        let roller: FixedRoller = FixedRoller::new(&[10]);
+        let specs: [RollSpec; 2] = [RollSpec::new(DieSize::D20, 0, Advantage::None), RollSpec::new(DieSize::D20, i32::MAX, Advantage::None)];
+        assert_eq!(roller.try_d_many(&specs).unwrap_err(), DiceError::Overflow);
+        assert_eq!(roller.remaining(), 1);
+    }
+
+    #[test]
+    fn d3_rolls_every_face_from_1_to_3() {
+        let dice: Dice = Dice::seeded(5);
+        let mut seen: [bool; 3] = [false; 3];
+        for _ in 0..300 {
+            let face: u16 = dice.d(DieSize::D3, 0, Advantage::None).natural();
+            assert!((1..=3).contains(&face), "{}", face);
+            seen[face as usize - 1] = true;
+        }
+        assert_eq!(seen, [true; 3]);
+    }
+}
@@ -3,12 +3,20 @@
 //! The environment consists of circumstances that apply to all
 //! characters in the game, and which may be passed to the
 //! appropriate checks to affect their outcome.
+use crate::dice::Advantage;
 
 /// Lighting affects how well characters can make vision-related checks.
 ///
 /// Characters with darkvision will have an easier time in low-light
 /// conditions.
+///
+/// Levels are ordered from darkest to brightest.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum Lighting {
+    /// Darkness from magic, such as the Darkness spell, which darkvision
+    /// cannot see through and no ordinary light brightens. Only
+    /// truesight or Devil's Sight can see in it.
+    MagicalDark,
     /// Characters without darkvision cannot see. Characters with
     /// darkvision see only with disadvantage.
     Dark,
@@ -16,4 +24,367 @@ pub enum Lighting {
     Dim,
     /// Everyone can see without any problem.
     Light,
-}
\ No newline at end of file
+}
+
+impl std::fmt::Display for Lighting {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Lighting::MagicalDark => write!(f, "magical darkness"),
+            Lighting::Dark => write!(f, "darkness"),
+            Lighting::Dim => write!(f, "dim light"),
+            Lighting::Light => write!(f, "bright light"),
+        }
+    }
+}
+
+/// Something which sheds light, such as a torch or a lantern.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct LightSource {
+    /// What the light is, such as "torch".
+    pub name: String,
+    /// How far the source sheds bright light, in feet.
+    pub bright_radius: u16,
+    /// How far the source sheds dim light, in feet, including the
+    /// bright radius.
+    pub dim_radius: u16,
+}
+
+impl LightSource {
+    /// A light source.
+    /// * `name` - What the light is.
+    /// * `bright_radius` - How far it sheds bright light, in feet.
+    /// * `dim_radius` - How far it sheds dim light, in feet.
+    pub fn new(name: &str, bright_radius: u16, dim_radius: u16) -> LightSource {
+        LightSource {name: String::from(name), bright_radius, dim_radius}
+    }
+
+    /// A torch, bright to 20 feet and dim to 40.
+    pub fn torch() -> LightSource {
+        LightSource::new("torch", 20, 40)
+    }
+
+    /// A hooded lantern, bright to 30 feet and dim to 60.
+    pub fn lantern() -> LightSource {
+        LightSource::new("lantern", 30, 60)
+    }
+
+    /// The light the source sheds on something.
+    /// * `distance` - How far the lit thing is from the source, in feet.
+    pub fn lighting_at(&self, distance: u16) -> Option<Lighting> {
+        if distance <= self.bright_radius {
+            Some(Lighting::Light)
+        } else if distance <= self.dim_radius {
+            Some(Lighting::Dim)
+        } else {
+            None
+        }
+    }
+}
+
+/// The lighting where something is, given the ambient lighting and the
+/// light sources around it.
+///
+/// Light sources only ever brighten the ambient lighting, and never
+/// brighten magical darkness.
+/// * `ambient` - The level of environmental lighting.
+/// * `sources` - Each light source, with how far it is in feet.
+pub fn effective_lighting(ambient: Lighting, sources: &[(LightSource, u16)]) -> Lighting {
+    if ambient == Lighting::MagicalDark {
+        return ambient;
+    }
+    sources.iter()
+        .filter_map(|(source, distance)| source.lighting_at(*distance))
+        .fold(ambient, Lighting::max)
+}
+
+/// Obscurement, such as fog, foliage, or smoke, blocks sight whatever
+/// the lighting, and darkvision does not see through it.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Obscurement {
+    /// Nothing blocks sight.
+    #[default]
+    Clear,
+    /// Characters see only with disadvantage, as through light fog.
+    LightlyObscured,
+    /// Characters cannot see, as through dense fog.
+    HeavilyObscured,
+}
+
+impl std::fmt::Display for Obscurement {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Obscurement::Clear => write!(f, "clear"),
+            Obscurement::LightlyObscured => write!(f, "lightly obscured"),
+            Obscurement::HeavilyObscured => write!(f, "heavily obscured"),
+        }
+    }
+}
+
+/// The weather in a scene, which can hamper Perception and block sight.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Weather {
+    /// Nothing out of the ordinary.
+    #[default]
+    Clear,
+    /// Rain, which drowns out sights and sounds.
+    Rain,
+    /// Heavy rain, which also blocks sight beyond `HEAVY_WEATHER_VISIBILITY`.
+    HeavyRain,
+    /// A snowstorm, which hampers and blocks as heavy rain does.
+    Snowstorm,
+    /// Fog, which blocks sight entirely.
+    Fog,
+}
+
+/// How far one can see through heavy rain or a snowstorm, in feet.
+pub const HEAVY_WEATHER_VISIBILITY: u16 = 60;
+
+impl Weather {
+    /// The advantage level the weather gives Perception checks which
+    /// rely on a sense.
+    /// * `sense` - The sense the check relies on.
+    pub fn perception_effect(&self, sense: Sense) -> Advantage {
+        match (self, sense) {
+            (Weather::Rain | Weather::HeavyRain | Weather::Snowstorm, Sense::Sight | Sense::Hearing) => Advantage::Disadvantage,
+            _ => Advantage::None,
+        }
+    }
+
+    /// How much the weather obscures a target.
+    /// * `distance` - How far away the target is in feet, or `None` if
+    ///   not known, when only weather thick at any distance obscures it.
+    pub fn obscurement(&self, distance: Option<u16>) -> Obscurement {
+        match self {
+            Weather::Fog => Obscurement::HeavilyObscured,
+            Weather::HeavyRain | Weather::Snowstorm if distance.is_some_and(|feet| feet > HEAVY_WEATHER_VISIBILITY) => Obscurement::HeavilyObscured,
+            _ => Obscurement::Clear,
+        }
+    }
+}
+
+impl std::fmt::Display for Weather {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Weather::Clear => write!(f, "clear weather"),
+            Weather::Rain => write!(f, "rain"),
+            Weather::HeavyRain => write!(f, "heavy rain"),
+            Weather::Snowstorm => write!(f, "snowstorm"),
+            Weather::Fog => write!(f, "fog"),
+        }
+    }
+}
+
+/// How noisy a scene is, which hampers listening as darkness hampers
+/// sight.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum NoiseLevel {
+    /// Nothing drowns out sounds.
+    #[default]
+    Quiet,
+    /// Chatter or a crackling fire, which is no trouble.
+    Moderate,
+    /// A roaring tavern, so characters hear only with disadvantage.
+    Loud,
+    /// A waterfall or a battle, so characters cannot hear.
+    Deafening,
+}
+
+impl NoiseLevel {
+    /// The advantage level the noise gives Perception checks which
+    /// rely on hearing.
+    pub fn hearing_effect(&self) -> Advantage {
+        match self {
+            NoiseLevel::Quiet | NoiseLevel::Moderate => Advantage::None,
+            NoiseLevel::Loud => Advantage::Disadvantage,
+            NoiseLevel::Deafening => Advantage::Fail,
+        }
+    }
+}
+
+impl std::fmt::Display for NoiseLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            NoiseLevel::Quiet => write!(f, "quiet"),
+            NoiseLevel::Moderate => write!(f, "moderate noise"),
+            NoiseLevel::Loud => write!(f, "loud noise"),
+            NoiseLevel::Deafening => write!(f, "deafening noise"),
+        }
+    }
+}
+
+/// The sense a Perception check relies on.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Sense {
+    /// Seeing, which the lighting affects.
+    #[default]
+    Sight,
+    /// Listening, as at a door.
+    Hearing,
+    /// Smelling.
+    Smell,
+}
+
+impl std::fmt::Display for Sense {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Sense::Sight => write!(f, "sight"),
+            Sense::Hearing => write!(f, "hearing"),
+            Sense::Smell => write!(f, "smell"),
+        }
+    }
+}
+
+/// Thresholds for how the environment affects checks, which each table
+/// may tune to its taste.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct EnvironmentRules {
+    /// How far away, in feet, a target can be perceived without penalty.
+    pub clear_range: u16,
+    /// How far away, in feet, a target can be perceived at all; beyond
+    /// `clear_range`, only with disadvantage.
+    pub horizon: u16,
+}
+
+impl EnvironmentRules {
+    /// The advantage level the distance to a target gives Perception
+    /// checks.
+    /// * `distance` - How far away the target is in feet.
+    pub fn distance_effect(&self, distance: u16) -> Advantage {
+        if distance > self.horizon {
+            Advantage::Fail
+        } else if distance > self.clear_range {
+            Advantage::Disadvantage
+        } else {
+            Advantage::None
+        }
+    }
+}
+
+impl Default for EnvironmentRules {
+    fn default() -> EnvironmentRules {
+        EnvironmentRules {clear_range: 60, horizon: 300}
+    }
+}
+
+/// The circumstances in which a check is made.
+///
+/// Each check takes from this only what applies to it, so lighting and
+/// obscurement matter only to checks which rely on sight.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CheckContext {
+    /// The level of environmental lighting, before any light sources.
+    pub lighting: Lighting,
+    /// Light sources, with how far each is from the target in feet.
+    pub light_sources: Vec<(LightSource, u16)>,
+    /// How much the area is obscured, apart from the lighting.
+    pub obscurement: Obscurement,
+    /// The weather in the scene.
+    pub weather: Weather,
+    /// How noisy the scene is, which matters only to checks relying on hearing.
+    pub noise: NoiseLevel,
+    /// How far away the target is in feet, or `None` if not known.
+    /// Perception checks are banded by a known distance, and it is
+    /// measured against the range of a character's darkvision and other
+    /// senses; an unknown distance gives no penalty, and is taken to be
+    /// within range of them all.
+    pub distance: Option<u16>,
+    /// How the distance and other circumstances translate to advantage.
+    pub rules: EnvironmentRules,
+    /// Whether the character or their target is in direct sunlight,
+    /// which is brighter than `Lighting::Light` alone says.
+    pub sunlight: bool,
+    /// The sense a Perception check relies on. Other checks ignore it.
+    pub sense: Sense,
+}
+
+impl CheckContext {
+    /// Describe checks made in some lighting, against a target at an
+    /// unknown distance.
+    /// * `lighting` - The level of environmental lighting.
+    pub fn new(lighting: Lighting) -> CheckContext {
+        CheckContext {lighting, light_sources: Vec::new(), obscurement: Obscurement::Clear, weather: Weather::Clear, noise: NoiseLevel::Quiet, distance: None, rules: EnvironmentRules::default(), sunlight: false, sense: Sense::Sight}
+    }
+
+    /// The lighting on the target, with the light sources' light.
+    pub fn effective_lighting(&self) -> Lighting {
+        effective_lighting(self.lighting, &self.light_sources)
+    }
+
+    /// The same circumstances, with another light source.
+    /// * `source` - The light source.
+    /// * `distance` - How far the source is from the target, in feet.
+    pub fn with_light_source(mut self, source: LightSource, distance: u16) -> CheckContext {
+        self.light_sources.push((source, distance));
+        self
+    }
+
+    /// The same circumstances, against a target some distance away.
+    /// * `distance` - How far away the target is in feet.
+    pub fn with_distance(self, distance: u16) -> CheckContext {
+        CheckContext {distance: Some(distance), ..self}
+    }
+
+    /// The same circumstances, judged by a table's own thresholds.
+    /// * `rules` - The thresholds to use.
+    pub fn with_rules(self, rules: EnvironmentRules) -> CheckContext {
+        CheckContext {rules, ..self}
+    }
+
+    /// The same circumstances, in some weather.
+    /// * `weather` - The weather in the scene.
+    pub fn with_weather(self, weather: Weather) -> CheckContext {
+        CheckContext {weather, ..self}
+    }
+
+    /// The same circumstances, with some level of noise.
+    /// * `noise` - How noisy the scene is.
+    pub fn with_noise(self, noise: NoiseLevel) -> CheckContext {
+        CheckContext {noise, ..self}
+    }
+
+    /// The same circumstances, with the area obscured.
+    /// * `obscurement` - How much the area is obscured.
+    pub fn with_obscurement(self, obscurement: Obscurement) -> CheckContext {
+        CheckContext {obscurement, ..self}
+    }
+
+    /// The same circumstances, for a Perception check relying on a sense.
+    /// * `sense` - The sense the check relies on.
+    pub fn with_sense(self, sense: Sense) -> CheckContext {
+        CheckContext {sense, ..self}
+    }
+
+    /// The same circumstances, in or out of direct sunlight.
+    /// * `sunlight` - Whether the character or their target is in direct sunlight.
+    pub fn with_sunlight(self, sunlight: bool) -> CheckContext {
+        CheckContext {sunlight, ..self}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distances_are_banded_by_the_rules() {
+        let rules: EnvironmentRules = EnvironmentRules::default();
+        assert_eq!(rules.distance_effect(60), Advantage::None);
+        assert_eq!(rules.distance_effect(61), Advantage::Disadvantage);
+        assert_eq!(rules.distance_effect(300), Advantage::Disadvantage);
+        assert_eq!(rules.distance_effect(301), Advantage::Fail);
+    }
+
+    #[test]
+    fn light_sources_brighten_the_ambient_lighting() {
+        assert_eq!(effective_lighting(Lighting::Dark, &[(LightSource::torch(), 25)]), Lighting::Dim);
+        assert_eq!(effective_lighting(Lighting::Dark, &[(LightSource::torch(), 50)]), Lighting::Dark);
+        assert_eq!(effective_lighting(Lighting::Dark, &[(LightSource::torch(), 50), (LightSource::lantern(), 10)]), Lighting::Light);
+        assert_eq!(effective_lighting(Lighting::Light, &[(LightSource::torch(), 25)]), Lighting::Light);
+    }
+
+    #[test]
+    fn light_does_not_reach_into_magical_darkness() {
+        assert_eq!(effective_lighting(Lighting::MagicalDark, &[(LightSource::torch(), 5)]), Lighting::MagicalDark);
+        assert_eq!(Lighting::MagicalDark.to_string(), "magical darkness");
+    }
+}
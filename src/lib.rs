@@ -0,0 +1,9 @@
+//! Rules and dice for running checks against the grue.
+//!
+//! The GUI in `main.rs` is a thin layer over these modules, which
+//! hold the game logic proper.
+
+pub mod dice;
+pub mod environment;
+pub mod pc;
+pub mod debug;
@@ -1,11 +1,14 @@
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
 use iced::executor;
-use iced::{Application, Command, Element, Settings, Theme};
-use iced::widget::text;
+use iced::futures::{SinkExt, StreamExt};
+use iced::{Application, Command, Element, Settings, Subscription, Theme};
+use iced::widget::{button, checkbox, column, radio, row, text};
 
-mod dice;
-mod environment;
-mod pc;
-mod debug;
+use rollforgrue::{debug, dice, pc};
+use rollforgrue::dice::Roller;
+use rollforgrue::environment::{CheckContext, Lighting, Obscurement};
 
 static DEBUG: debug::Debug = debug::Debug {};
 
@@ -15,17 +18,173 @@ pub fn main() -> iced::Result {
     RollForGrue::run(Settings::default())
 }
 
+/// Set to a comma-separated list of faces to roll loaded dice in debug builds.
+const LOADED_DICE_VAR: &str = "ROLLFORGRUE_LOADED_DICE";
+
+/// Set to the path of a character or party config to load.
+const PARTY_VAR: &str = "ROLLFORGRUE_PARTY";
+
+/// The config loaded when `PARTY_VAR` is not set.
+const DEFAULT_PARTY_PATH: &str = "party.json";
+
 struct RollForGrue {
-    dice: dice::Dice,
-    last_result: i8,
+    /// The dice every PC rolls with.
+    dice: Rc<dyn Roller>,
+    /// The path of the config the PCs were loaded from, which is
+    /// watched so that they can be reloaded when it changes.
+    config_path: PathBuf,
+    /// The PCs, all rolling with `dice`.
+    party: pc::Party,
+    /// The PCs reloaded since the last roll.
+    reloaded: Vec<pc::PcId>,
+    /// The most recent roll, if any has been made and there are no PCs.
+    last_result: Option<dice::RollResult>,
+    /// Each PC's most recent Perception check, in party order.
+    last_checks: Vec<pc::CheckResult>,
+    /// The lighting the party is in, as chosen with the radio buttons.
+    light_level: Lighting,
+    /// Whether the party is in direct sunlight, as chosen with the checkbox.
+    sunlight: bool,
+    /// How obscured the area is, as chosen with the radio buttons.
+    obscurement: Obscurement,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum GrueMessage {
-    TestMessage
+    TestMessage,
+    /// The lighting radio buttons were changed.
+    LightingChanged(Lighting),
+    /// The direct sunlight checkbox was toggled.
+    SunlightToggled(bool),
+    /// The obscurement radio buttons were changed.
+    ObscurementChanged(Obscurement),
+    /// The config the PCs were loaded from was changed on disk.
+    ConfigChanged,
+    /// A PC was reloaded from their changed config; holds their identifier.
+    PcReloaded(pc::PcId),
 }
 
 impl RollForGrue {
+    /// Choose the dice to roll with.
+    ///
+    /// Debug builds roll loaded dice when `LOADED_DICE_VAR` is set,
+    /// to reproduce particular outcomes.
+    fn make_dice() -> Rc<dyn Roller> {
+        if cfg!(debug_assertions) {
+            if let Ok(faces) = std::env::var(LOADED_DICE_VAR) {
+                let faces: Vec<u16> = faces.split(',').filter_map(|face| face.trim().parse().ok()).collect();
+                log::info!("Rolling loaded dice: {:?}", faces);
+                return Rc::new(dice::LoadedDice::new(&faces));
+            }
+        }
+        Rc::new(dice::Dice::new())
+    }
+
+    /// The path of the config to load the PCs from: `PARTY_VAR`, or
+    /// `DEFAULT_PARTY_PATH` if it is not set.
+    fn config_path() -> PathBuf {
+        PathBuf::from(std::env::var(PARTY_VAR).unwrap_or_else(|_| String::from(DEFAULT_PARTY_PATH)))
+    }
+
+    /// The directory the files a config's characters are based on are
+    /// found relative to.
+    /// * `path` - The path of the config.
+    fn config_dir(path: &Path) -> &Path {
+        path.parent().unwrap_or_else(|| Path::new(""))
+    }
+
+    /// Load the PCs from a config, to roll with the given dice.
+    ///
+    /// A config which cannot be loaded is logged, and leaves no PCs.
+    /// * `dice` - The dice every PC rolls with.
+    /// * `path` - The path of the config.
+    fn load_pcs(dice: &Rc<dyn Roller>, path: &Path) -> pc::Party {
+        let loaded: Result<Vec<pc::PC>, pc::PcError> = std::fs::read_to_string(path)
+            .map_err(pc::PcError::from)
+            .and_then(|text| pc::parse_party_in(Rc::clone(dice), &text, RollForGrue::config_dir(path)));
+        match loaded {
+            Ok(pcs) => pc::Party::new(pcs),
+            Err(error) => {
+                log::error!("Cannot load the party from {}: {}", path.display(), error);
+                pc::Party::new(Vec::new())
+            },
+        }
+    }
+
+    /// Reload the PCs after their config changes, keeping what they
+    /// gained in play, and report each one reloaded.
+    ///
+    /// A config which cannot be loaded is logged, and leaves the PCs
+    /// as they were.
+    fn reload_pcs(&mut self) -> Command<GrueMessage> {
+        let reloaded: Result<Vec<String>, pc::PcError> = std::fs::read_to_string(&self.config_path)
+            .map_err(pc::PcError::from)
+            .and_then(|text| self.party.reload(Rc::clone(&self.dice), &text, RollForGrue::config_dir(&self.config_path)));
+        match reloaded {
+            Ok(names) => {
+                log::info!("Reloaded {}", names.join(", "));
+                let ids: Vec<pc::PcId> = self.party.members().iter().map(pc::PC::id).collect();
+                Command::batch(ids.into_iter().map(|id| Command::perform(async move {id}, GrueMessage::PcReloaded)))
+            },
+            Err(error) => {
+                log::warn!("Keeping the party as it was; cannot reload {}: {}", self.config_path.display(), error);
+                Command::none()
+            },
+        }
+    }
+}
+
+/// Send `GrueMessage::ConfigChanged` whenever the file at a path is
+/// written.
+///
+/// The directory holding the file is watched rather than the file
+/// itself, since many editors save by replacing the file. If the
+/// directory cannot be watched, this is logged and nothing is sent.
+/// * `path` - The path of the file to watch.
+fn watch_config(path: PathBuf) -> Subscription<GrueMessage> {
+    struct WatchConfig;
+    iced::subscription::channel(std::any::TypeId::of::<WatchConfig>(), 16, move |mut output| {
+        let path: PathBuf = path.clone();
+        async move {
+            let (sender, mut changes) = iced::futures::channel::mpsc::unbounded::<()>();
+            let file_name = path.file_name().map(|name| name.to_os_string());
+            let watched = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                if let Ok(event) = event {
+                    let written: bool = event.kind.is_create() || event.kind.is_modify();
+                    if written && event.paths.iter().any(|changed| changed.file_name() == file_name.as_deref()) {
+                        let _ = sender.unbounded_send(());
+                    }
+                }
+            });
+            let directory: &Path = match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            };
+            // The watcher stops when dropped, so it is held for as long as this runs.
+            let _watcher = match watched {
+                Ok(mut watcher) => match notify::Watcher::watch(&mut watcher, directory, notify::RecursiveMode::NonRecursive) {
+                    Ok(()) => Some(watcher),
+                    Err(error) => {
+                        log::warn!("Cannot watch {} for changes: {}", path.display(), error);
+                        None
+                    },
+                },
+                Err(error) => {
+                    log::warn!("Cannot watch {} for changes: {}", path.display(), error);
+                    None
+                },
+            };
+            loop {
+                match changes.next().await {
+                    Some(()) => {
+                        let _ = output.send(GrueMessage::ConfigChanged).await;
+                    },
+                    // No watcher is running, so nothing will ever be sent.
+                    None => iced::futures::future::pending::<()>().await,
+                }
+            }
+        }
+    })
 }
 
 impl Application for RollForGrue {
@@ -35,7 +194,10 @@ impl Application for RollForGrue {
     type Theme = Theme;
 
     fn new(_flags: ()) -> (RollForGrue, Command<Self::Message>) {
-        let mut app: RollForGrue = RollForGrue {dice: dice::Dice::new(), last_result: 0};
+        let dice: Rc<dyn Roller> = RollForGrue::make_dice();
+        let config_path: PathBuf = RollForGrue::config_path();
+        let party: pc::Party = RollForGrue::load_pcs(&dice, &config_path);
+        let mut app: RollForGrue = RollForGrue {dice, config_path, party, reloaded: Vec::new(), last_result: None, last_checks: Vec::new(), light_level: Lighting::Light, sunlight: false, obscurement: Obscurement::Clear};
         let command: Command<GrueMessage> = app.update(Self::Message::TestMessage);
         (app, command)
     }
@@ -44,12 +206,87 @@ impl Application for RollForGrue {
         String::from("Roll For Grue")
     }
 
-    fn update(&mut self, _message: Self::Message) -> Command<Self::Message> {
-        self.last_result = self.dice.d(20, 0, dice::Advantage::None);
+    fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
+        match message {
+            GrueMessage::TestMessage if self.party.is_empty() => {
+                self.last_result = Some(self.dice.d(dice::DieSize::D20, 0, dice::Advantage::None));
+            },
+            GrueMessage::TestMessage => {
+                self.last_checks.clear();
+                self.reloaded.clear();
+                for pc in self.party.members_mut() {
+                    let context: CheckContext = CheckContext::new(self.light_level).with_sunlight(self.sunlight).with_obscurement(self.obscurement);
+                    match pc.check_in(&context, pc::Ability::Wisdom, pc::Proficiency::Perception, dice::Advantage::None) {
+                        Ok(check) => self.last_checks.push(check),
+                        Err(error) => log::error!("{} cannot make a Perception check: {}", pc.name(), error),
+                    };
+                }
+            },
+            GrueMessage::LightingChanged(lighting) => {
+                self.light_level = lighting;
+            },
+            GrueMessage::SunlightToggled(sunlight) => {
+                self.sunlight = sunlight;
+            },
+            GrueMessage::ObscurementChanged(obscurement) => {
+                self.obscurement = obscurement;
+            },
+            GrueMessage::ConfigChanged => return self.reload_pcs(),
+            GrueMessage::PcReloaded(id) => {
+                if !self.reloaded.contains(&id) {
+                    self.reloaded.push(id);
+                }
+            },
+        };
         Command::none()
     }
 
-    fn view(&self) -> Element<Self::Message> {
-        text(format!("Hello, world! You rolled a {}.", self.last_result)).into()
+    fn subscription(&self) -> Subscription<Self::Message> {
+        watch_config(self.config_path.clone())
+    }
+
+    fn view(&self) -> Element<'_, Self::Message> {
+        let shown: String = if self.last_checks.is_empty() {
+            match self.last_result.as_ref().map(|result| result.rolled_total()) {
+                Some(Some(total)) => format!("You rolled a {}.", total),
+                Some(None) => String::from("You rolled: FAIL"),
+                None => String::from("—"),
+            }
+        } else {
+            let checks: Vec<String> = self.last_checks.iter().map(|check| match check.roll().rolled_total() {
+                Some(total) => format!("{} rolled a {}.", check.name(), total),
+                None => format!("{} rolled: FAIL", check.name()),
+            }).collect();
+            checks.join(" ")
+        };
+        let lighting: Element<'_, Self::Message> = row![
+            radio("Light", Lighting::Light, Some(self.light_level), GrueMessage::LightingChanged),
+            radio("Dim", Lighting::Dim, Some(self.light_level), GrueMessage::LightingChanged),
+            radio("Dark", Lighting::Dark, Some(self.light_level), GrueMessage::LightingChanged),
+            radio("Magical darkness", Lighting::MagicalDark, Some(self.light_level), GrueMessage::LightingChanged),
+            checkbox("Direct sunlight", self.sunlight, GrueMessage::SunlightToggled),
+        ].spacing(10).into();
+        let obscurement: Element<'_, Self::Message> = row![
+            radio("Clear", Obscurement::Clear, Some(self.obscurement), GrueMessage::ObscurementChanged),
+            radio("Lightly obscured", Obscurement::LightlyObscured, Some(self.obscurement), GrueMessage::ObscurementChanged),
+            radio("Heavily obscured", Obscurement::HeavilyObscured, Some(self.obscurement), GrueMessage::ObscurementChanged),
+        ].spacing(10).into();
+        let reloaded: String = if self.reloaded.is_empty() {
+            String::new()
+        } else {
+            // A PC reloaded and then dropped from the config is not shown.
+            let names: Vec<&str> = self.reloaded.iter()
+                .filter_map(|id| self.party.get_by_id(*id))
+                .map(pc::PC::name)
+                .collect();
+            format!("Reloaded {}.", names.join(", "))
+        };
+        column![
+            text(format!("Hello, world! {}", shown)),
+            lighting,
+            obscurement,
+            button("Roll").on_press(GrueMessage::TestMessage),
+            text(reloaded),
+        ].spacing(10).into()
     }
 }
\ No newline at end of file
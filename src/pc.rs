@@ -4,17 +4,25 @@
 //! character traits. Rules for how characters interact with
 //! the environment (such as darkvision) are also contained
 //! here.
-use json::JsonValue;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::Read;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::rc::Rc;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use crate::dice::{Dice, Advantage};
-use crate::environment::Lighting;
+use crate::dice::{self, Advantage, AdvantageSet, Bonus, Bounds, CheckOutcome, DiceError, DieSize, PendingRoll, Roller, RollOptions, RollResult, RollSpec};
+use crate::environment::{CheckContext, Lighting, Obscurement, Sense, Weather};
+
+pub mod import;
+mod migrate;
+
+pub use migrate::CONFIG_VERSION;
 
 /// Ability score categories
-#[derive(Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum Ability {
     Strength,
     Dexterity,
@@ -23,10 +31,46 @@ pub enum Ability {
     Wisdom,
     Charisma,
 }
+impl Ability {
+    /// Every ability, in character-sheet order.
+    pub const ALL: [Ability; 6] = [
+        Ability::Strength,
+        Ability::Dexterity,
+        Ability::Constitution,
+        Ability::Intelligence,
+        Ability::Wisdom,
+        Ability::Charisma,
+    ];
+
+    /// The ability's three-letter abbreviation, such as "STR".
+    pub fn abbrev(&self) -> &'static str {
+        match self {
+            Ability::Strength => "STR",
+            Ability::Dexterity => "DEX",
+            Ability::Constitution => "CON",
+            Ability::Intelligence => "INT",
+            Ability::Wisdom => "WIS",
+            Ability::Charisma => "CHA",
+        }
+    }
+}
+impl std::fmt::Display for Ability {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name: &str = match self {
+            Ability::Strength => "Strength",
+            Ability::Dexterity => "Dexterity",
+            Ability::Constitution => "Constitution",
+            Ability::Intelligence => "Intelligence",
+            Ability::Wisdom => "Wisdom",
+            Ability::Charisma => "Charisma",
+        };
+        write!(f, "{}", name)
+    }
+}
 impl FromStr for Ability {
     type Err = ();
     fn from_str(input: &str) -> Result<Ability, Self::Err> {
-        match input {
+        match input.to_lowercase().as_str() {
             "strength" => Ok(Ability::Strength),
             "dexterity" => Ok(Ability::Dexterity),
             "constitution" => Ok(Ability::Constitution),
@@ -38,153 +82,4650 @@ impl FromStr for Ability {
     }
 }
 
-/// Things which one can be proficient in
-#[derive(Eq, Hash, PartialEq)]
+/// Things which one can be proficient in: the eighteen skills.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum Proficiency {
+    Acrobatics,
+    AnimalHandling,
+    Arcana,
+    Athletics,
+    Deception,
+    History,
     Insight,
+    Intimidation,
     Investigation,
+    Medicine,
+    Nature,
     Perception,
+    Performance,
+    Persuasion,
+    Religion,
+    SleightOfHand,
+    Stealth,
+    Survival,
 }
 impl FromStr for Proficiency {
     type Err = ();
     fn from_str(input: &str) -> Result<Proficiency, Self::Err> {
-        match input {
+        match input.to_lowercase().as_str() {
+            "acrobatics" => Ok(Proficiency::Acrobatics),
+            "animal handling" | "animal_handling" | "animalhandling" => Ok(Proficiency::AnimalHandling),
+            "arcana" => Ok(Proficiency::Arcana),
+            "athletics" => Ok(Proficiency::Athletics),
+            "deception" => Ok(Proficiency::Deception),
+            "history" => Ok(Proficiency::History),
             "insight"  => Ok(Proficiency::Insight),
+            "intimidation" => Ok(Proficiency::Intimidation),
             "investigation"  => Ok(Proficiency::Investigation),
+            "medicine" => Ok(Proficiency::Medicine),
+            "nature" => Ok(Proficiency::Nature),
             "perception"  => Ok(Proficiency::Perception),
+            "performance" => Ok(Proficiency::Performance),
+            "persuasion" => Ok(Proficiency::Persuasion),
+            "religion" => Ok(Proficiency::Religion),
+            "sleight of hand" | "sleight_of_hand" | "sleightofhand" => Ok(Proficiency::SleightOfHand),
+            "stealth" => Ok(Proficiency::Stealth),
+            "survival" => Ok(Proficiency::Survival),
             _ => Err(()),
         }
     }
 }
 
-/// A player character.
-///
-/// This struct is roughly equivalent to a dndbeyond character
-/// sheet, in that it encapsulates the rules for rolling for
-/// various checks alongside the values that numerically affect
-/// the outcome.
-pub struct PC<'a> {
-    /// The source of randomness that a character uses to make rolls.
-    dice: &'a Dice,
-    /// A mapping from ability to ability score.
-    abilities: HashMap<Ability, u8>,
-    /// A mapping from proficiency to proficiency level.
-    ///
-    /// When making a roll, the PC multiplies their proficiency bonus
-    /// by their proficiency level (0, 1, or 2) to determine the
-    /// proficiency modifier.
-    proficiencies: HashMap<Proficiency, u8>,
-    /// The PC's proficiency bonus.
-    proficiency_bonus: u8,
-    /// Whether the PC has darkvision.
-    darkvision: bool,
+impl std::fmt::Display for Proficiency {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name: &str = match self {
+            Proficiency::Acrobatics => "Acrobatics",
+            Proficiency::AnimalHandling => "Animal Handling",
+            Proficiency::Arcana => "Arcana",
+            Proficiency::Athletics => "Athletics",
+            Proficiency::Deception => "Deception",
+            Proficiency::History => "History",
+            Proficiency::Insight => "Insight",
+            Proficiency::Intimidation => "Intimidation",
+            Proficiency::Investigation => "Investigation",
+            Proficiency::Medicine => "Medicine",
+            Proficiency::Nature => "Nature",
+            Proficiency::Perception => "Perception",
+            Proficiency::Performance => "Performance",
+            Proficiency::Persuasion => "Persuasion",
+            Proficiency::Religion => "Religion",
+            Proficiency::SleightOfHand => "Sleight of Hand",
+            Proficiency::Stealth => "Stealth",
+            Proficiency::Survival => "Survival",
+        };
+        write!(f, "{}", name)
+    }
 }
 
-impl PC<'_> {
-    /// Create a character from a configuration file.
-    ///
-    /// * `dice` - The dice that the PC will use to generate randomness.
-    /// * `config` - A JSON configuration file which lays out the character's attributes.
-    pub fn new<'a, 'b>(dice: &'a Dice, config: &'b mut File) -> std::io::Result<PC<'a>> {
-        let mut abilities: HashMap<Ability, u8> = HashMap::new();
-        let mut proficiencies: HashMap<Proficiency, u8> = HashMap::new();
+impl Proficiency {
+    /// Every skill, in alphabetical order.
+    pub const ALL: [Proficiency; 18] = [
+        Proficiency::Acrobatics,
+        Proficiency::AnimalHandling,
+        Proficiency::Arcana,
+        Proficiency::Athletics,
+        Proficiency::Deception,
+        Proficiency::History,
+        Proficiency::Insight,
+        Proficiency::Intimidation,
+        Proficiency::Investigation,
+        Proficiency::Medicine,
+        Proficiency::Nature,
+        Proficiency::Perception,
+        Proficiency::Performance,
+        Proficiency::Persuasion,
+        Proficiency::Religion,
+        Proficiency::SleightOfHand,
+        Proficiency::Stealth,
+        Proficiency::Survival,
+    ];
 
-        // Parse the file to a JSON value
-        let mut config_string: String = String::new();
-        config.read_to_string(&mut config_string)?;
-        let config_data: JsonValue = match json::parse(&config_string) {
-            Ok(data) => data,
-            Err(_error) => panic!(),
-        };
+    /// Whether a check with this skill relies on sight, and so
+    /// suffers in poor light.
+    pub fn relies_on_sight(&self) -> bool {
+        matches!(self, Proficiency::Perception | Proficiency::Investigation)
+    }
 
-        // Insert each ability score into the PC's abilities.
-        // Panic if they don't coerce to the enum or if their values
-        // don't fit in a u8.
-        for (ability, score) in config_data["abilities"].entries() {
-            let ability_val: Ability = match Ability::from_str(ability) {
-                Ok(data) => data,
-                Err(_error) => panic!(),
-            };
-            let score_val: u8 = match score.as_u8() {
-                Some(data) => data,
-                None => panic!(),
-            };
-            abilities.insert(ability_val, score_val);
+    /// The ability a check with this skill normally uses.
+    pub fn default_ability(&self) -> Ability {
+        match self {
+            Proficiency::Athletics => Ability::Strength,
+            Proficiency::Acrobatics | Proficiency::SleightOfHand | Proficiency::Stealth => Ability::Dexterity,
+            Proficiency::Arcana | Proficiency::History | Proficiency::Investigation | Proficiency::Nature | Proficiency::Religion => Ability::Intelligence,
+            Proficiency::AnimalHandling | Proficiency::Insight | Proficiency::Medicine | Proficiency::Perception | Proficiency::Survival => Ability::Wisdom,
+            Proficiency::Deception | Proficiency::Intimidation | Proficiency::Performance | Proficiency::Persuasion => Ability::Charisma,
         }
+    }
+}
 
-        // Insert each proficiency into the PC's proficiencies.
-        // Panic if they don't coerce to the enum or if their values
-        // don't fit in a u8.
-        for (proficiency, level) in config_data["proficiencies"].entries() {
-            let proficiency_val: Proficiency = match Proficiency::from_str(proficiency) {
-                Ok(data) => data,
-                Err(_error) => panic!(),
-            };
-            let level_val: u8 = match level.as_u8() {
-                Some(data) => data,
-                None => panic!(),
-            };
-            proficiencies.insert(proficiency_val, level_val);
+/// A skill a check can use: one of the eighteen, one the table
+/// defines for itself, or a tool.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Skill {
+    /// One of the eighteen standard skills.
+    Standard(Proficiency),
+    /// A skill a character's config defines; holds its name.
+    Custom(String),
+    /// A tool, such as thieves' tools; holds its name in lowercase.
+    Tool(String),
+}
+impl From<Proficiency> for Skill {
+    fn from(proficiency: Proficiency) -> Skill {
+        Skill::Standard(proficiency)
+    }
+}
+impl std::fmt::Display for Skill {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Skill::Standard(proficiency) => write!(f, "{}", proficiency),
+            Skill::Custom(name) | Skill::Tool(name) => write!(f, "{}", name),
         }
+    }
+}
 
-        // Set the proficiency bonus.
-        let proficiency_bonus: u8 = match config_data["proficiency_bonus"].as_u8() {
-            Some(data) => data,
-            None => panic!(),
-        };
+/// A homebrew skill, keyed off an ability like the standard ones.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CustomSkill {
+    /// The ability a check with the skill uses.
+    pub ability: Ability,
+    /// The character's proficiency level in the skill.
+    pub level: ProficiencyLevel,
+}
 
-        // Set the darkvision status.
-        let darkvision: bool = match config_data["darkvision"].as_bool() {
-            Some(data) => data,
-            None => panic!(),
-        };
+/// How proficient a character is in a skill or saving throw.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ProficiencyLevel {
+    /// Not proficient.
+    #[default]
+    None,
+    /// Proficient, adding the proficiency bonus.
+    Proficient,
+    /// Expertise, adding the proficiency bonus twice.
+    Expertise,
+}
+impl ProficiencyLevel {
+    /// How many times the proficiency bonus is added at this level.
+    pub fn multiplier(&self) -> u8 {
+        match self {
+            ProficiencyLevel::None => 0,
+            ProficiencyLevel::Proficient => 1,
+            ProficiencyLevel::Expertise => 2,
+        }
+    }
+}
+
+/// How a character's proficiency modifier on a check is made up.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ProficiencyModifier {
+    /// The character's proficiency bonus.
+    pub bonus: u8,
+    /// The character's proficiency level in the skill.
+    pub level: ProficiencyLevel,
+    /// Whether half the bonus is added for Jack of All Trades, since
+    /// the character is not proficient.
+    pub jack_of_all_trades: bool,
+}
+impl ProficiencyModifier {
+    /// The number added to the check: the bonus times the level's
+    /// multiplier, or half the bonus, rounded down, for Jack of All Trades.
+    pub fn total(&self) -> u8 {
+        if self.jack_of_all_trades {
+            self.bonus / 2
+        } else {
+            self.bonus * self.level.multiplier()
+        }
+    }
+}
+impl std::fmt::Display for ProficiencyModifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.level {
+            _ if self.jack_of_all_trades => write!(f, "+{} (half of +{}, Jack of All Trades)", self.total(), self.bonus),
+            ProficiencyLevel::None => write!(f, "+0 (not proficient)"),
+            ProficiencyLevel::Proficient => write!(f, "+{} (proficient)", self.total()),
+            ProficiencyLevel::Expertise => write!(f, "+{} (expertise, twice +{})", self.total(), self.bonus),
+        }
+    }
+}
 
-        Ok(PC {dice, abilities, proficiencies, proficiency_bonus, darkvision})
+impl TryFrom<u8> for ProficiencyLevel {
+    type Error = ();
+    fn try_from(level: u8) -> Result<ProficiencyLevel, Self::Error> {
+        match level {
+            0 => Ok(ProficiencyLevel::None),
+            1 => Ok(ProficiencyLevel::Proficient),
+            2 => Ok(ProficiencyLevel::Expertise),
+            _ => Err(()),
+        }
+    }
+}
+impl FromStr for ProficiencyLevel {
+    type Err = ();
+    fn from_str(input: &str) -> Result<ProficiencyLevel, Self::Err> {
+        match input {
+            "none" => Ok(ProficiencyLevel::None),
+            "proficient" => Ok(ProficiencyLevel::Proficient),
+            "expertise" => Ok(ProficiencyLevel::Expertise),
+            _ => Err(()),
+        }
     }
+}
 
-    /// Given a profiency category, return the PC's proficiency modifier.
-    ///
-    /// The proficiency modifier is the PC's proficiency bonus multiplied
-    /// by their level of proficiency (0, 1, or 2 for expertise).
-    /// * `proficiency` - The type of proficiency whose modifier to retrieve.
-    pub fn proficiency_modifier(&self, proficiency: Proficiency) -> u8 {
-        self.proficiency_bonus * self.proficiencies[&proficiency]
+/// Conditions which change how a character's checks resolve.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Condition {
+    /// Automatically fails checks which rely on sight.
+    Blinded,
+    /// Automatically fails Perception checks which rely on hearing.
+    Deafened,
+    /// Has disadvantage on ability checks.
+    Poisoned,
+    /// Automatically fails ability checks.
+    Unconscious,
+}
+impl FromStr for Condition {
+    type Err = ();
+    fn from_str(input: &str) -> Result<Condition, Self::Err> {
+        match input {
+            "blinded" => Ok(Condition::Blinded),
+            "deafened" => Ok(Condition::Deafened),
+            "poisoned" => Ok(Condition::Poisoned),
+            "unconscious" => Ok(Condition::Unconscious),
+            _ => Err(()),
+        }
+    }
+}
+impl std::fmt::Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Condition::Blinded => write!(f, "blinded"),
+            Condition::Deafened => write!(f, "deafened"),
+            Condition::Poisoned => write!(f, "poisoned"),
+            Condition::Unconscious => write!(f, "unconscious"),
+        }
     }
+}
 
-    /// Given an ability score, return the PC's ability modifier.
-    /// * `ability` - The ability whose modifier to retrieve.
-    pub fn ability_modifier(&self, ability: Ability) -> i8 {
-        let ability_score: u8 = self.abilities[&ability];
-        (ability_score as i8 - 10) / 2
+/// Racial traits a character can have.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Trait {
+    /// Proficiency in Perception.
+    KeenSenses,
+    /// Advantage on saves against being charmed. No save yet says
+    /// what it is against, so this has no effect on rolls.
+    FeyAncestry,
+    /// Rerolls natural 1s (Halfling Luck).
+    Lucky,
+    /// Expertise in History checks about stonework. No check yet says
+    /// what it is about, so this has no effect on rolls.
+    Stonecunning,
+    /// Disadvantage on Perception checks that rely on sight while the
+    /// character or their target is in direct sunlight.
+    SunlightSensitivity,
+    /// Advantage on Perception checks that rely on hearing or smell.
+    KeenHearingAndSmell,
+    /// Seeing normally in darkness, magical or not, within
+    /// `DEVILS_SIGHT_RANGE` (the warlock's Eldritch Invocation).
+    DevilsSight,
+    /// A trait this crate does not know; holds the name as written.
+    Unknown(String),
+}
+impl From<&str> for Trait {
+    fn from(input: &str) -> Trait {
+        match input {
+            "keen_senses" => Trait::KeenSenses,
+            "fey_ancestry" => Trait::FeyAncestry,
+            "lucky" => Trait::Lucky,
+            "stonecunning" => Trait::Stonecunning,
+            "sunlight_sensitivity" => Trait::SunlightSensitivity,
+            "keen_hearing_and_smell" => Trait::KeenHearingAndSmell,
+            "devils_sight" => Trait::DevilsSight,
+            _ => Trait::Unknown(String::from(input)),
+        }
+    }
+}
+impl std::fmt::Display for Trait {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Trait::KeenSenses => write!(f, "keen_senses"),
+            Trait::FeyAncestry => write!(f, "fey_ancestry"),
+            Trait::Lucky => write!(f, "lucky"),
+            Trait::Stonecunning => write!(f, "stonecunning"),
+            Trait::SunlightSensitivity => write!(f, "sunlight_sensitivity"),
+            Trait::KeenHearingAndSmell => write!(f, "keen_hearing_and_smell"),
+            Trait::DevilsSight => write!(f, "devils_sight"),
+            Trait::Unknown(name) => write!(f, "{}", name),
+        }
     }
+}
 
-    /// Roll a check.
-    ///
-    /// Roll a d20, adding the appropriate ability and proficiency modifiers,
-    /// and with the appropriate level of advantage.
-    /// * `ability` - The ability to apply to the check.
-    /// * `proficiency` - The proficiency to apply to the check.
-    /// * `advantage` - The advantage level of the check.
-    pub fn check(&mut self, ability: Ability, proficiency: Proficiency, advantage: Advantage) -> i8 {
-        let proficiency_bonus: u8 = self.proficiency_modifier(proficiency);
-        let ability_score: i8 = self.ability_modifier(ability);
-        let total_modifier: i8 = proficiency_bonus as i8 + ability_score;
+/// What a magic item does for its bearer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ItemEffect {
+    /// A bonus to every ability check, as from a Stone of Good Luck.
+    CheckBonus(i32),
+    /// A bonus to checks with one skill.
+    SkillBonus(Proficiency, i32),
+    /// Advantage on checks with one skill, as from Eyes of the Eagle.
+    SkillAdvantage(Proficiency),
+    /// Darkvision reaching some distance in feet, as from Goggles of Night.
+    Darkvision(u16),
+}
+
+/// A magic item a character carries.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Item {
+    /// The item's name, which is shown wherever it affects a check.
+    pub name: String,
+    /// What the item does.
+    pub effects: Vec<ItemEffect>,
+}
 
-        self.dice.d(20, total_modifier, advantage)
+/// The checks a temporary modifier applies to.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ModifierScope {
+    /// Checks with one skill.
+    Skill(Proficiency),
+    /// Checks with one ability, whatever the skill.
+    Ability(Ability),
+    /// Every check.
+    AllChecks,
+}
+impl ModifierScope {
+    /// Whether the modifier applies to a check.
+    /// * `ability` - The ability the check uses.
+    /// * `skill` - The skill the check uses, if any.
+    pub fn applies_to(&self, ability: Ability, skill: Option<&Skill>) -> bool {
+        match self {
+            ModifierScope::Skill(proficiency) => skill == Some(&Skill::Standard(*proficiency)),
+            ModifierScope::Ability(scope_ability) => *scope_ability == ability,
+            ModifierScope::AllChecks => true,
+        }
     }
+}
 
-    /// Roll a Wisdom (Perception) check.
-    ///
-    /// Apply all available modifiers, including potential disadvantage from
-    /// lighting conditions.
-    /// * `advantage` - Any additional advantage beyond the usual perception parameters.
-    /// * `lighting` - The level of environmental lighting.
-    pub fn perception_check(&mut self, advantage: Advantage, lighting: Lighting) {
-        let lighting_advantage: Advantage = match lighting {
-            Lighting::Dark => if self.darkvision { Advantage::Disadvantage } else { Advantage::Fail },
-            Lighting::Dim => if self.darkvision { Advantage::None } else { Advantage::Disadvantage },
-            Lighting::Light => Advantage::None,
-        };
-        self.check(Ability::Wisdom, Proficiency::Perception, advantage + lighting_advantage);
+/// A named modifier layered onto checks during a session, such as
+/// +2 from an item or -2 from a curse.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TemporaryModifier {
+    /// The name of the effect granting the modifier.
+    pub name: String,
+    /// The checks the modifier applies to.
+    pub scope: ModifierScope,
+    /// The number added to the checks.
+    pub amount: i32,
+}
+
+/// A spell affecting a character's checks while it lasts.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ActiveEffect {
+    /// Advantage on checks with the chosen ability.
+    EnhanceAbility(Ability),
+    /// `PASS_WITHOUT_TRACE_BONUS` added to Stealth checks.
+    PassWithoutTrace,
+}
+impl ActiveEffect {
+    /// The name of the spell, as shown in a check's breakdown.
+    pub fn spell(&self) -> &'static str {
+        match self {
+            ActiveEffect::EnhanceAbility(_) => "Enhance Ability",
+            ActiveEffect::PassWithoutTrace => "Pass Without Trace",
+        }
+    }
+
+    /// The flat bonus the effect gives checks, if any.
+    fn modifier(&self) -> Option<TemporaryModifier> {
+        match self {
+            ActiveEffect::EnhanceAbility(_) => None,
+            ActiveEffect::PassWithoutTrace => Some(TemporaryModifier {
+                name: String::from(self.spell()),
+                scope: ModifierScope::Skill(Proficiency::Stealth),
+                amount: PASS_WITHOUT_TRACE_BONUS,
+            }),
+        }
+    }
+}
+impl std::fmt::Display for ActiveEffect {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ActiveEffect::EnhanceAbility(ability) => write!(f, "{} ({})", self.spell(), ability),
+            ActiveEffect::PassWithoutTrace => write!(f, "{}", self.spell()),
+        }
+    }
+}
+
+/// A spell on a character, with how long it has left.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LastingEffect {
+    /// What the spell does.
+    pub effect: ActiveEffect,
+    /// The rounds the spell has left, or `None` if it lasts until removed.
+    pub rounds_left: Option<u16>,
+}
+
+/// An extra die waiting to be added to a character's checks, such as
+/// guidance's d4.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingBonus {
+    /// The die, named after the effect granting it.
+    pub bonus: Bonus,
+    /// Whether the die is added to every check until removed, rather
+    /// than only the next one.
+    pub persistent: bool,
+}
+
+/// A check a character made, with every number that went into it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckResult {
+    /// The name of the character who made the check.
+    name: String,
+    /// The ability the check used.
+    ability: Ability,
+    /// The skill the check used, if any.
+    skill: Option<Skill>,
+    /// The modifier from the ability score.
+    ability_modifier: i32,
+    /// The modifier from proficiency.
+    proficiency_modifier: u8,
+    /// The temporary modifiers which applied.
+    modifiers: Vec<TemporaryModifier>,
+    /// Every source of advantage or disadvantage on the check.
+    advantage_sources: AdvantageSet,
+    /// Whether Inspiration was spent on the check.
+    inspiration_spent: bool,
+    /// The sense a Perception check relied on, if it was made in
+    /// some circumstances.
+    sense: Option<Sense>,
+    /// The roll itself.
+    roll: RollResult,
+}
+
+impl CheckResult {
+    /// The name of the character who made the check.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The ability the check used.
+    pub fn ability(&self) -> Ability {
+        self.ability
+    }
+
+    /// The skill the check used, if any.
+    pub fn skill(&self) -> Option<&Skill> {
+        self.skill.as_ref()
+    }
+
+    /// The standard skill the check used, if it used one.
+    pub fn proficiency(&self) -> Option<Proficiency> {
+        match self.skill {
+            Some(Skill::Standard(proficiency)) => Some(proficiency),
+            Some(Skill::Custom(_)) | Some(Skill::Tool(_)) | None => None,
+        }
+    }
+
+    /// The modifier from the ability score.
+    pub fn ability_modifier(&self) -> i32 {
+        self.ability_modifier
+    }
+
+    /// The modifier from proficiency.
+    pub fn proficiency_modifier(&self) -> u8 {
+        self.proficiency_modifier
+    }
+
+    /// The temporary modifiers which applied.
+    pub fn modifiers(&self) -> &[TemporaryModifier] {
+        &self.modifiers
+    }
+
+    /// The advantage level the check was rolled with.
+    pub fn advantage(&self) -> Advantage {
+        self.roll.advantage()
+    }
+
+    /// Every source of advantage or disadvantage on the check.
+    pub fn advantage_sources(&self) -> &AdvantageSet {
+        &self.advantage_sources
+    }
+
+    /// Whether Inspiration was spent on the check.
+    pub fn inspiration_spent(&self) -> bool {
+        self.inspiration_spent
+    }
+
+    /// The sense a Perception check relied on, if it was made in some
+    /// circumstances; see `PC::check_in`.
+    pub fn sense(&self) -> Option<Sense> {
+        self.sense
+    }
+
+    /// Every die rolled, in order, including any not kept.
+    pub fn faces(&self) -> &[u16] {
+        self.roll.faces()
+    }
+
+    /// The face showing on the kept die.
+    pub fn natural(&self) -> u16 {
+        self.roll.natural()
+    }
+
+    /// The final value of the check.
+    pub fn total(&self) -> i32 {
+        self.roll.total()
+    }
+
+    /// The roll itself.
+    pub fn roll(&self) -> &RollResult {
+        &self.roll
+    }
+}
+
+/// One ability on a stat block.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AbilityLine {
+    /// The ability.
+    pub ability: Ability,
+    /// The character's score in it.
+    pub score: u8,
+    /// The modifier the score gives.
+    pub modifier: i32,
+}
+
+/// One skill on a stat block.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SkillLine {
+    /// The skill.
+    pub proficiency: Proficiency,
+    /// The ability the skill usually uses.
+    pub ability: Ability,
+    /// The character's proficiency level in the skill.
+    pub level: ProficiencyLevel,
+    /// Everything a check with the skill adds, as `check` adds it.
+    pub modifier: i32,
+}
+
+/// Everything displayable about a character, worked out at once and
+/// owning its data, so that it can outlive the character's borrow.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatBlock {
+    /// The character's name.
+    pub name: String,
+    /// Every ability, in character-sheet order.
+    pub abilities: Vec<AbilityLine>,
+    /// Every standard skill, in alphabetical order, with its usual ability.
+    pub skills: Vec<SkillLine>,
+    /// The character's passive scores in each of `PASSIVE_SKILLS`.
+    pub passives: Vec<(Proficiency, i32)>,
+    /// How far the character's darkvision reaches, in feet, if they have it.
+    pub darkvision: Option<u16>,
+    /// How far the character's truesight reaches, in feet, if they have it.
+    pub truesight: Option<u16>,
+    /// The character's conditions, sorted by name.
+    pub conditions: Vec<Condition>,
+    /// The character's level of exhaustion.
+    pub exhaustion: u8,
+    /// The character's hit points now and their maximum, if they track
+    /// hit points.
+    pub hit_points: Option<(u16, u16)>,
+}
+
+/// What a check would add, worked out without rolling.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CheckPreview {
+    /// The ability the check would use.
+    pub ability: Ability,
+    /// The proficiency the check would use.
+    pub proficiency: Proficiency,
+    /// The modifier from the ability score.
+    pub ability_modifier: i32,
+    /// The modifier from proficiency.
+    pub proficiency_modifier: u8,
+    /// The temporary modifiers which would apply.
+    pub modifiers: Vec<TemporaryModifier>,
+    /// Every modifier added together.
+    pub total_modifier: i32,
+    /// The advantage level the check would be rolled with.
+    pub advantage: Advantage,
+    /// Every source of advantage or disadvantage on the check.
+    pub advantage_sources: AdvantageSet,
+    /// The chance of meeting the DC, if one was given.
+    pub success_probability: Option<f64>,
+}
+
+impl std::fmt::Display for CheckPreview {
+    /// Itemize the check, as in "d20 + 2 (Wisdom) + 6 (Perception)
+    /// + 2 (cloak), Disadvantage from dim light without darkvision".
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut parts: Vec<(i32, String)> = vec![
+            (self.ability_modifier, self.ability.to_string()),
+            (self.proficiency_modifier as i32, self.proficiency.to_string()),
+        ];
+        parts.extend(self.modifiers.iter().map(|modifier| (modifier.amount, modifier.name.clone())));
+        write!(f, "d20")?;
+        for (amount, source) in parts.iter().filter(|(amount, _)| *amount != 0) {
+            let sign: char = if *amount < 0 { '-' } else { '+' };
+            write!(f, " {} {} ({})", sign, amount.abs(), source)?;
+        }
+        if !self.advantage_sources.sources().is_empty() {
+            write!(f, ", {:?} from {}", self.advantage, self.advantage_sources.reasons().join(" and "))?;
+        }
+        if let Some(probability) = self.success_probability {
+            write!(f, ", {:.0}% to succeed", probability * 100.0)?;
+        }
+        Ok(())
+    }
+}
+
+/// Something a rest changed about a character.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RestChange {
+    /// Exhaustion went down; holds the new level.
+    ExhaustionReduced(u8),
+    /// Hit points came back; holds how many.
+    HitPointsRestored(u16),
+    /// A spell ran out.
+    EffectEnded(ActiveEffect),
+    /// An unspent Bardic Inspiration die ran out.
+    InspirationDieLapsed(DieSize),
+    /// Help from another character ran out; holds the helper's name.
+    HelpLapsed(String),
+    /// A one-shot bonus die ran out; holds its label.
+    BonusDieLapsed(String),
+}
+
+impl std::fmt::Display for RestChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RestChange::ExhaustionReduced(level) => write!(f, "exhaustion drops to {}", level),
+            RestChange::HitPointsRestored(amount) => write!(f, "regains {} hit points", amount),
+            RestChange::EffectEnded(effect) => write!(f, "{} ends", effect),
+            RestChange::InspirationDieLapsed(sides) => write!(f, "Bardic Inspiration {} lapses", sides),
+            RestChange::HelpLapsed(helper) => write!(f, "help from {} lapses", helper),
+            RestChange::BonusDieLapsed(label) => write!(f, "{} lapses", label),
+        }
+    }
+}
+
+/// A dying character's death saving throws since they dropped.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DeathSaves {
+    /// The successes so far, up to `DEATH_SAVES_NEEDED`.
+    pub successes: u8,
+    /// The failures so far, up to `DEATH_SAVES_NEEDED`.
+    pub failures: u8,
+}
+
+impl DeathSaves {
+    /// Whether the character has succeeded enough to stabilize.
+    pub fn is_stable(&self) -> bool {
+        self.successes >= DEATH_SAVES_NEEDED
+    }
+
+    /// Whether the character has failed enough to die.
+    pub fn is_dead(&self) -> bool {
+        self.failures >= DEATH_SAVES_NEEDED
     }
-}
\ No newline at end of file
+}
+
+/// What a single death saving throw counted as.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DeathSaveOutcome {
+    /// The roll was 10 or more.
+    Success,
+    /// The roll was under 10.
+    Failure,
+    /// A natural 1, which counts as two failures.
+    DoubleFailure,
+    /// A natural 20: the character regains 1 hit point, and their
+    /// death saves start over.
+    Revived,
+}
+
+/// A death saving throw, and where it left the character.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeathSaveResult {
+    /// The roll itself.
+    roll: RollResult,
+    /// What the roll counted as.
+    outcome: DeathSaveOutcome,
+    /// The character's death saves after the roll.
+    state: DeathSaves,
+}
+
+impl DeathSaveResult {
+    /// The roll itself.
+    pub fn roll(&self) -> &RollResult {
+        &self.roll
+    }
+
+    /// What the roll counted as.
+    pub fn outcome(&self) -> DeathSaveOutcome {
+        self.outcome
+    }
+
+    /// The character's death saves after the roll.
+    pub fn state(&self) -> DeathSaves {
+        self.state
+    }
+}
+
+/// The successes that stabilize a dying character, or failures that kill them.
+pub const DEATH_SAVES_NEEDED: u8 = 3;
+
+/// The lowest death saving throw which succeeds.
+pub const DEATH_SAVE_DC: i32 = 10;
+
+/// The rounds a short rest takes: an hour of six-second rounds.
+pub const SHORT_REST_ROUNDS: u16 = 600;
+
+/// The bonus Pass Without Trace gives Stealth checks.
+pub const PASS_WITHOUT_TRACE_BONUS: i32 = 10;
+
+/// How far darkvision reaches, in feet, when a config only says that
+/// a character has it.
+pub const DEFAULT_DARKVISION_RANGE: u16 = 60;
+
+/// How far Devil's Sight sees through darkness, in feet.
+pub const DEVILS_SIGHT_RANGE: u16 = 120;
+
+/// The highest level of exhaustion, at which a character dies.
+pub const MAX_EXHAUSTION: u8 = 6;
+
+/// Reasons a character cannot be created from a configuration.
+#[derive(Debug)]
+pub enum PcError {
+    /// The configuration file could not be read.
+    Io(std::io::Error),
+    /// The configuration is not valid JSON of the expected shape.
+    Parse(serde_json::Error),
+    /// A skill was named that does not exist; holds the name.
+    UnknownProficiency(String),
+    /// A condition was named that does not exist; holds the name.
+    UnknownCondition(String),
+    /// A proficiency level was named that does not exist; holds the name.
+    UnknownProficiencyLevel(String),
+    /// An advantage level was named that does not exist; holds the name.
+    UnknownAdvantage(String),
+    /// Values are outside the ranges the rules allow; holds every one.
+    OutOfRange(Vec<ValidationIssue>),
+    /// Two characters in a party share a name; holds the name.
+    DuplicateName(String),
+    /// Two characters in a party share an identifier; holds the identifier.
+    DuplicateId(PcId),
+    /// The config is of a newer version than this crate reads; holds
+    /// its version.
+    NewerVersion(u64),
+    /// A character's `"base"` leads back to itself; holds each base in
+    /// the chain, ending with the one repeated.
+    BaseCycle(Vec<String>),
+    /// A character's `"base"` chain is more than `MAX_BASE_DEPTH` long;
+    /// holds the base that would go too deep.
+    BaseTooDeep(String),
+    /// Two keys give values which disagree.
+    Conflict {
+        /// The first key.
+        first: &'static str,
+        /// The key which disagrees with it.
+        second: &'static str,
+    },
+}
+
+impl std::fmt::Display for PcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PcError::Io(error) => write!(f, "cannot read character config: {}", error),
+            PcError::Parse(error) => write!(f, "cannot parse character config: {}", error),
+            PcError::UnknownProficiency(name) => write!(f, "unknown proficiency \"{}\"", name),
+            PcError::UnknownCondition(name) => write!(f, "unknown condition \"{}\"", name),
+            PcError::UnknownProficiencyLevel(name) => write!(f, "unknown proficiency level \"{}\"", name),
+            PcError::UnknownAdvantage(name) => write!(f, "unknown advantage level \"{}\"", name),
+            PcError::OutOfRange(issues) => {
+                let shown: Vec<String> = issues.iter().map(|issue| issue.to_string()).collect();
+                write!(f, "values out of range: {}", shown.join(", "))
+            },
+            PcError::DuplicateName(name) => write!(f, "more than one character is named \"{}\"", name),
+            PcError::DuplicateId(id) => write!(f, "more than one character has the identifier {}", id),
+            PcError::NewerVersion(version) => write!(f, "config version {} needs a newer rollforgrue, which reads up to version {}", version, CONFIG_VERSION),
+            PcError::BaseCycle(chain) => write!(f, "character bases form a cycle: {}", chain.join(" -> ")),
+            PcError::BaseTooDeep(base) => write!(f, "character base \"{}\" is more than {} bases deep", base, MAX_BASE_DEPTH),
+            PcError::Conflict {first, second} => write!(f, "\"{}\" and \"{}\" disagree", first, second),
+        }
+    }
+}
+
+/// Reasons a check with Inspiration can be refused.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InspirationError {
+    /// The character has no Inspiration to spend.
+    NotHeld,
+    /// The check itself could not be rolled.
+    Dice(DiceError),
+}
+
+impl std::fmt::Display for InspirationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            InspirationError::NotHeld => write!(f, "no Inspiration to spend"),
+            InspirationError::Dice(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for InspirationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InspirationError::Dice(error) => Some(error),
+            InspirationError::NotHeld => None,
+        }
+    }
+}
+
+impl From<DiceError> for InspirationError {
+    fn from(error: DiceError) -> InspirationError {
+        InspirationError::Dice(error)
+    }
+}
+
+/// Reasons the Help action can be refused.
+#[derive(Debug, PartialEq, Eq)]
+pub enum HelpError {
+    /// The table requires proficiency to help, and the helper has none.
+    NotProficient {
+        /// The helper's name.
+        helper: String,
+        /// The skill they tried to help with.
+        skill: Proficiency,
+    },
+    /// A character tried to help themselves.
+    OwnHelp,
+}
+
+impl std::fmt::Display for HelpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            HelpError::NotProficient {helper, skill} => write!(f, "{} is not proficient in {}, so cannot help", helper, skill),
+            HelpError::OwnHelp => write!(f, "a character cannot help themselves"),
+        }
+    }
+}
+
+impl std::error::Error for HelpError {}
+
+/// Help one character offers another with a skill, giving advantage on
+/// the next check with it. A grant can be used only once.
+#[derive(Debug, PartialEq, Eq)]
+pub struct HelpGrant {
+    /// The helper's name.
+    helper: String,
+    /// The skill the help is with.
+    skill: Proficiency,
+}
+
+impl HelpGrant {
+    /// The helper's name.
+    pub fn helper(&self) -> &str {
+        &self.helper
+    }
+
+    /// The skill the help is with.
+    pub fn skill(&self) -> Proficiency {
+        self.skill
+    }
+
+    /// Whether the help applies to a check.
+    /// * `skill` - The skill the check uses, if any.
+    fn applies_to(&self, skill: Option<&Skill>) -> bool {
+        skill == Some(&Skill::Standard(self.skill))
+    }
+}
+
+/// Feats which change a character's sheet when taken.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Feat {
+    /// Skill Expert: proficiency in one skill, and expertise in another
+    /// the character is already proficient in.
+    SkillExpert {
+        /// The skill to gain proficiency in.
+        proficiency: Proficiency,
+        /// The proficient skill to gain expertise in.
+        expertise: Proficiency,
+    },
+}
+
+/// Reasons a feat cannot be taken.
+#[derive(Debug, PartialEq, Eq)]
+pub enum FeatError {
+    /// The character is already proficient in the skill they would
+    /// gain proficiency in; holds the skill.
+    AlreadyProficient(Proficiency),
+    /// Expertise was chosen in a skill the character is not proficient
+    /// in; holds the skill.
+    NotProficient(Proficiency),
+    /// Expertise was chosen in a skill the character already has
+    /// expertise in; holds the skill.
+    AlreadyExpert(Proficiency),
+    /// The same skill was chosen for both proficiency and expertise.
+    SameSkill(Proficiency),
+}
+
+impl std::fmt::Display for FeatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FeatError::AlreadyProficient(skill) => write!(f, "already proficient in {}", skill),
+            FeatError::NotProficient(skill) => write!(f, "cannot gain expertise in {} without proficiency", skill),
+            FeatError::AlreadyExpert(skill) => write!(f, "already has expertise in {}", skill),
+            FeatError::SameSkill(skill) => write!(f, "{} was chosen twice", skill),
+        }
+    }
+}
+
+impl std::error::Error for FeatError {}
+
+/// Reasons a check with a skill named at runtime can be refused.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SkillError {
+    /// No standard or custom skill has the name; holds the name.
+    Unknown(String),
+    /// The check itself could not be rolled.
+    Dice(DiceError),
+}
+
+impl std::fmt::Display for SkillError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SkillError::Unknown(name) => write!(f, "unknown skill \"{}\"", name),
+            SkillError::Dice(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for SkillError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SkillError::Dice(error) => Some(error),
+            SkillError::Unknown(_) => None,
+        }
+    }
+}
+
+impl From<DiceError> for SkillError {
+    fn from(error: DiceError) -> SkillError {
+        SkillError::Dice(error)
+    }
+}
+
+/// A config value outside the range the rules allow.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationIssue {
+    /// The key holding the value, such as "abilities.wisdom".
+    pub field: String,
+    /// The value given.
+    pub value: i64,
+}
+
+impl ValidationIssue {
+    /// Note a value which is out of range.
+    /// * `field` - The key holding the value.
+    /// * `value` - The value given.
+    fn new(field: &str, value: impl Into<i64>) -> ValidationIssue {
+        ValidationIssue {field: String::from(field), value: value.into()}
+    }
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "\"{}\" is {}", self.field, self.value)
+    }
+}
+
+impl std::error::Error for PcError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PcError::Io(error) => Some(error),
+            PcError::Parse(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for PcError {
+    fn from(error: std::io::Error) -> PcError {
+        PcError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for PcError {
+    fn from(error: serde_json::Error) -> PcError {
+        PcError::Parse(error)
+    }
+}
+
+/// A character's identifier, which stays the same however the party
+/// is reordered, and is saved with the character.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PcId(u64);
+
+/// The identifier the next character without one is given.
+static NEXT_PC_ID: AtomicU64 = AtomicU64::new(1);
+
+impl PcId {
+    /// An identifier no character has been given yet.
+    fn next() -> PcId {
+        PcId(NEXT_PC_ID.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// An identifier a config gives, which new identifiers will not repeat.
+    /// * `id` - The identifier as a number.
+    fn claim(id: u64) -> PcId {
+        NEXT_PC_ID.fetch_max(id.saturating_add(1), Ordering::Relaxed);
+        PcId(id)
+    }
+
+    /// The identifier as a number, as it is saved.
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+}
+
+impl std::fmt::Display for PcId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// The name of a character whose config gives none.
+pub const UNNAMED: &str = "Unnamed";
+
+/// The highest level a character can reach.
+pub const MAX_LEVEL: u8 = 20;
+
+/// The proficiency bonus of a character of a given level.
+///
+/// Levels outside 1 to `MAX_LEVEL` are treated as the nearest level
+/// in that range.
+/// * `level` - The character's level, from 1 to `MAX_LEVEL`.
+pub fn proficiency_bonus_for_level(level: u8) -> u8 {
+    let bonus: u8 = MIN_PROFICIENCY_BONUS + level.saturating_sub(1) / 4;
+    bonus.clamp(MIN_PROFICIENCY_BONUS, MAX_PROFICIENCY_BONUS)
+}
+
+/// The lowest proficiency bonus a character can have.
+pub const MIN_PROFICIENCY_BONUS: u8 = 2;
+
+/// The highest proficiency bonus a character can have.
+pub const MAX_PROFICIENCY_BONUS: u8 = 6;
+
+/// The furthest darkvision can reach, in feet.
+pub const MAX_DARKVISION_RANGE: u16 = 300;
+
+/// The furthest truesight can reach, in feet.
+pub const MAX_TRUESIGHT_RANGE: u16 = 300;
+
+/// The bonus the Alert feat adds to initiative.
+pub const ALERT_INITIATIVE_BONUS: i32 = 5;
+
+/// How many times members tied on initiative roll off before the tie
+/// is left to party order.
+pub const MAX_INITIATIVE_ROLL_OFFS: u8 = 3;
+
+/// The bonus the Observant feat adds to passive Perception and Investigation.
+pub const OBSERVANT_PASSIVE_BONUS: i32 = 5;
+
+/// The skills whose passive scores are shown on a character sheet.
+pub const PASSIVE_SKILLS: [Proficiency; 3] = [Proficiency::Perception, Proficiency::Investigation, Proficiency::Insight];
+
+/// The score of an ability a config leaves out.
+pub const DEFAULT_ABILITY_SCORE: u8 = 10;
+
+/// The highest ability score the rules allow.
+pub const MAX_ABILITY_SCORE: u8 = 30;
+
+/// A character's attributes as written in a JSON configuration file.
+///
+/// Names of abilities, skills, and conditions are kept as written,
+/// and checked when the `PC` is built.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PcConfig {
+    /// The version of the config format; a config which gives none is
+    /// of version 1, and is migrated when parsed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u64>,
+    /// The character's identifier. A character without one is given
+    /// one when loaded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+    /// The character's name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Each ability's score. Abilities left out score `DEFAULT_ABILITY_SCORE`.
+    #[serde(default)]
+    pub abilities: HashMap<String, u8>,
+    /// Each skill's proficiency level. Skills left out have no proficiency.
+    #[serde(default)]
+    pub proficiencies: HashMap<String, ProficiencyLevelConfig>,
+    /// Homebrew skills, by name.
+    #[serde(default)]
+    pub custom_skills: HashMap<String, CustomSkillConfig>,
+    /// Each tool's proficiency level, such as "thieves' tools".
+    #[serde(default)]
+    pub tools: HashMap<String, ProficiencyLevelConfig>,
+    /// The languages the character knows.
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// Each ability's saving throw proficiency level.
+    #[serde(default)]
+    pub saving_throws: HashMap<String, ProficiencyLevelConfig>,
+    /// The advantage level the character always has on checks with some
+    /// skills, such as "advantage" or "disadvantage", by skill name.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub skill_advantages: HashMap<String, String>,
+    /// The character's proficiency bonus. If this is left out, it
+    /// follows from the level, or is the lowest bonus if that is too.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proficiency_bonus: Option<u8>,
+    /// The character's level, from which the proficiency bonus follows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<u8>,
+    /// The character's senses beyond ordinary sight and hearing.
+    #[serde(default, skip_serializing_if = "SensesConfig::is_empty")]
+    pub senses: SensesConfig,
+    /// Whether the character has Halfling Luck.
+    #[serde(default)]
+    pub lucky: bool,
+    /// The luck points the character has, for the Lucky feat.
+    #[serde(default)]
+    pub luck_points: u8,
+    /// Whether the character has Elven Accuracy.
+    #[serde(default)]
+    pub elven_accuracy: bool,
+    /// Whether the character has Jack of All Trades.
+    #[serde(default)]
+    pub jack_of_all_trades: bool,
+    /// Whether the character starts with Inspiration.
+    #[serde(default)]
+    pub inspiration: bool,
+    /// Whether the character has the Observant feat.
+    #[serde(default)]
+    pub observant: bool,
+    /// Whether the character has the Alert feat.
+    #[serde(default)]
+    pub alert: bool,
+    /// Whether the character has Feral Instinct.
+    #[serde(default)]
+    pub feral_instinct: bool,
+    /// The table's limits on check totals.
+    #[serde(default)]
+    pub house_rules: HouseRulesConfig,
+    /// The character's racial traits, such as "keen_senses".
+    #[serde(default)]
+    pub traits: Vec<String>,
+    /// The magic items the character carries.
+    #[serde(default)]
+    pub items: Vec<ItemConfig>,
+    /// The conditions the character starts with.
+    #[serde(default)]
+    pub conditions: Vec<String>,
+    /// The character's level of exhaustion.
+    #[serde(default)]
+    pub exhaustion: u8,
+    /// The character's hit point maximum. A character without one does
+    /// not track hit points.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_hp: Option<u16>,
+    /// The character's hit points now, or their maximum if not given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_hp: Option<u16>,
+    /// The temporary modifiers on the character's checks.
+    #[serde(default)]
+    pub modifiers: Vec<ModifierConfig>,
+    /// Any keys the config does not use, such as misspelled ones.
+    #[serde(flatten)]
+    unknown: HashMap<String, serde_json::Value>,
+}
+
+/// A character's senses as a config gives them.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct SensesConfig {
+    /// How far the character's darkvision reaches, in feet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub darkvision: Option<u16>,
+    /// How far the character's truesight reaches, in feet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truesight: Option<u16>,
+}
+
+impl SensesConfig {
+    /// Whether the config gives no senses, and so need not be saved.
+    pub fn is_empty(&self) -> bool {
+        self.darkvision.is_none() && self.truesight.is_none()
+    }
+}
+
+/// How a config gives a proficiency level.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ProficiencyLevelConfig {
+    /// The level as a number: 0, 1, or 2 for expertise.
+    Number(u8),
+    /// The level by name: "none", "proficient", or "expertise".
+    Name(String),
+}
+
+impl ProficiencyLevelConfig {
+    /// Read the proficiency level, noting it if it is out of range.
+    /// * `field` - The key holding the level, for reporting.
+    /// * `issues` - The out-of-range values found so far.
+    fn resolve(&self, field: &str, issues: &mut Vec<ValidationIssue>) -> Result<ProficiencyLevel, PcError> {
+        match self {
+            ProficiencyLevelConfig::Number(level) => match ProficiencyLevel::try_from(*level) {
+                Ok(data) => Ok(data),
+                Err(_error) => {
+                    issues.push(ValidationIssue::new(field, *level));
+                    Ok(ProficiencyLevel::None)
+                },
+            },
+            ProficiencyLevelConfig::Name(name) => ProficiencyLevel::from_str(name).map_err(|_| PcError::UnknownProficiencyLevel(name.clone())),
+        }
+    }
+}
+
+impl From<ProficiencyLevel> for ProficiencyLevelConfig {
+    fn from(level: ProficiencyLevel) -> ProficiencyLevelConfig {
+        let name: &str = match level {
+            ProficiencyLevel::None => "none",
+            ProficiencyLevel::Proficient => "proficient",
+            ProficiencyLevel::Expertise => "expertise",
+        };
+        ProficiencyLevelConfig::Name(String::from(name))
+    }
+}
+
+/// A magic item, as written in a configuration file.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ItemConfig {
+    /// The item's name.
+    pub name: String,
+    /// What the item does.
+    #[serde(default)]
+    pub effects: Vec<ItemEffectConfig>,
+}
+
+/// What a magic item does, as written in a configuration file, such
+/// as `{"type": "skill_bonus", "skill": "perception", "amount": 2}`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ItemEffectConfig {
+    /// A bonus to every ability check.
+    CheckBonus {
+        /// The bonus.
+        amount: i32,
+    },
+    /// A bonus to checks with one skill.
+    SkillBonus {
+        /// The skill's name.
+        skill: String,
+        /// The bonus.
+        amount: i32,
+    },
+    /// Advantage on checks with one skill.
+    SkillAdvantage {
+        /// The skill's name.
+        skill: String,
+    },
+    /// Darkvision.
+    Darkvision {
+        /// How far the darkvision reaches, in feet.
+        #[serde(default = "default_darkvision_range")]
+        range: u16,
+    },
+}
+
+impl From<&ItemEffect> for ItemEffectConfig {
+    fn from(effect: &ItemEffect) -> ItemEffectConfig {
+        match *effect {
+            ItemEffect::CheckBonus(amount) => ItemEffectConfig::CheckBonus {amount},
+            ItemEffect::SkillBonus(skill, amount) => ItemEffectConfig::SkillBonus {skill: skill.to_string().to_lowercase(), amount},
+            ItemEffect::SkillAdvantage(skill) => ItemEffectConfig::SkillAdvantage {skill: skill.to_string().to_lowercase()},
+            ItemEffect::Darkvision(range) => ItemEffectConfig::Darkvision {range},
+        }
+    }
+}
+
+/// The range of darkvision an item's config gives none for.
+fn default_darkvision_range() -> u16 {
+    DEFAULT_DARKVISION_RANGE
+}
+
+/// A homebrew skill, as written in a configuration file.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CustomSkillConfig {
+    /// The ability a check with the skill uses.
+    pub ability: String,
+    /// The character's proficiency level in the skill.
+    #[serde(default = "no_proficiency")]
+    pub level: ProficiencyLevelConfig,
+}
+
+/// A temporary modifier, as written in a configuration file, such as
+/// `{"name": "belt", "ability": "strength", "amount": 1}`. A modifier
+/// which names neither an ability nor a skill applies to every check.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ModifierConfig {
+    /// The name of the effect granting the modifier.
+    pub name: String,
+    /// The number added to the checks.
+    pub amount: i32,
+    /// The ability whose checks the modifier applies to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ability: Option<String>,
+    /// The skill whose checks the modifier applies to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skill: Option<String>,
+}
+
+/// The proficiency level of a custom skill whose config gives none.
+fn no_proficiency() -> ProficiencyLevelConfig {
+    ProficiencyLevelConfig::Number(0)
+}
+
+/// The table's house rules, as written in a configuration file.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct HouseRulesConfig {
+    /// The lowest total a check may have.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_total: Option<i32>,
+    /// The highest total a check may have.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_total: Option<i32>,
+    /// Whether a character must be proficient in a skill to help with it.
+    #[serde(default)]
+    pub strict_help: bool,
+    /// Whether a character below half their hit points has disadvantage
+    /// on ability checks.
+    #[serde(default)]
+    pub bloodied_disadvantage: bool,
+}
+
+impl PcConfig {
+    /// Parse a configuration from JSON, migrating it from an older
+    /// version if need be.
+    ///
+    /// * `text` - The JSON to parse.
+    /// * `strict` - Whether to refuse keys the config does not use,
+    ///   rather than leaving them for `unknown_keys`.
+    pub fn parse(text: &str, strict: bool) -> Result<PcConfig, PcError> {
+        let mut document: serde_json::Value = serde_json::from_str(text)?;
+        migrate::migrate(&mut document)?;
+        let config: PcConfig = PcConfig::deserialize(document)?;
+        if strict {
+            if let Some(key) = config.unknown_keys().first() {
+                return Err(PcError::Parse(serde::de::Error::custom(format!("unknown key \"{}\"", key))));
+            }
+        }
+        Ok(config)
+    }
+
+    /// The top-level keys the config does not use, sorted.
+    pub fn unknown_keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self.unknown.keys().map(|key| key.as_str()).collect();
+        keys.sort_unstable();
+        keys
+    }
+}
+
+/// A player character.
+///
+/// This struct is roughly equivalent to a dndbeyond character
+/// sheet, in that it encapsulates the rules for rolling for
+/// various checks alongside the values that numerically affect
+/// the outcome.
+pub struct PC {
+    /// The character's identifier.
+    id: PcId,
+    /// The character's name.
+    name: String,
+    /// The source of randomness that a character uses to make rolls,
+    /// which may be shared with other characters.
+    dice: Rc<dyn Roller>,
+    /// A mapping from ability to ability score. Every ability has a
+    /// score; those the config leaves out score `DEFAULT_ABILITY_SCORE`.
+    abilities: HashMap<Ability, u8>,
+    /// A mapping from proficiency to proficiency level.
+    ///
+    /// When making a roll, the PC multiplies their proficiency bonus
+    /// by their proficiency level's multiplier to determine the
+    /// proficiency modifier.
+    proficiencies: HashMap<Proficiency, ProficiencyLevel>,
+    /// A mapping from ability to saving throw proficiency level.
+    ///
+    /// This is kept apart from the skill proficiencies; an ability
+    /// missing from it has no saving throw proficiency.
+    save_proficiencies: HashMap<Ability, ProficiencyLevel>,
+    /// Homebrew skills, by name.
+    custom_skills: HashMap<String, CustomSkill>,
+    /// Each tool's proficiency level, keyed by its name in lowercase.
+    tools: HashMap<String, ProficiencyLevel>,
+    /// The languages the PC knows, in lowercase.
+    languages: HashSet<String>,
+    /// The advantage level the PC always has on checks with some skills.
+    skill_advantages: HashMap<Proficiency, Advantage>,
+    /// The PC's proficiency bonus.
+    proficiency_bonus: u8,
+    /// The PC's level, if their config gives it.
+    level: Option<u8>,
+    /// How far the PC's darkvision reaches, in feet, if they have it.
+    darkvision: Option<u16>,
+    /// How far the PC's truesight reaches, in feet, if they have it.
+    truesight: Option<u16>,
+    /// Whether the PC rerolls natural 1s (Halfling Luck).
+    lucky: bool,
+    /// The luck points the PC has left to spend (the Lucky feat).
+    luck_points: u8,
+    /// Whether the PC rolls three dice for advantage on checks
+    /// other than Strength and Constitution (Elven Accuracy).
+    elven_accuracy: bool,
+    /// Whether the PC adds half their proficiency bonus to skills they
+    /// are not proficient in (Jack of All Trades).
+    jack_of_all_trades: bool,
+    /// Whether the PC holds Inspiration, to spend for advantage on one check.
+    inspiration: bool,
+    /// Help another character has given, waiting for a check with its skill.
+    help: Option<HelpGrant>,
+    /// Whether the PC must be proficient in a skill to help with it.
+    strict_help: bool,
+    /// Whether the PC has disadvantage on ability checks while bloodied.
+    bloodied_disadvantage: bool,
+    /// Whether the PC adds `OBSERVANT_PASSIVE_BONUS` to passive Perception
+    /// and Investigation (the Observant feat).
+    observant: bool,
+    /// Whether the PC adds `ALERT_INITIATIVE_BONUS` to initiative (the Alert feat).
+    alert: bool,
+    /// Whether the PC has advantage on initiative (Feral Instinct).
+    feral_instinct: bool,
+    /// The table's limits on check totals.
+    bounds: Bounds,
+    /// The PC's racial traits, in the order their config gives them.
+    traits: Vec<Trait>,
+    /// The magic items the PC carries.
+    items: Vec<Item>,
+    /// The bonuses the PC's items give, each named after its item.
+    item_modifiers: Vec<TemporaryModifier>,
+    /// The conditions currently affecting the PC.
+    conditions: HashSet<Condition>,
+    /// The conditions the PC was loaded with, so that a reload can
+    /// tell them from those gained or lost in play.
+    config_conditions: HashSet<Condition>,
+    /// The PC's level of exhaustion, from 0 to `MAX_EXHAUSTION`.
+    exhaustion: u8,
+    /// The PC's hit point maximum, if they track hit points.
+    max_hp: Option<u16>,
+    /// The PC's hit points now, from 0 to `max_hp`.
+    current_hp: u16,
+    /// The temporary modifiers on the PC's checks, in the order added.
+    modifiers: Vec<TemporaryModifier>,
+    /// The spells affecting the PC, in the order added.
+    effects: Vec<LastingEffect>,
+    /// The bonuses the PC's spells give, each named after its spell.
+    effect_modifiers: Vec<TemporaryModifier>,
+    /// The PC's death saving throws since they last dropped.
+    death_saves: DeathSaves,
+    /// Extra dice waiting to be added to the PC's checks, in the order set.
+    bonus_dice: Vec<PendingBonus>,
+    /// The Bardic Inspiration die the PC holds, if any.
+    inspiration_die: Option<DieSize>,
+}
+
+impl PC {
+    /// Create a character from a configuration file.
+    ///
+    /// Keys the config does not use are logged and otherwise ignored.
+    /// A character with no name is named `UNNAMED`; see `open` to name
+    /// them after their file instead.
+    /// * `dice` - The dice that the PC will use to generate randomness.
+    /// * `config` - A JSON configuration file which lays out the character's attributes.
+    pub fn new(dice: Rc<dyn Roller>, config: &mut File) -> Result<PC, PcError> {
+        let mut config_string: String = String::new();
+        config.read_to_string(&mut config_string)?;
+        let config_data: PcConfig = PcConfig::parse(&config_string, false)?;
+        PC::from_config(dice, &config_data)
+    }
+
+    /// Create a character from a configuration file at a path.
+    ///
+    /// A character with no name is named after the file, so
+    /// "aria.json" makes "aria".
+    /// * `dice` - The dice that the PC will use to generate randomness.
+    /// * `path` - The path of a JSON configuration file.
+    pub fn open(dice: Rc<dyn Roller>, path: &Path) -> Result<PC, PcError> {
+        let config_string: String = std::fs::read_to_string(path)?;
+        let mut config_data: PcConfig = PcConfig::parse(&config_string, false)?;
+        if config_data.name.is_none() {
+            config_data.name = path.file_stem().map(|stem| stem.to_string_lossy().into_owned());
+        }
+        PC::from_config(dice, &config_data)
+    }
+
+    /// Create a character from a parsed configuration.
+    ///
+    /// * `dice` - The dice that the PC will use to generate randomness.
+    /// * `config` - The character's attributes.
+    pub fn from_config(dice: Rc<dyn Roller>, config: &PcConfig) -> Result<PC, PcError> {
+        for key in config.unknown_keys() {
+            log::warn!("Ignoring unknown character config key \"{}\"", key);
+        }
+
+        // Values out of range are gathered so they can all be reported.
+        let mut issues: Vec<ValidationIssue> = Vec::new();
+
+        // Insert each ability score into the PC's abilities, skipping
+        // unknown abilities so that a typo is not mistaken for a default.
+        let mut abilities: HashMap<Ability, u8> = HashMap::new();
+        for (ability, &score) in &config.abilities {
+            let ability_val: Ability = match Ability::from_str(ability) {
+                Ok(data) => data,
+                Err(_error) => {
+                    log::warn!("Ignoring unknown ability \"{}\"", ability);
+                    continue;
+                },
+            };
+            if !(1..=MAX_ABILITY_SCORE).contains(&score) {
+                issues.push(ValidationIssue::new(&format!("abilities.{}", ability), score));
+            }
+            abilities.insert(ability_val, score);
+        }
+        for ability in Ability::ALL {
+            abilities.entry(ability).or_insert_with(|| {
+                log::info!("No {} score given; using {}", ability, DEFAULT_ABILITY_SCORE);
+                DEFAULT_ABILITY_SCORE
+            });
+        }
+
+        // Insert each proficiency into the PC's proficiencies.
+        let mut proficiencies: HashMap<Proficiency, ProficiencyLevel> = HashMap::new();
+        for (proficiency, level) in &config.proficiencies {
+            let proficiency_val: Proficiency = Proficiency::from_str(proficiency).map_err(|_| PcError::UnknownProficiency(proficiency.clone()))?;
+            let level_val: ProficiencyLevel = level.resolve(&format!("proficiencies.{}", proficiency), &mut issues)?;
+            proficiencies.insert(proficiency_val, level_val);
+        }
+
+        // Insert each custom skill. A custom skill may not share a
+        // name with a standard one, which would always be found first.
+        let mut custom_skills: HashMap<String, CustomSkill> = HashMap::new();
+        for (name, skill) in &config.custom_skills {
+            if Proficiency::from_str(name).is_ok() {
+                log::warn!("Ignoring custom skill \"{}\", which is already a standard skill", name);
+                continue;
+            }
+            let ability: Ability = match Ability::from_str(&skill.ability) {
+                Ok(data) => data,
+                Err(_error) => {
+                    log::warn!("Ignoring custom skill \"{}\" with unknown ability \"{}\"", name, skill.ability);
+                    continue;
+                },
+            };
+            let level: ProficiencyLevel = skill.level.resolve(&format!("custom_skills.{}.level", name), &mut issues)?;
+            custom_skills.insert(name.clone(), CustomSkill {ability, level});
+        }
+
+        // Insert each tool proficiency and language. Their names are
+        // free-form, so they are kept in lowercase for lookups.
+        let mut tools: HashMap<String, ProficiencyLevel> = HashMap::new();
+        for (tool, level) in &config.tools {
+            let level_val: ProficiencyLevel = level.resolve(&format!("tools.{}", tool), &mut issues)?;
+            tools.insert(tool.to_lowercase(), level_val);
+        }
+        let languages: HashSet<String> = config.languages.iter().map(|language| language.to_lowercase()).collect();
+
+        // Read the racial traits, which can add to what the config says
+        // elsewhere. Unknown traits are kept so that they are not lost.
+        let mut traits: Vec<Trait> = Vec::new();
+        for name in &config.traits {
+            let trait_val: Trait = Trait::from(name.as_str());
+            if let Trait::Unknown(name) = &trait_val {
+                log::warn!("Keeping unknown trait \"{}\", which has no effect", name);
+            }
+            traits.push(trait_val);
+        }
+        if traits.contains(&Trait::KeenSenses) {
+            let perception: &mut ProficiencyLevel = proficiencies.entry(Proficiency::Perception).or_default();
+            if *perception == ProficiencyLevel::None {
+                *perception = ProficiencyLevel::Proficient;
+            }
+        }
+
+        // Insert each saving throw proficiency.
+        let mut save_proficiencies: HashMap<Ability, ProficiencyLevel> = HashMap::new();
+        for (ability, level) in &config.saving_throws {
+            let ability_val: Ability = match Ability::from_str(ability) {
+                Ok(data) => data,
+                Err(_error) => {
+                    log::warn!("Ignoring saving throw proficiency in unknown ability \"{}\"", ability);
+                    continue;
+                },
+            };
+            let level_val: ProficiencyLevel = level.resolve(&format!("saving_throws.{}", ability), &mut issues)?;
+            save_proficiencies.insert(ability_val, level_val);
+        }
+
+        // Insert each skill's standing advantage level.
+        let mut skill_advantages: HashMap<Proficiency, Advantage> = HashMap::new();
+        for (skill, advantage) in &config.skill_advantages {
+            let skill_val: Proficiency = Proficiency::from_str(skill).map_err(|_| PcError::UnknownProficiency(skill.clone()))?;
+            let advantage_val: Advantage = Advantage::from_str(advantage).map_err(|_| PcError::UnknownAdvantage(advantage.clone()))?;
+            skill_advantages.insert(skill_val, advantage_val);
+        }
+
+        // Set the proficiency bonus, which follows from the level if
+        // one is given. Both may be given only if they agree.
+        let proficiency_bonus: u8 = match (config.level, config.proficiency_bonus) {
+            (Some(level), _) if !(1..=MAX_LEVEL).contains(&level) => {
+                issues.push(ValidationIssue::new("level", level));
+                MIN_PROFICIENCY_BONUS
+            },
+            (Some(level), Some(bonus)) if bonus != proficiency_bonus_for_level(level) => {
+                return Err(PcError::Conflict {first: "level", second: "proficiency_bonus"});
+            },
+            (Some(level), _) => proficiency_bonus_for_level(level),
+            (None, Some(bonus)) => {
+                if !(MIN_PROFICIENCY_BONUS..=MAX_PROFICIENCY_BONUS).contains(&bonus) {
+                    issues.push(ValidationIssue::new("proficiency_bonus", bonus));
+                }
+                bonus
+            },
+            (None, None) => {
+                log::info!("No proficiency bonus or level given; using {}", MIN_PROFICIENCY_BONUS);
+                MIN_PROFICIENCY_BONUS
+            },
+        };
+
+        // Read the magic items, and the bonuses they give.
+        let mut items: Vec<Item> = Vec::new();
+        let mut item_modifiers: Vec<TemporaryModifier> = Vec::new();
+        for item in &config.items {
+            let mut effects: Vec<ItemEffect> = Vec::new();
+            for effect in &item.effects {
+                let effect_val: ItemEffect = match effect {
+                    ItemEffectConfig::CheckBonus {amount} => ItemEffect::CheckBonus(*amount),
+                    ItemEffectConfig::SkillBonus {skill, amount} => {
+                        let skill_val: Proficiency = Proficiency::from_str(skill).map_err(|_| PcError::UnknownProficiency(skill.clone()))?;
+                        ItemEffect::SkillBonus(skill_val, *amount)
+                    },
+                    ItemEffectConfig::SkillAdvantage {skill} => {
+                        let skill_val: Proficiency = Proficiency::from_str(skill).map_err(|_| PcError::UnknownProficiency(skill.clone()))?;
+                        ItemEffect::SkillAdvantage(skill_val)
+                    },
+                    ItemEffectConfig::Darkvision {range} => ItemEffect::Darkvision(*range),
+                };
+                let scope: Option<(ModifierScope, i32)> = match effect_val {
+                    ItemEffect::CheckBonus(amount) => Some((ModifierScope::AllChecks, amount)),
+                    ItemEffect::SkillBonus(skill, amount) => Some((ModifierScope::Skill(skill), amount)),
+                    ItemEffect::SkillAdvantage(_) | ItemEffect::Darkvision(_) => None,
+                };
+                if let Some((scope, amount)) = scope {
+                    item_modifiers.push(TemporaryModifier {name: item.name.clone(), scope, amount});
+                }
+                effects.push(effect_val);
+            }
+            items.push(Item {name: item.name.clone(), effects});
+        }
+
+        // Set the darkvision range. Items which give darkvision can
+        // only extend it.
+        let innate_darkvision: Option<u16> = config.senses.darkvision.filter(|&feet| feet > 0);
+        let item_darkvision: Option<u16> = items.iter()
+            .flat_map(|item| item.effects.iter())
+            .filter_map(|effect| match effect {
+                ItemEffect::Darkvision(feet) if *feet > 0 => Some(*feet),
+                _ => None,
+            })
+            .max();
+        let darkvision: Option<u16> = innate_darkvision.max(item_darkvision);
+        match darkvision {
+            Some(feet) if feet > MAX_DARKVISION_RANGE => issues.push(ValidationIssue::new("darkvision", feet)),
+            _ => (),
+        };
+        let truesight: Option<u16> = config.senses.truesight.filter(|&feet| feet > 0);
+        match truesight {
+            Some(feet) if feet > MAX_TRUESIGHT_RANGE => issues.push(ValidationIssue::new("truesight", feet)),
+            _ => (),
+        };
+
+        // Add any conditions the PC starts with.
+        let mut conditions: HashSet<Condition> = HashSet::new();
+        for condition in &config.conditions {
+            let condition_val: Condition = Condition::from_str(condition).map_err(|_| PcError::UnknownCondition(condition.clone()))?;
+            conditions.insert(condition_val);
+        }
+
+        if config.exhaustion > MAX_EXHAUSTION {
+            issues.push(ValidationIssue::new("exhaustion", config.exhaustion));
+        }
+
+        // Hit points start full unless the config says otherwise, and
+        // a character at 0 is unconscious.
+        let current_hp: u16 = config.current_hp.or(config.max_hp).unwrap_or_default();
+        match (config.max_hp, config.current_hp) {
+            (Some(0), _) => issues.push(ValidationIssue::new("max_hp", 0)),
+            (max_hp, Some(current_hp)) if Some(current_hp) > max_hp => issues.push(ValidationIssue::new("current_hp", current_hp)),
+            (Some(_), _) if current_hp == 0 => {
+                conditions.insert(Condition::Unconscious);
+            },
+            _ => (),
+        };
+
+        // Add any temporary modifiers saved with the PC. A modifier may
+        // apply to an ability or a skill, but not both.
+        let mut modifiers: Vec<TemporaryModifier> = Vec::new();
+        for modifier in &config.modifiers {
+            let scope: ModifierScope = match (&modifier.ability, &modifier.skill) {
+                (Some(_), Some(_)) => return Err(PcError::Conflict {first: "modifiers.ability", second: "modifiers.skill"}),
+                (Some(ability), None) => match Ability::from_str(ability) {
+                    Ok(data) => ModifierScope::Ability(data),
+                    Err(_error) => {
+                        log::warn!("Ignoring modifier \"{}\" with unknown ability \"{}\"", modifier.name, ability);
+                        continue;
+                    },
+                },
+                (None, Some(skill)) => ModifierScope::Skill(Proficiency::from_str(skill).map_err(|_| PcError::UnknownProficiency(skill.clone()))?),
+                (None, None) => ModifierScope::AllChecks,
+            };
+            modifiers.push(TemporaryModifier {name: modifier.name.clone(), scope, amount: modifier.amount});
+        }
+
+        if !issues.is_empty() {
+            issues.sort_by(|a, b| a.field.cmp(&b.field));
+            return Err(PcError::OutOfRange(issues));
+        }
+
+        Ok(PC {
+            id: config.id.map_or_else(PcId::next, PcId::claim),
+            name: config.name.clone().unwrap_or_else(|| String::from(UNNAMED)),
+            dice,
+            abilities,
+            proficiencies,
+            save_proficiencies,
+            custom_skills,
+            tools,
+            languages,
+            skill_advantages,
+            proficiency_bonus,
+            level: config.level,
+            darkvision,
+            truesight,
+            lucky: config.lucky || traits.contains(&Trait::Lucky),
+            luck_points: config.luck_points,
+            elven_accuracy: config.elven_accuracy,
+            jack_of_all_trades: config.jack_of_all_trades,
+            inspiration: config.inspiration,
+            help: None,
+            strict_help: config.house_rules.strict_help,
+            bloodied_disadvantage: config.house_rules.bloodied_disadvantage,
+            observant: config.observant,
+            alert: config.alert,
+            feral_instinct: config.feral_instinct,
+            bounds: Bounds {min: config.house_rules.min_total, max: config.house_rules.max_total},
+            traits,
+            items,
+            item_modifiers,
+            config_conditions: conditions.clone(),
+            conditions,
+            exhaustion: config.exhaustion,
+            max_hp: config.max_hp,
+            current_hp,
+            modifiers,
+            effects: Vec::new(),
+            effect_modifiers: Vec::new(),
+            death_saves: DeathSaves::default(),
+            bonus_dice: Vec::new(),
+            inspiration_die: None,
+        })
+    }
+
+    /// The configuration this character would be loaded from as they
+    /// stand now, so that changes made during a session can be saved.
+    ///
+    /// Conditions, exhaustion, temporary modifiers, luck points, and
+    /// Inspiration are saved as they are now, so spent Inspiration stays
+    /// spent. Help from another character, death saving throws, pending
+    /// bonus dice, Bardic Inspiration dice, and spells are not saved. Tool and
+    /// language names are saved in lowercase, and darkvision which only
+    /// comes from items is left to the items.
+    pub fn to_config(&self) -> PcConfig {
+        let abilities: HashMap<String, u8> = self.abilities.iter()
+            .map(|(ability, &score)| (ability.to_string().to_lowercase(), score))
+            .collect();
+        let proficiencies: HashMap<String, ProficiencyLevelConfig> = self.proficiencies.iter()
+            .map(|(proficiency, &level)| (proficiency.to_string().to_lowercase(), ProficiencyLevelConfig::from(level)))
+            .collect();
+        let custom_skills: HashMap<String, CustomSkillConfig> = self.custom_skills.iter()
+            .map(|(name, skill)| (name.clone(), CustomSkillConfig {ability: skill.ability.to_string().to_lowercase(), level: ProficiencyLevelConfig::from(skill.level)}))
+            .collect();
+        let tools: HashMap<String, ProficiencyLevelConfig> = self.tools.iter()
+            .map(|(tool, &level)| (tool.clone(), ProficiencyLevelConfig::from(level)))
+            .collect();
+        let mut languages: Vec<String> = self.languages.iter().cloned().collect();
+        languages.sort_unstable();
+        let saving_throws: HashMap<String, ProficiencyLevelConfig> = self.save_proficiencies.iter()
+            .map(|(ability, &level)| (ability.to_string().to_lowercase(), ProficiencyLevelConfig::from(level)))
+            .collect();
+        let skill_advantages: HashMap<String, String> = self.skill_advantages.iter()
+            .map(|(proficiency, advantage)| (proficiency.to_string().to_lowercase(), advantage.to_string()))
+            .collect();
+
+        // Darkvision is saved only where it reaches beyond what the
+        // PC's items give, since the items give it again on loading.
+        let item_darkvision: Option<u16> = self.items.iter()
+            .flat_map(|item| item.effects.iter())
+            .filter_map(|effect| match effect {
+                ItemEffect::Darkvision(feet) => Some(*feet),
+                _ => None,
+            })
+            .max();
+        let darkvision: Option<u16> = self.darkvision.filter(|&feet| Some(feet) > item_darkvision);
+
+        let items: Vec<ItemConfig> = self.items.iter()
+            .map(|item| ItemConfig {name: item.name.clone(), effects: item.effects.iter().map(ItemEffectConfig::from).collect()})
+            .collect();
+        let mut conditions: Vec<String> = self.conditions.iter().map(|condition| condition.to_string()).collect();
+        conditions.sort_unstable();
+        let modifiers: Vec<ModifierConfig> = self.modifiers.iter()
+            .map(|modifier| {
+                let (ability, skill): (Option<String>, Option<String>) = match modifier.scope {
+                    ModifierScope::Ability(ability) => (Some(ability.to_string().to_lowercase()), None),
+                    ModifierScope::Skill(proficiency) => (None, Some(proficiency.to_string().to_lowercase())),
+                    ModifierScope::AllChecks => (None, None),
+                };
+                ModifierConfig {name: modifier.name.clone(), amount: modifier.amount, ability, skill}
+            })
+            .collect();
+
+        PcConfig {
+            version: Some(CONFIG_VERSION),
+            id: Some(self.id.value()),
+            name: Some(self.name.clone()),
+            abilities,
+            proficiencies,
+            custom_skills,
+            tools,
+            languages,
+            saving_throws,
+            skill_advantages,
+            // The level gives the proficiency bonus, so only one is saved.
+            proficiency_bonus: if self.level.is_some() { None } else { Some(self.proficiency_bonus) },
+            level: self.level,
+            senses: SensesConfig {darkvision, truesight: self.truesight},
+            lucky: self.lucky,
+            luck_points: self.luck_points,
+            elven_accuracy: self.elven_accuracy,
+            jack_of_all_trades: self.jack_of_all_trades,
+            inspiration: self.inspiration,
+            observant: self.observant,
+            alert: self.alert,
+            feral_instinct: self.feral_instinct,
+            house_rules: HouseRulesConfig {
+                min_total: self.bounds.min,
+                max_total: self.bounds.max,
+                strict_help: self.strict_help,
+                bloodied_disadvantage: self.bloodied_disadvantage,
+            },
+            traits: self.traits.iter().map(|trait_val| trait_val.to_string()).collect(),
+            items,
+            conditions,
+            exhaustion: self.exhaustion,
+            max_hp: self.max_hp,
+            current_hp: self.current_hp(),
+            modifiers,
+            unknown: HashMap::new(),
+        }
+    }
+
+    /// Keep what a character gained in play when they are reloaded:
+    /// their identifier, the conditions they gained or lost, their temporary
+    /// modifiers, any help waiting to be used, their death saves, their pending bonus
+    /// dice, their Bardic Inspiration die, their spells, and their damage. A modifier
+    /// the new config also names is taken from the config, and hit points
+    /// are kept only up to the new maximum.
+    ///
+    /// Conditions start from the new config, so that removing one from
+    /// the config clears it; only changes made in play are kept on top.
+    /// * `previous` - The character as they were before reloading.
+    fn keep_play_state(&mut self, previous: PC) {
+        self.id = previous.id;
+        for condition in previous.config_conditions.difference(&previous.conditions) {
+            self.conditions.remove(condition);
+        }
+        self.conditions.extend(previous.conditions.difference(&previous.config_conditions));
+        for modifier in previous.modifiers {
+            if !self.modifiers.iter().any(|existing| existing.name == modifier.name) {
+                self.modifiers.push(modifier);
+            }
+        }
+        self.help = previous.help;
+        self.death_saves = previous.death_saves;
+        self.bonus_dice = previous.bonus_dice;
+        self.inspiration_die = previous.inspiration_die;
+        self.effects = previous.effects;
+        self.effect_modifiers = previous.effect_modifiers;
+        if let (Some(max_hp), Some(_)) = (self.max_hp, previous.max_hp) {
+            self.current_hp = previous.current_hp.min(max_hp);
+        }
+    }
+
+    /// The character as JSON that `new` can load, with keys sorted.
+    /// See `to_config` for what is saved.
+    pub fn to_json(&self) -> String {
+        // Going through a `Value` sorts the keys of every map.
+        let value: serde_json::Value = serde_json::to_value(self.to_config()).expect("a character config is always valid JSON");
+        serde_json::to_string_pretty(&value).expect("a JSON value always serializes")
+    }
+
+    /// Save the character as JSON to a path.
+    ///
+    /// The JSON is written to a temporary file beside the path and then
+    /// renamed over it, so that a failed save never leaves half a file.
+    /// * `path` - The path to save to.
+    pub fn save(&self, path: &Path) -> Result<(), PcError> {
+        let mut temp_name: std::ffi::OsString = path.file_name().map(|name| name.to_os_string()).unwrap_or_default();
+        temp_name.push(".tmp");
+        let temp_path: std::path::PathBuf = path.with_file_name(temp_name);
+        let mut temp: File = File::create(&temp_path)?;
+        temp.write_all(self.to_json().as_bytes())?;
+        temp.sync_all()?;
+        std::fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+
+    /// The character's identifier.
+    pub fn id(&self) -> PcId {
+        self.id
+    }
+
+    /// The character's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The character's level, if their config gives it.
+    pub fn level(&self) -> Option<u8> {
+        self.level
+    }
+
+    /// The character's racial traits, in the order their config gives them.
+    pub fn traits(&self) -> &[Trait] {
+        &self.traits
+    }
+
+    /// Given a profiency category, return the PC's proficiency modifier.
+    ///
+    /// The proficiency modifier is the PC's proficiency bonus multiplied
+    /// by their proficiency level's multiplier. A PC with Jack of All
+    /// Trades adds half their bonus, rounded down, to skills they are
+    /// not proficient in. A skill the config leaves out is one the PC
+    /// is not proficient in.
+    /// * `proficiency` - The type of proficiency whose modifier to retrieve.
+    pub fn proficiency_modifier(&self, proficiency: Proficiency) -> u8 {
+        self.proficiency_breakdown(proficiency).total()
+    }
+
+    /// How the PC's proficiency modifier for a skill is made up, as
+    /// `proficiency_modifier` describes.
+    /// * `proficiency` - The type of proficiency whose modifier to retrieve.
+    pub fn proficiency_breakdown(&self, proficiency: Proficiency) -> ProficiencyModifier {
+        self.skill_breakdown(&Skill::Standard(proficiency))
+    }
+
+    /// Given a skill, standard, custom, or tool, return the PC's
+    /// proficiency modifier.
+    /// * `skill` - The skill whose modifier to retrieve.
+    pub fn skill_modifier(&self, skill: &Skill) -> u8 {
+        self.skill_breakdown(skill).total()
+    }
+
+    /// How the PC's proficiency modifier for a skill, standard, custom,
+    /// or tool, is made up.
+    /// * `skill` - The skill whose modifier to retrieve.
+    pub fn skill_breakdown(&self, skill: &Skill) -> ProficiencyModifier {
+        let level: ProficiencyLevel = match skill {
+            Skill::Standard(proficiency) => self.proficiencies.get(proficiency).copied().unwrap_or_default(),
+            Skill::Custom(name) => self.custom_skills.get(name).map(|custom| custom.level).unwrap_or_default(),
+            Skill::Tool(name) => self.tool_proficiency(name),
+        };
+        self.level_breakdown(level)
+    }
+
+    /// Take a feat which changes the PC's sheet, such as at a level-up.
+    ///
+    /// The feat is checked in full before anything changes, so a
+    /// refused feat leaves the PC as they were.
+    /// * `feat` - The feat to take.
+    pub fn apply_feat(&mut self, feat: Feat) -> Result<(), FeatError> {
+        match feat {
+            Feat::SkillExpert {proficiency, expertise} => {
+                if proficiency == expertise {
+                    return Err(FeatError::SameSkill(proficiency));
+                }
+                let level = |skill: Proficiency| self.proficiencies.get(&skill).copied().unwrap_or_default();
+                if level(proficiency) != ProficiencyLevel::None {
+                    return Err(FeatError::AlreadyProficient(proficiency));
+                }
+                match level(expertise) {
+                    ProficiencyLevel::None => return Err(FeatError::NotProficient(expertise)),
+                    ProficiencyLevel::Expertise => return Err(FeatError::AlreadyExpert(expertise)),
+                    ProficiencyLevel::Proficient => (),
+                };
+                self.proficiencies.insert(proficiency, ProficiencyLevel::Proficient);
+                self.proficiencies.insert(expertise, ProficiencyLevel::Expertise);
+                log::info!("{} takes Skill Expert: proficiency in {}, expertise in {}", self.name, proficiency, expertise);
+            },
+        };
+        Ok(())
+    }
+
+    /// The PC's proficiency level with a tool, which is
+    /// `ProficiencyLevel::None` for a tool they have no proficiency with.
+    /// * `tool` - The tool's name, in any case.
+    pub fn tool_proficiency(&self, tool: &str) -> ProficiencyLevel {
+        self.tools.get(&tool.to_lowercase()).copied().unwrap_or_default()
+    }
+
+    /// Whether the PC knows a language.
+    /// * `language` - The language's name, in any case.
+    pub fn knows_language(&self, language: &str) -> bool {
+        self.languages.contains(&language.to_lowercase())
+    }
+
+    /// The proficiency modifier for a skill at some proficiency level,
+    /// including Jack of All Trades.
+    /// * `level` - The PC's proficiency level in the skill.
+    fn level_modifier(&self, level: ProficiencyLevel) -> u8 {
+        self.level_breakdown(level).total()
+    }
+
+    /// How the proficiency modifier for a skill at some proficiency
+    /// level is made up, including Jack of All Trades.
+    /// * `level` - The PC's proficiency level in the skill.
+    fn level_breakdown(&self, level: ProficiencyLevel) -> ProficiencyModifier {
+        ProficiencyModifier {
+            bonus: self.proficiency_bonus,
+            level,
+            jack_of_all_trades: self.jack_of_all_trades && level == ProficiencyLevel::None,
+        }
+    }
+
+    /// The homebrew skills the PC's config defines, by name.
+    pub fn custom_skills(&self) -> &HashMap<String, CustomSkill> {
+        &self.custom_skills
+    }
+
+    /// Given an ability score, return the PC's ability modifier.
+    ///
+    /// The modifier rounds down, so a score of 9 gives -1 rather than 0.
+    /// Every ability is given a score when the PC is built, but a missing
+    /// one would score `DEFAULT_ABILITY_SCORE` rather than panic.
+    /// * `ability` - The ability whose modifier to retrieve.
+    pub fn ability_modifier(&self, ability: Ability) -> i32 {
+        let ability_score: u8 = self.ability_score(ability);
+        (ability_score as i32 - 10).div_euclid(2)
+    }
+
+    /// The PC's score in an ability.
+    /// * `ability` - The ability whose score to retrieve.
+    pub fn ability_score(&self, ability: Ability) -> u8 {
+        self.abilities.get(&ability).copied().unwrap_or(DEFAULT_ABILITY_SCORE)
+    }
+
+    /// Total the modifiers that apply to a check.
+    /// * `ability` - The ability to apply to the check.
+    /// * `skill` - The skill to apply to the check, if any.
+    fn check_modifier(&self, ability: Ability, skill: Option<&Skill>) -> i32 {
+        let proficiency_bonus: u8 = match skill {
+            Some(skill) => self.skill_modifier(skill),
+            None => self.level_modifier(ProficiencyLevel::None),
+        };
+        let ability_score: i32 = self.ability_modifier(ability);
+        let temporary: i32 = self.applicable_modifiers(ability, skill).map(|modifier| modifier.amount).sum();
+        proficiency_bonus as i32 + ability_score + temporary
+    }
+
+    /// The item bonuses, temporary modifiers, and spell bonuses which
+    /// apply to a check.
+    /// * `ability` - The ability the check uses.
+    /// * `skill` - The skill the check uses, if any.
+    fn applicable_modifiers<'s>(&'s self, ability: Ability, skill: Option<&'s Skill>) -> impl Iterator<Item = &'s TemporaryModifier> {
+        self.item_modifiers.iter()
+            .chain(self.modifiers.iter())
+            .chain(self.effect_modifiers.iter())
+            .filter(move |modifier| modifier.scope.applies_to(ability, skill))
+    }
+
+    /// The dice options that apply to every check the PC makes.
+    /// * `bonuses` - Extra dice to add to this check.
+    /// * `label` - What the check is for, to show in the log.
+    fn roll_options(&self, bonuses: &[Bonus], label: String) -> RollOptions {
+        RollOptions {
+            reroll_ones: self.lucky,
+            bonuses: bonuses.to_vec(),
+            bounds: self.bounds,
+            label: Some(label),
+            ..Default::default()
+        }
+    }
+
+    /// Describe a check for the log, such as "Aria rolls a Wisdom
+    /// (Perception) check", or "Aria rolls a Strength check" without a skill.
+    ///
+    /// Any temporary modifiers which apply are listed after it, as in
+    /// "Aria rolls a Wisdom (Perception) check [+2 cloak, -1 curse]".
+    /// * `ability` - The ability the check uses.
+    /// * `skill` - The skill the check uses, if any.
+    fn check_label(&self, ability: &Ability, skill: Option<&Skill>) -> String {
+        let label: String = match skill {
+            Some(skill) => format!("{} rolls a {} ({}) check", self.name, ability, skill),
+            None => format!("{} rolls a {} check", self.name, ability),
+        };
+        let modifiers: Vec<String> = self.applicable_modifiers(*ability, skill)
+            .map(|modifier| format!("{:+} {}", modifier.amount, modifier.name))
+            .collect();
+        if modifiers.is_empty() {
+            label
+        } else {
+            format!("{} [{}]", label, modifiers.join(", "))
+        }
+    }
+
+    /// Add a named modifier to the PC's checks.
+    ///
+    /// A modifier with the same name as one already added replaces it
+    /// rather than stacking.
+    /// * `name` - The name of the effect granting the modifier.
+    /// * `scope` - The checks the modifier applies to.
+    /// * `amount` - The number to add to the checks.
+    pub fn add_modifier(&mut self, name: &str, scope: ModifierScope, amount: i32) {
+        let modifier: TemporaryModifier = TemporaryModifier {name: String::from(name), scope, amount};
+        match self.modifiers.iter_mut().find(|existing| existing.name == name) {
+            Some(existing) => *existing = modifier,
+            None => self.modifiers.push(modifier),
+        }
+    }
+
+    /// Remove a named modifier from the PC's checks, if it was added.
+    /// * `name` - The name of the modifier to remove.
+    pub fn remove_modifier(&mut self, name: &str) {
+        self.modifiers.retain(|modifier| modifier.name != name);
+    }
+
+    /// The temporary modifiers on the PC's checks, in the order added.
+    pub fn active_modifiers(&self) -> &[TemporaryModifier] {
+        &self.modifiers
+    }
+
+    /// Add an extra die to the PC's next check, or to every check until
+    /// removed, as from guidance or bless.
+    ///
+    /// A die with the same label as one already set replaces it rather
+    /// than stacking.
+    /// * `label` - The name of the effect granting the die.
+    /// * `sides` - The number of sides on the die.
+    /// * `persistent` - Whether the die is added to every check until
+    ///   removed, rather than only the next one.
+    pub fn set_pending_bonus_die(&mut self, label: &str, sides: DieSize, persistent: bool) {
+        let pending: PendingBonus = PendingBonus {bonus: Bonus::new(label, sides), persistent};
+        match self.bonus_dice.iter_mut().find(|existing| existing.bonus.label == label) {
+            Some(existing) => *existing = pending,
+            None => self.bonus_dice.push(pending),
+        }
+    }
+
+    /// Remove an extra die from the PC's checks, if it was set.
+    /// * `label` - The name of the effect granting the die.
+    pub fn remove_bonus_die(&mut self, label: &str) {
+        self.bonus_dice.retain(|pending| pending.bonus.label != label);
+    }
+
+    /// The extra dice waiting to be added to the PC's checks, in the order set.
+    pub fn pending_bonus_dice(&self) -> &[PendingBonus] {
+        &self.bonus_dice
+    }
+
+    /// Come under a spell's effect. A spell already affecting the PC in
+    /// the same way is replaced, so recasting it restarts its duration.
+    /// * `effect` - What the spell does.
+    /// * `rounds` - How many rounds the spell lasts, or `None` if it
+    ///   lasts until removed.
+    pub fn add_effect(&mut self, effect: ActiveEffect, rounds: Option<u16>) {
+        let lasting: LastingEffect = LastingEffect {effect, rounds_left: rounds};
+        match self.effects.iter_mut().find(|existing| existing.effect == effect) {
+            Some(existing) => *existing = lasting,
+            None => self.effects.push(lasting),
+        }
+        self.refresh_effect_modifiers();
+    }
+
+    /// End a spell's effect, if the PC is under it.
+    /// * `effect` - What the spell does.
+    pub fn remove_effect(&mut self, effect: ActiveEffect) {
+        self.effects.retain(|lasting| lasting.effect != effect);
+        self.refresh_effect_modifiers();
+    }
+
+    /// The spells affecting the PC, in the order added.
+    pub fn active_effects(&self) -> &[LastingEffect] {
+        &self.effects
+    }
+
+    /// Count down a round of the PC's spells, ending those whose time is
+    /// up, and return the effects that ended.
+    pub fn tick_round(&mut self) -> Vec<ActiveEffect> {
+        let mut expired: Vec<ActiveEffect> = Vec::new();
+        for lasting in &mut self.effects {
+            if let Some(rounds) = lasting.rounds_left.as_mut() {
+                *rounds = rounds.saturating_sub(1);
+                if *rounds == 0 {
+                    log::info!("{} on {} ends", lasting.effect, self.name);
+                    expired.push(lasting.effect);
+                }
+            }
+        }
+        self.effects.retain(|lasting| lasting.rounds_left != Some(0));
+        self.refresh_effect_modifiers();
+        expired
+    }
+
+    /// Take a short rest.
+    ///
+    /// An hour passes, so spells with less than an hour left end, and
+    /// help, one-shot bonus dice, and an unspent Bardic Inspiration die,
+    /// which last minutes, lapse. Hit points, exhaustion, conditions,
+    /// and Inspiration are unchanged. Returns what changed, in order.
+    pub fn short_rest(&mut self) -> Vec<RestChange> {
+        let mut changes: Vec<RestChange> = Vec::new();
+        self.end_effects(|rounds_left| rounds_left.is_some_and(|rounds| rounds <= SHORT_REST_ROUNDS), &mut changes);
+        self.lapse_transient_state(&mut changes);
+        for change in &changes {
+            log::info!("{} rests: {}", self.name, change);
+        }
+        changes
+    }
+
+    /// Take a long rest.
+    ///
+    /// Every spell ends, and what a short rest lapses lapses. A PC with
+    /// at least 1 hit point, or who does not track hit points, also
+    /// loses a level of exhaustion, regains all their hit points, and
+    /// starts their death saves over; a PC at 0 gains none of this.
+    /// Temporary modifiers, conditions, and Inspiration are unchanged.
+    /// Returns what changed, in order.
+    pub fn long_rest(&mut self) -> Vec<RestChange> {
+        let mut changes: Vec<RestChange> = Vec::new();
+        self.end_effects(|_| true, &mut changes);
+        self.lapse_transient_state(&mut changes);
+        let benefits: bool = self.max_hp.is_none() || self.current_hp > 0;
+        if benefits {
+            if self.exhaustion > 0 {
+                self.exhaustion -= 1;
+                changes.push(RestChange::ExhaustionReduced(self.exhaustion));
+            }
+            if let Some(max_hp) = self.max_hp.filter(|&max_hp| max_hp > self.current_hp) {
+                changes.push(RestChange::HitPointsRestored(max_hp - self.current_hp));
+                self.current_hp = max_hp;
+            }
+            self.reset_death_saves();
+        } else {
+            log::info!("{} is at 0 hit points, and gains nothing from a long rest", self.name);
+        }
+        for change in &changes {
+            log::info!("{} rests: {}", self.name, change);
+        }
+        changes
+    }
+
+    /// End the PC's spells whose time runs out during a rest.
+    /// * `ends` - Whether a spell with the given rounds left ends.
+    /// * `changes` - What the rest has changed so far.
+    fn end_effects(&mut self, ends: impl Fn(Option<u16>) -> bool, changes: &mut Vec<RestChange>) {
+        let (ended, kept): (Vec<LastingEffect>, Vec<LastingEffect>) = std::mem::take(&mut self.effects).into_iter().partition(|lasting| ends(lasting.rounds_left));
+        changes.extend(ended.into_iter().map(|lasting| RestChange::EffectEnded(lasting.effect)));
+        self.effects = kept;
+        self.refresh_effect_modifiers();
+    }
+
+    /// Let what lasts only minutes lapse during a rest: help, one-shot
+    /// bonus dice, and a Bardic Inspiration die.
+    /// * `changes` - What the rest has changed so far.
+    fn lapse_transient_state(&mut self, changes: &mut Vec<RestChange>) {
+        if let Some(help) = self.help.take() {
+            changes.push(RestChange::HelpLapsed(help.helper));
+        }
+        for pending in self.bonus_dice.iter().filter(|pending| !pending.persistent) {
+            changes.push(RestChange::BonusDieLapsed(pending.bonus.label.clone()));
+        }
+        self.bonus_dice.retain(|pending| pending.persistent);
+        if let Some(sides) = self.inspiration_die.take() {
+            changes.push(RestChange::InspirationDieLapsed(sides));
+        }
+    }
+
+    /// Rebuild the bonuses the PC's spells give after the spells change.
+    fn refresh_effect_modifiers(&mut self) {
+        self.effect_modifiers = self.effects.iter().filter_map(|lasting| lasting.effect.modifier()).collect();
+    }
+
+    /// Start suffering from a condition.
+    /// * `condition` - The condition to add.
+    pub fn add_condition(&mut self, condition: Condition) {
+        self.conditions.insert(condition);
+    }
+
+    /// Stop suffering from a condition.
+    /// * `condition` - The condition to remove.
+    pub fn remove_condition(&mut self, condition: Condition) {
+        self.conditions.remove(&condition);
+    }
+
+    /// Whether the PC is suffering from a condition.
+    /// * `condition` - The condition to look for.
+    pub fn has_condition(&self, condition: Condition) -> bool {
+        self.conditions.contains(&condition)
+    }
+
+    /// The PC's hit point maximum, if they track hit points.
+    pub fn max_hp(&self) -> Option<u16> {
+        self.max_hp
+    }
+
+    /// The PC's hit points now, if they track hit points.
+    pub fn current_hp(&self) -> Option<u16> {
+        self.max_hp.map(|_| self.current_hp)
+    }
+
+    /// Whether the PC is below half their hit point maximum.
+    pub fn is_bloodied(&self) -> bool {
+        self.max_hp.is_some_and(|max_hp| u32::from(self.current_hp) * 2 < u32::from(max_hp))
+    }
+
+    /// Lose hit points, down to 0, at which the PC falls unconscious and
+    /// starts their death saves over. A PC who does not track hit points
+    /// is unaffected.
+    /// * `amount` - The damage taken.
+    pub fn take_damage(&mut self, amount: u16) {
+        if self.max_hp.is_none() {
+            log::warn!("{} does not track hit points; ignoring {} damage", self.name, amount);
+            return;
+        }
+        let was_up: bool = self.current_hp > 0;
+        self.current_hp = self.current_hp.saturating_sub(amount);
+        if was_up && self.current_hp == 0 {
+            log::info!("{} falls unconscious", self.name);
+            self.add_condition(Condition::Unconscious);
+            self.reset_death_saves();
+        }
+    }
+
+    /// Regain hit points, up to the PC's maximum. A PC healed from 0 is
+    /// no longer unconscious, and their death saves start over. A PC who
+    /// does not track hit points is unaffected.
+    /// * `amount` - The hit points regained.
+    pub fn heal(&mut self, amount: u16) {
+        let max_hp: u16 = match self.max_hp {
+            Some(max_hp) => max_hp,
+            None => {
+                log::warn!("{} does not track hit points; ignoring {} healing", self.name, amount);
+                return;
+            },
+        };
+        let was_down: bool = self.current_hp == 0;
+        self.current_hp = self.current_hp.saturating_add(amount).min(max_hp);
+        if was_down && self.current_hp > 0 {
+            log::info!("{} regains consciousness", self.name);
+            self.remove_condition(Condition::Unconscious);
+            self.reset_death_saves();
+        }
+    }
+
+    /// The PC's level of exhaustion.
+    pub fn exhaustion(&self) -> u8 {
+        self.exhaustion
+    }
+
+    /// Set the PC's level of exhaustion.
+    ///
+    /// Levels beyond `MAX_EXHAUSTION` are treated as `MAX_EXHAUSTION`.
+    /// * `level` - The new level of exhaustion.
+    pub fn set_exhaustion(&mut self, level: u8) {
+        self.exhaustion = level.min(MAX_EXHAUSTION);
+    }
+
+    /// The advantage level of an ability check, once help, the PC's
+    /// config, items, spells, conditions, and Elven Accuracy are taken
+    /// into account. Every check, rolled or not, resolves its advantage
+    /// here. Help is not used up.
+    /// * `ability` - The ability the check uses.
+    /// * `skill` - The skill the check uses, if any.
+    /// * `advantage_set` - The sources of advantage on the check so far, to which the rest are added.
+    fn check_advantage(&self, ability: Ability, skill: Option<&Skill>, advantage_set: &mut AdvantageSet) -> Advantage {
+        if let Some(help) = self.help.as_ref().filter(|help| help.applies_to(skill)) {
+            advantage_set.add(Advantage::Advantage, &format!("help from {}", help.helper));
+        }
+        self.add_skill_advantage(skill, advantage_set);
+        self.add_item_advantage(skill, advantage_set);
+        self.add_effect_advantage(ability, advantage_set);
+        self.add_condition_advantage(advantage_set);
+        self.sharpen_advantage(&ability, advantage_set.resolve())
+    }
+
+    /// Add the advantage the PC's config gives checks with a skill.
+    /// * `skill` - The skill the check uses, if any.
+    /// * `advantage_set` - The sources of advantage on the check so far.
+    fn add_skill_advantage(&self, skill: Option<&Skill>, advantage_set: &mut AdvantageSet) {
+        if let Some(Skill::Standard(proficiency)) = skill {
+            if let Some(&advantage) = self.skill_advantages.get(proficiency) {
+                advantage_set.add(advantage, "character config");
+            }
+        }
+    }
+
+    /// Add the advantage that the PC's spells give a check.
+    /// * `ability` - The ability the check uses.
+    /// * `advantage_set` - The sources of advantage on the check so far.
+    fn add_effect_advantage(&self, ability: Ability, advantage_set: &mut AdvantageSet) {
+        for lasting in &self.effects {
+            if lasting.effect == ActiveEffect::EnhanceAbility(ability) {
+                advantage_set.add(Advantage::Advantage, lasting.effect.spell());
+            }
+        }
+    }
+
+    /// Add the advantage that the PC's items give a check.
+    /// * `skill` - The skill the check uses, if any.
+    /// * `advantage_set` - The sources of advantage on the check so far.
+    fn add_item_advantage(&self, skill: Option<&Skill>, advantage_set: &mut AdvantageSet) {
+        for item in &self.items {
+            let grants: bool = item.effects.iter().any(|effect| match effect {
+                ItemEffect::SkillAdvantage(proficiency) => skill == Some(&Skill::Standard(*proficiency)),
+                _ => false,
+            });
+            if grants {
+                advantage_set.add(Advantage::Advantage, &item.name);
+            }
+        }
+    }
+
+    /// The magic items the PC carries.
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    /// Add the advantage that the PC's conditions give an ability check.
+    /// * `advantage_set` - The sources of advantage on the check so far.
+    fn add_condition_advantage(&self, advantage_set: &mut AdvantageSet) {
+        if self.has_condition(Condition::Unconscious) {
+            advantage_set.add(Advantage::Fail, "unconscious");
+        }
+        if self.has_condition(Condition::Poisoned) {
+            advantage_set.add(Advantage::Disadvantage, "poisoned");
+        }
+        if self.bloodied_disadvantage && self.is_bloodied() {
+            advantage_set.add(Advantage::Disadvantage, "bloodied");
+        }
+        if self.exhaustion >= MAX_EXHAUSTION {
+            advantage_set.add(Advantage::Fail, "death from exhaustion");
+        } else if self.exhaustion >= 1 {
+            advantage_set.add(Advantage::Disadvantage, "exhaustion");
+        }
+    }
+
+    /// Upgrade advantage to super-advantage where Elven Accuracy applies.
+    /// * `ability` - The ability the check uses.
+    /// * `advantage` - The advantage level of the check.
+    fn sharpen_advantage(&self, ability: &Ability, advantage: Advantage) -> Advantage {
+        let accurate: bool = !matches!(ability, Ability::Strength | Ability::Constitution);
+        if self.elven_accuracy && accurate && advantage == Advantage::Advantage {
+            Advantage::SuperAdvantage
+        } else {
+            advantage
+        }
+    }
+
+    /// Roll a check.
+    ///
+    /// Roll a d20, adding the appropriate ability and proficiency modifiers,
+    /// and with the appropriate level of advantage.
+    /// * `ability` - The ability to apply to the check.
+    /// * `proficiency` - The proficiency to apply to the check.
+    /// * `advantage` - The advantage level of the check.
+    pub fn check(&mut self, ability: Ability, proficiency: Proficiency, advantage: Advantage) -> Result<CheckResult, DiceError> {
+        self.check_with_bonuses(ability, proficiency, advantage, &[])
+    }
+
+    /// Roll a plain ability check, with no skill.
+    ///
+    /// Only the ability modifier and any temporary modifiers on the
+    /// ability or on all checks are added, along with half the
+    /// proficiency bonus for Jack of All Trades.
+    /// * `ability` - The ability to apply to the check.
+    /// * `advantage` - The advantage level of the check.
+    pub fn ability_check(&mut self, ability: Ability, advantage: Advantage) -> Result<CheckResult, DiceError> {
+        let advantage_set: AdvantageSet = AdvantageSet::new().with(advantage, "the situation");
+        self.roll_check(ability, None, advantage_set, &[])
+    }
+
+    /// Roll a skill check with the skill's usual ability.
+    /// * `skill` - The skill to apply to the check.
+    /// * `advantage` - The advantage level of the check.
+    pub fn skill_check(&mut self, skill: Proficiency, advantage: Advantage) -> Result<CheckResult, DiceError> {
+        self.skill_check_with(skill.default_ability(), skill, advantage)
+    }
+
+    /// Roll a skill check with an ability other than the skill's usual
+    /// one, such as Strength (Intimidation).
+    ///
+    /// The proficiency modifier is the same whichever ability is used.
+    /// * `ability` - The ability to apply to the check.
+    /// * `skill` - The skill to apply to the check.
+    /// * `advantage` - The advantage level of the check.
+    pub fn skill_check_with(&mut self, ability: Ability, skill: Proficiency, advantage: Advantage) -> Result<CheckResult, DiceError> {
+        self.check(ability, skill, advantage)
+    }
+
+    /// Roll a check with extra dice from effects such as guidance or bless.
+    ///
+    /// Each bonus die is rolled once, whatever the advantage level.
+    /// A roll whose total cannot be represented is refused with an error.
+    /// * `ability` - The ability to apply to the check.
+    /// * `proficiency` - The proficiency to apply to the check.
+    /// * `advantage` - The advantage level of the check.
+    /// * `bonuses` - The extra dice to add.
+    pub fn check_with_bonuses(&mut self, ability: Ability, proficiency: Proficiency, advantage: Advantage, bonuses: &[Bonus]) -> Result<CheckResult, DiceError> {
+        let advantage_set: AdvantageSet = AdvantageSet::new().with(advantage, "the situation");
+        self.roll_check(ability, Some(Skill::Standard(proficiency)), advantage_set, bonuses)
+    }
+
+    /// Roll a check with a tool, such as Dexterity with thieves' tools.
+    ///
+    /// A PC with no proficiency with the tool still rolls, adding only
+    /// what any check adds.
+    /// * `tool` - The tool's name, in any case.
+    /// * `ability` - The ability to apply to the check.
+    /// * `advantage` - The advantage level of the check.
+    pub fn tool_check(&mut self, tool: &str, ability: Ability, advantage: Advantage) -> Result<CheckResult, DiceError> {
+        let advantage_set: AdvantageSet = AdvantageSet::new().with(advantage, "the situation");
+        self.roll_check(ability, Some(Skill::Tool(tool.to_lowercase())), advantage_set, &[])
+    }
+
+    /// Roll a check with a skill named at runtime, standard or custom.
+    ///
+    /// Standard skills are looked up first, and use their usual ability.
+    /// * `name` - The skill's name, such as "perception" or "Lore (Grues)".
+    /// * `advantage` - The advantage level of the check.
+    pub fn skill_check_by_name(&mut self, name: &str, advantage: Advantage) -> Result<CheckResult, SkillError> {
+        let (ability, skill): (Ability, Skill) = match Proficiency::from_str(name) {
+            Ok(proficiency) => (proficiency.default_ability(), Skill::Standard(proficiency)),
+            Err(_error) => match self.custom_skills.get(name) {
+                Some(custom) => (custom.ability, Skill::Custom(String::from(name))),
+                None => return Err(SkillError::Unknown(String::from(name))),
+            },
+        };
+        let advantage_set: AdvantageSet = AdvantageSet::new().with(advantage, "the situation");
+        Ok(self.roll_check(ability, Some(skill), advantage_set, &[])?)
+    }
+
+    /// Roll a check, recording every number that goes into it.
+    /// * `ability` - The ability to apply to the check.
+    /// * `skill` - The skill to apply to the check, if any.
+    /// * `advantage_set` - The sources of advantage on the check before conditions.
+    /// * `bonuses` - The extra dice to add.
+    fn roll_check(&mut self, ability: Ability, skill: Option<Skill>, mut advantage_set: AdvantageSet, bonuses: &[Bonus]) -> Result<CheckResult, DiceError> {
+        let spec: RollSpec = self.check_spec(ability, skill.as_ref(), &mut advantage_set, bonuses);
+        let roll: RollResult = self.dice.try_d_with_options(spec.sides, spec.modifier, spec.advantage, &spec.options)?;
+        Ok(self.finish_check(ability, skill, advantage_set, roll))
+    }
+
+    /// Work out the roll a check calls for, without rolling it.
+    /// * `ability` - The ability to apply to the check.
+    /// * `skill` - The skill to apply to the check, if any.
+    /// * `advantage_set` - The sources of advantage on the check before conditions, to which the rest are added.
+    /// * `bonuses` - The extra dice to add.
+    fn check_spec(&self, ability: Ability, skill: Option<&Skill>, advantage_set: &mut AdvantageSet, bonuses: &[Bonus]) -> RollSpec {
+        let advantage: Advantage = self.check_advantage(ability, skill, advantage_set);
+        let label: String = self.check_label(&ability, skill);
+        let total_modifier: i32 = self.check_modifier(ability, skill);
+
+        // Pending bonus dice join the check's own.
+        let mut bonuses: Vec<Bonus> = bonuses.to_vec();
+        bonuses.extend(self.bonus_dice.iter().map(|pending| pending.bonus.clone()));
+        RollSpec::new(DieSize::D20, total_modifier, advantage).with_options(self.roll_options(&bonuses, label))
+    }
+
+    /// Record a check once it is rolled. One-shot dice and any help are
+    /// used up.
+    /// * `ability` - The ability applied to the check.
+    /// * `skill` - The skill applied to the check, if any.
+    /// * `advantage_set` - Every source of advantage on the check.
+    /// * `roll` - The check's roll.
+    fn finish_check(&mut self, ability: Ability, skill: Option<Skill>, advantage_set: AdvantageSet, roll: RollResult) -> CheckResult {
+        self.bonus_dice.retain(|pending| pending.persistent);
+        self.help.take_if(|help| help.applies_to(skill.as_ref()));
+        CheckResult {
+            name: self.name.clone(),
+            ability,
+            ability_modifier: self.ability_modifier(ability),
+            proficiency_modifier: match &skill {
+                Some(skill) => self.skill_modifier(skill),
+                None => self.level_modifier(ProficiencyLevel::None),
+            },
+            modifiers: self.applicable_modifiers(ability, skill.as_ref()).cloned().collect(),
+            skill,
+            inspiration_spent: false,
+            sense: None,
+            advantage_sources: advantage_set,
+            roll,
+        }
+    }
+
+    /// Roll a check, spending Inspiration for advantage on it.
+    ///
+    /// The check is refused if the PC holds no Inspiration. Inspiration
+    /// is spent only if the check is rolled.
+    /// * `ability` - The ability to apply to the check.
+    /// * `proficiency` - The proficiency to apply to the check.
+    /// * `advantage` - The advantage level of the check before Inspiration.
+    pub fn check_with_inspiration(&mut self, ability: Ability, proficiency: Proficiency, advantage: Advantage) -> Result<CheckResult, InspirationError> {
+        if !self.inspiration {
+            return Err(InspirationError::NotHeld);
+        }
+        let advantage_set: AdvantageSet = AdvantageSet::new()
+            .with(advantage, "the situation")
+            .with(Advantage::Advantage, "Inspiration");
+        let mut result: CheckResult = self.roll_check(ability, Some(Skill::Standard(proficiency)), advantage_set, &[])?;
+        self.inspiration = false;
+        result.inspiration_spent = true;
+        log::info!("{} spends Inspiration", self.name);
+        Ok(result)
+    }
+
+    /// Give the PC Inspiration. A PC holds at most one.
+    pub fn grant_inspiration(&mut self) {
+        self.inspiration = true;
+    }
+
+    /// Whether the PC holds Inspiration.
+    pub fn has_inspiration(&self) -> bool {
+        self.inspiration
+    }
+
+    /// Offer help with a skill, to give another character advantage on
+    /// their next check with it.
+    ///
+    /// Under the strict rule, which the config's house rules choose,
+    /// the PC must be proficient in the skill.
+    /// * `skill` - The skill to help with.
+    pub fn help(&self, skill: Proficiency) -> Result<HelpGrant, HelpError> {
+        let proficient: bool = self.proficiencies.get(&skill).is_some_and(|level| *level != ProficiencyLevel::None);
+        if self.strict_help && !proficient {
+            return Err(HelpError::NotProficient {helper: self.name.clone(), skill});
+        }
+        Ok(HelpGrant {helper: self.name.clone(), skill})
+    }
+
+    /// Accept help from another character, to use on the PC's next check
+    /// with its skill. Help from a second helper replaces the first, as
+    /// advantage does not stack.
+    /// * `grant` - The help offered.
+    pub fn accept_help(&mut self, grant: HelpGrant) -> Result<(), HelpError> {
+        if grant.helper == self.name {
+            return Err(HelpError::OwnHelp);
+        }
+        self.help = Some(grant);
+        Ok(())
+    }
+
+    /// Help the PC has accepted and not yet used.
+    pub fn pending_help(&self) -> Option<&HelpGrant> {
+        self.help.as_ref()
+    }
+
+    /// Roll a check against a difficulty class.
+    /// * `ability` - The ability to apply to the check.
+    /// * `proficiency` - The proficiency to apply to the check.
+    /// * `advantage` - The advantage level of the check.
+    /// * `dc` - The difficulty class to meet.
+    pub fn check_dc(&mut self, ability: Ability, proficiency: Proficiency, advantage: Advantage, dc: i32) -> Result<CheckOutcome, DiceError> {
+        let result: CheckResult = self.check(ability, proficiency, advantage)?;
+        Ok(CheckOutcome::of(result.roll(), dc))
+    }
+
+    /// Roll a saving throw.
+    ///
+    /// Roll a d20, adding the ability modifier and, if the PC is
+    /// proficient in saves with that ability, their proficiency bonus.
+    /// * `ability` - The ability to save with.
+    /// * `advantage` - The advantage level of the save.
+    pub fn saving_throw(&mut self, ability: Ability, advantage: Advantage) -> Result<RollResult, DiceError> {
+        let level: ProficiencyLevel = self.save_proficiencies.get(&ability).copied().unwrap_or_default();
+        let label: String = format!("{} rolls a {} saving throw", self.name, ability);
+        let total_modifier: i32 = self.ability_modifier(ability) + (self.proficiency_bonus * level.multiplier()) as i32;
+
+        let options: RollOptions = self.roll_options(&[], label);
+
+        self.dice.try_d_with_options(DieSize::D20, total_modifier, advantage, &options)
+    }
+
+    /// Roll a death saving throw, counting it toward stabilizing or dying.
+    ///
+    /// The roll is a flat d20: nothing is added to it, though it can be
+    /// made with advantage. A roll of `DEATH_SAVE_DC` or more succeeds,
+    /// a natural 1 counts as two failures, and a natural 20 revives the
+    /// PC with 1 hit point, starting their death saves over.
+    /// * `advantage` - The advantage level of the roll.
+    pub fn death_save(&mut self, advantage: Advantage) -> Result<DeathSaveResult, DiceError> {
+        let label: String = format!("{} rolls a death saving throw", self.name);
+
+        let options: RollOptions = self.roll_options(&[], label);
+
+        let roll: RollResult = self.dice.try_d_with_options(DieSize::D20, 0, advantage, &options)?;
+        let outcome: DeathSaveOutcome = if roll.is_nat20() {
+            DeathSaveOutcome::Revived
+        } else if roll.is_nat1() {
+            DeathSaveOutcome::DoubleFailure
+        } else if roll.rolled_total().is_some_and(|total| total >= DEATH_SAVE_DC) {
+            DeathSaveOutcome::Success
+        } else {
+            DeathSaveOutcome::Failure
+        };
+        let saves: &mut DeathSaves = &mut self.death_saves;
+        match outcome {
+            DeathSaveOutcome::Revived => {
+                *saves = DeathSaves::default();
+                if self.max_hp.is_some() {
+                    self.current_hp = 1;
+                    self.conditions.remove(&Condition::Unconscious);
+                }
+            },
+            DeathSaveOutcome::Success => saves.successes = (saves.successes + 1).min(DEATH_SAVES_NEEDED),
+            DeathSaveOutcome::Failure => saves.failures = (saves.failures + 1).min(DEATH_SAVES_NEEDED),
+            DeathSaveOutcome::DoubleFailure => saves.failures = (saves.failures + 2).min(DEATH_SAVES_NEEDED),
+        };
+        let state: DeathSaves = *saves;
+        if state.is_dead() {
+            log::info!("{} dies", self.name);
+        } else if state.is_stable() {
+            log::info!("{} is stable", self.name);
+        } else if outcome == DeathSaveOutcome::Revived {
+            log::info!("{} regains 1 hit point", self.name);
+        }
+        Ok(DeathSaveResult {roll, outcome, state})
+    }
+
+    /// The PC's death saving throws since they last dropped.
+    pub fn death_save_state(&self) -> DeathSaves {
+        self.death_saves
+    }
+
+    /// Start the PC's death saves over, as when they are healed.
+    pub fn reset_death_saves(&mut self) {
+        self.death_saves = DeathSaves::default();
+    }
+
+    /// The number the PC adds to initiative: their Dexterity modifier,
+    /// plus `ALERT_INITIATIVE_BONUS` with the Alert feat.
+    pub fn initiative_modifier(&self) -> i32 {
+        let alert: i32 = if self.alert { ALERT_INITIATIVE_BONUS } else { 0 };
+        self.ability_modifier(Ability::Dexterity) + alert
+    }
+
+    /// Roll initiative.
+    ///
+    /// Initiative is a Dexterity check, so conditions, spells, and Elven
+    /// Accuracy apply, and Feral Instinct gives advantage.
+    /// * `advantage` - The advantage level of the roll.
+    pub fn initiative(&mut self, advantage: Advantage) -> Result<RollResult, DiceError> {
+        let mut advantage_set: AdvantageSet = AdvantageSet::new().with(advantage, "the situation");
+        if self.feral_instinct {
+            advantage_set.add(Advantage::Advantage, "Feral Instinct");
+        }
+        let advantage: Advantage = self.check_advantage(Ability::Dexterity, None, &mut advantage_set);
+        let label: String = format!("{} rolls initiative", self.name);
+
+        let options: RollOptions = self.roll_options(&[], label);
+
+        self.dice.try_d_with_options(DieSize::D20, self.initiative_modifier(), advantage, &options)
+    }
+
+    /// Roll a check which can be rerolled with a luck point, or have a
+    /// Bardic Inspiration die added, once seen.
+    ///
+    /// Rerolling the returned roll spends one of the PC's luck points,
+    /// and adding to it spends their inspiration die. Pending bonus dice
+    /// are added, and they and help with the skill are used up, as for
+    /// any other check.
+    /// * `ability` - The ability to apply to the check.
+    /// * `proficiency` - The proficiency to apply to the check.
+    /// * `advantage` - The advantage level of the check.
+    pub fn pending_check(&mut self, ability: Ability, proficiency: Proficiency, advantage: Advantage) -> PendingRoll<'_> {
+        let skill: Skill = Skill::Standard(proficiency);
+        let mut advantage_set: AdvantageSet = AdvantageSet::new().with(advantage, "the situation");
+        let spec: RollSpec = self.check_spec(ability, Some(&skill), &mut advantage_set, &[]);
+        self.bonus_dice.retain(|pending| pending.persistent);
+        self.help.take_if(|help| help.applies_to(Some(&skill)));
+
+        PendingRoll::roll(&*self.dice, spec.sides, spec.modifier, spec.advantage, &spec.options)
+            .with_luck_points(&mut self.luck_points)
+            .with_inspiration_die(&mut self.inspiration_die)
+    }
+
+    /// Receive a Bardic Inspiration die, to spend on a pending check once
+    /// it is seen. A PC holds only one, so this replaces any held already.
+    /// * `sides` - The number of sides on the die.
+    pub fn receive_inspiration_die(&mut self, sides: DieSize) {
+        self.inspiration_die = Some(sides);
+    }
+
+    /// The Bardic Inspiration die the PC holds, if any.
+    pub fn inspiration_die(&self) -> Option<DieSize> {
+        self.inspiration_die
+    }
+
+    /// The luck points the PC has left to spend.
+    pub fn luck_points(&self) -> u8 {
+        self.luck_points
+    }
+
+    /// Calculate the chance that a check meets a DC, without rolling.
+    /// * `ability` - The ability to apply to the check.
+    /// * `proficiency` - The proficiency to apply to the check.
+    /// * `advantage` - The advantage level of the check.
+    /// * `dc` - The difficulty class to meet.
+    pub fn check_probability(&self, ability: Ability, proficiency: Proficiency, advantage: Advantage, dc: i32) -> f64 {
+        let mut advantage_set: AdvantageSet = AdvantageSet::new().with(advantage, "the situation");
+        let advantage: Advantage = self.check_advantage(ability, Some(&Skill::Standard(proficiency)), &mut advantage_set);
+        let total_modifier: i32 = self.check_modifier(ability, Some(&Skill::Standard(proficiency)));
+        dice::success_probability(DieSize::D20, total_modifier, advantage, dc)
+    }
+
+    /// Calculate a passive score, such as passive Perception, without rolling.
+    ///
+    /// A passive score is 10 plus the modifiers the matching check would
+    /// add, and the Observant feat adds to passive Perception and
+    /// Investigation.
+    /// * `ability` - The ability to apply.
+    /// * `proficiency` - The proficiency to apply.
+    pub fn passive_score(&self, ability: Ability, proficiency: Proficiency) -> i32 {
+        let observant: i32 = match proficiency {
+            Proficiency::Perception | Proficiency::Investigation if self.observant => OBSERVANT_PASSIVE_BONUS,
+            _ => 0,
+        };
+        10 + self.check_modifier(ability, Some(&Skill::Standard(proficiency))) + observant
+    }
+
+    /// Everything displayable about the PC, for a character sheet.
+    ///
+    /// Skill modifiers are worked out as `check` works them out, with
+    /// each skill's usual ability, and include items, temporary
+    /// modifiers, and spells.
+    pub fn stat_block(&self) -> StatBlock {
+        let abilities: Vec<AbilityLine> = Ability::ALL.iter()
+            .map(|&ability| AbilityLine {ability, score: self.ability_score(ability), modifier: self.ability_modifier(ability)})
+            .collect();
+        let skills: Vec<SkillLine> = Proficiency::ALL.iter()
+            .map(|&proficiency| {
+                let ability: Ability = proficiency.default_ability();
+                SkillLine {
+                    proficiency,
+                    ability,
+                    level: self.proficiency_breakdown(proficiency).level,
+                    modifier: self.check_modifier(ability, Some(&Skill::Standard(proficiency))),
+                }
+            })
+            .collect();
+        let mut conditions: Vec<Condition> = self.conditions.iter().copied().collect();
+        conditions.sort_by_key(|condition| condition.to_string());
+        StatBlock {
+            name: self.name.clone(),
+            abilities,
+            skills,
+            passives: self.passives(),
+            darkvision: self.darkvision,
+            truesight: self.truesight,
+            conditions,
+            exhaustion: self.exhaustion,
+            hit_points: self.max_hp.map(|max_hp| (self.current_hp, max_hp)),
+        }
+    }
+
+    /// The PC's passive scores in each of `PASSIVE_SKILLS`, with each
+    /// skill's usual ability.
+    pub fn passives(&self) -> Vec<(Proficiency, i32)> {
+        PASSIVE_SKILLS.iter().map(|&skill| (skill, self.passive_score(skill.default_ability(), skill))).collect()
+    }
+
+    /// The PC's passive scores in each of `PASSIVE_SKILLS` in some
+    /// lighting, which affects only the skills that rely on sight.
+    ///
+    /// A score is `None` if the check would fail automatically.
+    /// * `lighting` - The level of environmental lighting.
+    pub fn passives_in(&self, lighting: Lighting) -> Vec<(Proficiency, Option<i32>)> {
+        PASSIVE_SKILLS.iter().map(|&skill| {
+            let mut advantage_set: AdvantageSet = self.situation_advantage(skill, Advantage::None, &CheckContext::new(lighting));
+            let ability: Ability = skill.default_ability();
+            let advantage: Advantage = self.check_advantage(ability, Some(&Skill::Standard(skill)), &mut advantage_set);
+            (skill, self.passive_score_with(ability, skill, advantage))
+        }).collect()
+    }
+
+    /// Calculate a passive score under some advantage level.
+    ///
+    /// Advantage adds 5 and disadvantage takes 5 away. Returns `None`
+    /// when the check would fail automatically, as when the PC cannot
+    /// perceive at all.
+    /// * `ability` - The ability to apply.
+    /// * `proficiency` - The proficiency to apply.
+    /// * `advantage` - The advantage level that applies.
+    pub fn passive_score_with(&self, ability: Ability, proficiency: Proficiency, advantage: Advantage) -> Option<i32> {
+        let adjustment: i32 = match advantage {
+            Advantage::None | Advantage::Canceled => 0,
+            Advantage::Advantage | Advantage::SuperAdvantage => 5,
+            Advantage::Disadvantage => -5,
+            Advantage::Fail => return None,
+        };
+        Some(self.passive_score(ability, proficiency) + adjustment)
+    }
+
+    /// The PC's passive Wisdom (Perception) score.
+    pub fn passive_perception(&self) -> i32 {
+        self.passive_score(Ability::Wisdom, Proficiency::Perception)
+    }
+
+    /// The PC's passive Wisdom (Perception) score in some lighting.
+    ///
+    /// Returns `None` if the PC cannot see at all.
+    /// * `advantage` - Any additional advantage beyond the usual perception parameters.
+    /// * `lighting` - The level of environmental lighting.
+    /// * `distance` - How far away the target is in feet, or `None` if not known.
+    pub fn passive_perception_in(&self, advantage: Advantage, lighting: Lighting, distance: Option<u16>) -> Option<i32> {
+        let context: CheckContext = CheckContext {distance, ..CheckContext::new(lighting)};
+        let mut advantage_set: AdvantageSet = self.situation_advantage(Proficiency::Perception, advantage, &context);
+        let advantage: Advantage = self.check_advantage(Ability::Wisdom, Some(&Skill::Standard(Proficiency::Perception)), &mut advantage_set);
+        self.passive_score_with(Ability::Wisdom, Proficiency::Perception, advantage)
+    }
+
+    /// Whether the PC's darkvision reaches a target. A target at an
+    /// unknown distance is taken to be within range.
+    /// * `distance` - How far away the target is in feet, or `None` if not known.
+    fn darkvision_reaches(&self, distance: Option<u16>) -> bool {
+        match (self.darkvision, distance) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(range), Some(distance)) => distance <= range,
+        }
+    }
+
+    /// Whether the PC sees a target normally however dark it is, even
+    /// in magical darkness, through truesight or Devil's Sight. A target
+    /// at an unknown distance is taken to be within range.
+    /// * `distance` - How far away the target is in feet, or `None` if not known.
+    fn sees_through_darkness(&self, distance: Option<u16>) -> bool {
+        let devils_sight: Option<u16> = if self.traits.contains(&Trait::DevilsSight) { Some(DEVILS_SIGHT_RANGE) } else { None };
+        match (self.truesight.max(devils_sight), distance) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(range), Some(distance)) => distance <= range,
+        }
+    }
+
+    /// Gather the advantage that applies to a check which relies on sight.
+    ///
+    /// Truesight and Devil's Sight see normally in any darkness;
+    /// darkvision does not see in magical darkness.
+    /// * `advantage` - Any additional advantage beyond the usual parameters.
+    /// * `lighting` - The level of environmental lighting.
+    /// * `darkvision` - Whether the PC's darkvision reaches the target.
+    /// * `sees_through_darkness` - Whether the PC's truesight or Devil's Sight reaches the target.
+    fn sight_advantage(&self, advantage: Advantage, lighting: Lighting, darkvision: bool, sees_through_darkness: bool) -> AdvantageSet {
+        let mut advantage_set: AdvantageSet = AdvantageSet::new().with(advantage, "the situation");
+        match lighting {
+            _ if sees_through_darkness => (),
+            Lighting::MagicalDark => advantage_set.add(Advantage::Fail, "magical darkness"),
+            Lighting::Dark => if darkvision {
+                advantage_set.add(Advantage::Disadvantage, "darkness, seen with darkvision");
+            } else {
+                advantage_set.add(Advantage::Fail, "darkness without darkvision");
+            },
+            Lighting::Dim => if !darkvision {
+                advantage_set.add(Advantage::Disadvantage, "dim light without darkvision");
+            },
+            Lighting::Light => (),
+        };
+        if self.has_condition(Condition::Blinded) {
+            advantage_set.add(Advantage::Fail, "blinded");
+        }
+        advantage_set
+    }
+
+    /// Gather the advantage that applies to a Perception check which
+    /// relies on hearing or smell, including the weather, the distance,
+    /// and, for hearing, the noise.
+    /// * `advantage` - Any additional advantage beyond the usual parameters.
+    /// * `context` - The circumstances of the check, including the sense it relies on.
+    fn sense_advantage(&self, advantage: Advantage, context: &CheckContext) -> AdvantageSet {
+        let sense: Sense = context.sense;
+        let mut advantage_set: AdvantageSet = AdvantageSet::new().with(advantage, "the situation");
+        advantage_set.add(context.weather.perception_effect(sense), &context.weather.to_string());
+        add_distance_advantage(context, &mut advantage_set);
+        if self.traits.contains(&Trait::KeenHearingAndSmell) {
+            advantage_set.add(Advantage::Advantage, "Keen Hearing and Smell");
+        }
+        if sense == Sense::Hearing {
+            advantage_set.add(context.noise.hearing_effect(), &context.noise.to_string());
+            if self.has_condition(Condition::Deafened) {
+                advantage_set.add(Advantage::Fail, "deafened");
+            }
+        }
+        advantage_set
+    }
+
+    /// Roll one side of a contest.
+    /// * `side` - The check to make.
+    /// * `lighting` - The level of environmental lighting, which matters
+    ///   only to Perception.
+    fn contest_check(&mut self, side: ContestSide, lighting: Lighting) -> Result<CheckResult, DiceError> {
+        let advantage_set: AdvantageSet = self.situation_advantage(side.proficiency, side.advantage, &CheckContext::new(lighting));
+        self.roll_check(side.ability, Some(Skill::Standard(side.proficiency)), advantage_set, &[])
+    }
+
+    /// Gather the advantage the situation gives a check, which for
+    /// skills that rely on sight includes the lighting, obscurement, and
+    /// weather, and for Perception includes the distance and Sunlight
+    /// Sensitivity. A Perception check which relies on another sense
+    /// ignores all but the weather and distance.
+    /// * `proficiency` - The proficiency the check uses.
+    /// * `advantage` - The advantage level before the situation and conditions.
+    /// * `context` - The circumstances of the check.
+    fn situation_advantage(&self, proficiency: Proficiency, advantage: Advantage, context: &CheckContext) -> AdvantageSet {
+        if proficiency == Proficiency::Perception && context.sense != Sense::Sight {
+            return self.sense_advantage(advantage, context);
+        }
+        if !proficiency.relies_on_sight() {
+            return AdvantageSet::new().with(advantage, "the situation");
+        }
+        // A known distance is measured against the range of each of the
+        // PC's senses: beyond its darkvision, darkness blinds them.
+        let darkvision: bool = self.darkvision_reaches(context.distance);
+        let sees_through_darkness: bool = self.sees_through_darkness(context.distance);
+        let mut advantage_set: AdvantageSet = self.sight_advantage(advantage, context.effective_lighting(), darkvision, sees_through_darkness);
+        // Darkvision sees through darkness, but not through fog.
+        match context.obscurement {
+            Obscurement::Clear => (),
+            Obscurement::LightlyObscured => advantage_set.add(Advantage::Disadvantage, "lightly obscured"),
+            Obscurement::HeavilyObscured => advantage_set.add(Advantage::Fail, "heavily obscured"),
+        };
+        if context.weather != Weather::Clear {
+            let weather: String = context.weather.to_string();
+            match context.weather.obscurement(context.distance) {
+                Obscurement::Clear => (),
+                Obscurement::LightlyObscured => advantage_set.add(Advantage::Disadvantage, &weather),
+                Obscurement::HeavilyObscured => advantage_set.add(Advantage::Fail, &weather),
+            };
+            if proficiency == Proficiency::Perception {
+                advantage_set.add(context.weather.perception_effect(Sense::Sight), &weather);
+            }
+        }
+        if proficiency == Proficiency::Perception {
+            add_distance_advantage(context, &mut advantage_set);
+        }
+        if proficiency == Proficiency::Perception && context.sunlight && self.traits.contains(&Trait::SunlightSensitivity) {
+            advantage_set.add(Advantage::Disadvantage, "direct sunlight (Sunlight Sensitivity)");
+        }
+        advantage_set
+    }
+
+    /// Work out everything a check would add, without rolling.
+    ///
+    /// The dice are not touched, and help the PC has accepted is not
+    /// used up.
+    /// * `ability` - The ability to apply to the check.
+    /// * `proficiency` - The proficiency to apply to the check.
+    /// * `advantage` - The advantage level before lighting and conditions.
+    /// * `lighting` - The level of environmental lighting.
+    /// * `dc` - The difficulty class to meet, if known.
+    pub fn preview_check(&self, ability: Ability, proficiency: Proficiency, advantage: Advantage, lighting: Lighting, dc: Option<i32>) -> CheckPreview {
+        let skill: Skill = Skill::Standard(proficiency);
+        let mut advantage_set: AdvantageSet = self.situation_advantage(proficiency, advantage, &CheckContext::new(lighting));
+        let advantage: Advantage = self.check_advantage(ability, Some(&skill), &mut advantage_set);
+        let total_modifier: i32 = self.check_modifier(ability, Some(&skill));
+        CheckPreview {
+            ability,
+            proficiency,
+            ability_modifier: self.ability_modifier(ability),
+            proficiency_modifier: self.proficiency_modifier(proficiency),
+            modifiers: self.applicable_modifiers(ability, Some(&skill)).cloned().collect(),
+            total_modifier,
+            advantage,
+            advantage_sources: advantage_set,
+            success_probability: dc.map(|dc| dice::success_probability(DieSize::D20, total_modifier, advantage, dc)),
+        }
+    }
+
+    /// Roll a check in some circumstances.
+    ///
+    /// A check with a skill which relies on sight, such as Perception
+    /// or Investigation, takes the lighting into account; others, such
+    /// as Insight, do not.
+    /// * `context` - The circumstances of the check.
+    /// * `ability` - The ability to apply to the check.
+    /// * `proficiency` - The proficiency to apply to the check.
+    /// * `advantage` - Any additional advantage beyond the circumstances.
+    pub fn check_in(&mut self, context: &CheckContext, ability: Ability, proficiency: Proficiency, advantage: Advantage) -> Result<CheckResult, DiceError> {
+        let advantage_set: AdvantageSet = self.situation_advantage(proficiency, advantage, context);
+        log::info!("{}'s {} check with {}", self.name, proficiency, advantage_set);
+        let mut result: CheckResult = self.roll_check(ability, Some(Skill::Standard(proficiency)), advantage_set, &[])?;
+        if proficiency == Proficiency::Perception {
+            result.sense = Some(context.sense);
+        }
+        Ok(result)
+    }
+
+    /// Roll a Wisdom (Perception) check relying on sight.
+    ///
+    /// Apply all available modifiers, including potential disadvantage from
+    /// lighting conditions.
+    /// * `advantage` - Any additional advantage beyond the usual perception parameters.
+    /// * `lighting` - The level of environmental lighting.
+    /// * `distance` - How far away the target is in feet, or `None` if not known.
+    pub fn perception_check(&mut self, advantage: Advantage, lighting: Lighting, distance: Option<u16>) -> Result<CheckResult, DiceError> {
+        self.perception_check_with(Sense::Sight, advantage, lighting, distance)
+    }
+
+    /// Roll a Wisdom (Perception) check relying on some sense.
+    ///
+    /// Only checks which rely on sight suffer in poor light.
+    /// * `sense` - The sense the check relies on.
+    /// * `advantage` - Any additional advantage beyond the usual perception parameters.
+    /// * `lighting` - The level of environmental lighting.
+    /// * `distance` - How far away the target is in feet, or `None` if not known.
+    pub fn perception_check_with(&mut self, sense: Sense, advantage: Advantage, lighting: Lighting, distance: Option<u16>) -> Result<CheckResult, DiceError> {
+        let context: CheckContext = CheckContext {distance, ..CheckContext::new(lighting)}.with_sense(sense);
+        self.check_in(&context, Ability::Wisdom, Proficiency::Perception, advantage)
+    }
+}
+
+/// Who wins a contest, or who wins a tied one.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ContestWinner {
+    /// The creature who started the contest, such as one hiding.
+    Initiator,
+    /// The creature the contest is against, such as one searching.
+    /// The rules give ties to the target, since nothing changes.
+    #[default]
+    Target,
+    /// Neither creature.
+    Neither,
+}
+
+/// One creature's side of a contest.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ContestSide {
+    /// The ability the creature's check uses.
+    pub ability: Ability,
+    /// The proficiency the creature's check uses.
+    pub proficiency: Proficiency,
+    /// The advantage level of the creature's check, before conditions
+    /// and lighting.
+    pub advantage: Advantage,
+}
+
+impl ContestSide {
+    /// A side which uses a skill with its usual ability.
+    /// * `skill` - The skill the check uses.
+    /// * `advantage` - The advantage level of the check.
+    pub fn skill(skill: Proficiency, advantage: Advantage) -> ContestSide {
+        ContestSide {ability: skill.default_ability(), proficiency: skill, advantage}
+    }
+}
+
+/// The outcome of a contest between two creatures.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContestResult {
+    /// The initiator's check.
+    initiator: CheckResult,
+    /// The target's check.
+    target: CheckResult,
+    /// Who won.
+    winner: ContestWinner,
+}
+
+impl ContestResult {
+    /// The initiator's check.
+    pub fn initiator(&self) -> &CheckResult {
+        &self.initiator
+    }
+
+    /// The target's check.
+    pub fn target(&self) -> &CheckResult {
+        &self.target
+    }
+
+    /// Who won.
+    pub fn winner(&self) -> ContestWinner {
+        self.winner
+    }
+}
+
+/// Add the advantage the distance to a target gives a Perception check.
+/// * `context` - The circumstances of the check, including the distance.
+/// * `advantage_set` - Where to add the advantage.
+fn add_distance_advantage(context: &CheckContext, advantage_set: &mut AdvantageSet) {
+    if let Some(feet) = context.distance {
+        advantage_set.add(context.rules.distance_effect(feet), &format!("{} ft away", feet));
+    }
+}
+
+/// Resolve a contest, such as one creature's Dexterity (Stealth)
+/// against another's Wisdom (Perception).
+///
+/// Each side rolls with their own advantage, and a Perception check
+/// also takes the lighting into account. A side which fails
+/// automatically loses whatever the other side's total; if both do,
+/// the contest is a tie.
+/// * `initiator` - The creature who starts the contest.
+/// * `initiator_side` - The check the initiator makes.
+/// * `target` - The creature the contest is against.
+/// * `target_side` - The check the target makes.
+/// * `lighting` - The level of environmental lighting.
+/// * `tiebreak` - Who wins a tie.
+pub fn contest(initiator: &mut PC, initiator_side: ContestSide, target: &mut PC, target_side: ContestSide, lighting: Lighting, tiebreak: ContestWinner) -> Result<ContestResult, DiceError> {
+    let initiator_check: CheckResult = initiator.contest_check(initiator_side, lighting)?;
+    let target_check: CheckResult = target.contest_check(target_side, lighting)?;
+    let initiator_failed: bool = initiator_check.roll().is_automatic_failure();
+    let target_failed: bool = target_check.roll().is_automatic_failure();
+    let winner: ContestWinner = match (initiator_failed, target_failed) {
+        (true, true) => tiebreak,
+        (true, false) => ContestWinner::Target,
+        (false, true) => ContestWinner::Initiator,
+        (false, false) => match initiator_check.total().cmp(&target_check.total()) {
+            std::cmp::Ordering::Greater => ContestWinner::Initiator,
+            std::cmp::Ordering::Less => ContestWinner::Target,
+            std::cmp::Ordering::Equal => tiebreak,
+        },
+    };
+    log::info!(
+        "Contest of {}'s {} against {}'s {}: {} to {}, {:?} wins",
+        initiator.name, initiator_side.proficiency, target.name, target_side.proficiency,
+        initiator_check.total(), target_check.total(), winner,
+    );
+    Ok(ContestResult {initiator: initiator_check, target: target_check, winner})
+}
+
+/// One party member's part in a group check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemberCheck {
+    /// The member's name.
+    pub name: String,
+    /// The member's check.
+    pub result: CheckResult,
+    /// How the member's roll fared against the DC.
+    pub outcome: CheckOutcome,
+}
+
+/// The outcome of a group check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GroupCheckResult {
+    /// Each member's check, in party order.
+    members: Vec<MemberCheck>,
+    /// Whether at least half the party succeeded.
+    passed: bool,
+}
+
+impl GroupCheckResult {
+    /// Each member's check, in party order.
+    pub fn members(&self) -> &[MemberCheck] {
+        &self.members
+    }
+
+    /// Whether at least half the party succeeded.
+    pub fn passed(&self) -> bool {
+        self.passed
+    }
+}
+
+/// One party member's place in the initiative order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InitiativeRoll {
+    /// The member's name.
+    pub name: String,
+    /// The member's initiative roll.
+    pub result: RollResult,
+    /// The face of the flat d20 which broke a tie, if one was needed.
+    pub tiebreak: Option<u16>,
+}
+
+/// The characters adventuring together.
+pub struct Party {
+    members: Vec<PC>,
+}
+
+impl Party {
+    /// Gather characters into a party.
+    /// * `members` - The characters, in the order they should act.
+    pub fn new(members: Vec<PC>) -> Party {
+        Party {members}
+    }
+
+    /// The characters in the party.
+    pub fn members(&self) -> &[PC] {
+        &self.members
+    }
+
+    /// The characters in the party, for changing.
+    pub fn members_mut(&mut self) -> &mut [PC] {
+        &mut self.members
+    }
+
+    /// Reload every member from a configuration's JSON after it changes,
+    /// as `reload_party` describes, returning the names of the
+    /// characters now in the party.
+    /// * `dice` - The dice that the PCs will share to generate randomness.
+    /// * `text` - JSON describing one character or a party.
+    /// * `dir` - The directory of the file the JSON was read from.
+    pub fn reload(&mut self, dice: Rc<dyn Roller>, text: &str, dir: &Path) -> Result<Vec<String>, PcError> {
+        reload_party(dice, text, dir, &mut self.members)
+    }
+
+    /// Add a character to the end of the party.
+    /// * `member` - The character to add.
+    pub fn push(&mut self, member: PC) {
+        self.members.push(member);
+    }
+
+    /// Find a character by name.
+    /// * `name` - The character's name.
+    pub fn get(&self, name: &str) -> Option<&PC> {
+        self.members.iter().find(|member| member.name == name)
+    }
+
+    /// Find a character by name, for changing.
+    /// * `name` - The character's name.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut PC> {
+        self.members.iter_mut().find(|member| member.name == name)
+    }
+
+    /// Find a character by identifier.
+    /// * `id` - The character's identifier.
+    pub fn get_by_id(&self, id: PcId) -> Option<&PC> {
+        self.members.iter().find(|member| member.id == id)
+    }
+
+    /// Find a character by identifier, for changing.
+    /// * `id` - The character's identifier.
+    pub fn get_by_id_mut(&mut self, id: PcId) -> Option<&mut PC> {
+        self.members.iter_mut().find(|member| member.id == id)
+    }
+
+    /// Take a character out of the party, keeping the others in order.
+    /// * `id` - The character's identifier.
+    pub fn remove(&mut self, id: PcId) -> Option<PC> {
+        let index: usize = self.members.iter().position(|member| member.id == id)?;
+        Some(self.members.remove(index))
+    }
+
+    /// The number of characters in the party.
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Whether the party has no characters.
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Roll initiative for every member, highest first.
+    ///
+    /// Ties go to the higher Dexterity modifier. Members still tied
+    /// each roll a flat d20, again until no two of them match, up to
+    /// `MAX_INITIATIVE_ROLL_OFFS` times; any who still match, as when
+    /// the dice roll averages, act in party order.
+    pub fn roll_initiative(&mut self) -> Result<Vec<InitiativeRoll>, DiceError> {
+        // Each entry holds the member's index, to find them again for tiebreaks.
+        let mut order: Vec<(usize, InitiativeRoll)> = Vec::new();
+        for (index, member) in self.members.iter_mut().enumerate() {
+            let result: RollResult = member.initiative(Advantage::None)?;
+            order.push((index, InitiativeRoll {name: member.name.clone(), result, tiebreak: None}));
+        }
+        let rank = |entry: &(usize, InitiativeRoll)| (entry.1.result.total(), self.members[entry.0].ability_modifier(Ability::Dexterity));
+        order.sort_by_key(|entry| std::cmp::Reverse(rank(entry)));
+
+        let mut start: usize = 0;
+        while start < order.len() {
+            let mut end: usize = start + 1;
+            while end < order.len() && rank(&order[end]) == rank(&order[start]) {
+                end += 1;
+            }
+            if end - start > 1 {
+                for _ in 0..MAX_INITIATIVE_ROLL_OFFS {
+                    let mut faces: Vec<u16> = Vec::new();
+                    for (index, entry) in &mut order[start..end] {
+                        let face: u16 = self.members[*index].dice.d_flat(DieSize::D20, 0).natural();
+                        entry.tiebreak = Some(face);
+                        faces.push(face);
+                    }
+                    faces.sort_unstable();
+                    faces.dedup();
+                    if faces.len() == end - start {
+                        break;
+                    }
+                }
+                order[start..end].sort_by_key(|entry| std::cmp::Reverse(entry.1.tiebreak));
+            }
+            start = end;
+        }
+
+        let order: Vec<InitiativeRoll> = order.into_iter().map(|(_, entry)| entry).collect();
+        let shown: Vec<String> = order.iter().map(|entry| format!("{} {}", entry.name, entry.result.total())).collect();
+        log::info!("Initiative order: {}", shown.join(", "));
+        Ok(order)
+    }
+
+    /// Have every member take a long rest, as `PC::long_rest` describes.
+    /// Returns each member's name with what changed for them, in party order.
+    pub fn long_rest(&mut self) -> Vec<(String, Vec<RestChange>)> {
+        self.members.iter_mut().map(|member| (member.name.clone(), member.long_rest())).collect()
+    }
+
+    /// Make a group check, which passes if at least half the party succeeds.
+    ///
+    /// A member whose advantage resolves to `Advantage::Fail` fails
+    /// without rolling. An empty party never passes. Members who share
+    /// dice roll as one batch, with `Roller::try_d_many`.
+    /// * `ability` - The ability to apply to each check.
+    /// * `proficiency` - The proficiency to apply to each check.
+    /// * `dc` - The difficulty class each member must meet.
+    /// * `advantage_overrides` - The advantage level for members, by name; others roll with none.
+    pub fn group_check(&mut self, ability: Ability, proficiency: Proficiency, dc: i32, advantage_overrides: &HashMap<String, Advantage>) -> Result<GroupCheckResult, DiceError> {
+        let skill: Skill = Skill::Standard(proficiency);
+        let mut advantage_sets: Vec<AdvantageSet> = Vec::new();
+        let mut specs: Vec<RollSpec> = Vec::new();
+        for member in &self.members {
+            let advantage: Advantage = advantage_overrides.get(&member.name).copied().unwrap_or(Advantage::None);
+            let mut advantage_set: AdvantageSet = AdvantageSet::new().with(advantage, "the situation");
+            specs.push(member.check_spec(ability, Some(&skill), &mut advantage_set, &[]));
+            advantage_sets.push(advantage_set);
+        }
+
+        // Members who share dice roll together, as one batch.
+        let mut rolls: Vec<Option<RollResult>> = vec![None; self.members.len()];
+        for first in 0..self.members.len() {
+            if rolls[first].is_some() {
+                continue;
+            }
+            let batch: Vec<usize> = (first..self.members.len())
+                .filter(|&index| Rc::ptr_eq(&self.members[index].dice, &self.members[first].dice))
+                .collect();
+            let batch_specs: Vec<RollSpec> = batch.iter().map(|&index| specs[index].clone()).collect();
+            let results: Vec<RollResult> = self.members[first].dice.try_d_many(&batch_specs)?;
+            for (index, result) in batch.into_iter().zip(results) {
+                rolls[index] = Some(result);
+            }
+        }
+
+        let mut members: Vec<MemberCheck> = Vec::new();
+        // Every member has been rolled for, so no roll is left out.
+        for ((member, advantage_set), roll) in self.members.iter_mut().zip(advantage_sets).zip(rolls.into_iter().flatten()) {
+            let result: CheckResult = member.finish_check(ability, Some(skill.clone()), advantage_set, roll);
+            let outcome: CheckOutcome = CheckOutcome::of(result.roll(), dc);
+            members.push(MemberCheck {name: member.name.clone(), result, outcome});
+        }
+        let successes: usize = members.iter().filter(|member| member.outcome.is_success()).count();
+        let passed: bool = !members.is_empty() && successes * 2 >= members.len();
+        log::info!("Group {} ({}) check against DC {}: {} of {} succeeded", ability, proficiency, dc, successes, members.len());
+        Ok(GroupCheckResult {members, passed})
+    }
+}
+
+impl From<Vec<PC>> for Party {
+    fn from(members: Vec<PC>) -> Party {
+        Party::new(members)
+    }
+}
+
+/// Load every character from a configuration file.
+///
+/// The file may describe a single character, or a whole party as a
+/// `"party"` array of characters. The characters are returned in the
+/// order given. Files named by `"base"` keys are found relative to the
+/// working directory; use `parse_party_in` to find them elsewhere.
+/// * `dice` - The dice that the PCs will share to generate randomness.
+/// * `config` - A JSON configuration file describing one character or a party.
+pub fn load_party(dice: Rc<dyn Roller>, config: &mut File) -> Result<Vec<PC>, PcError> {
+    let mut config_string: String = String::new();
+    config.read_to_string(&mut config_string)?;
+    parse_party(dice, &config_string)
+}
+
+/// Load every character from a configuration's JSON, as `load_party` does.
+/// * `dice` - The dice that the PCs will share to generate randomness.
+/// * `text` - JSON describing one character or a party.
+pub fn parse_party(dice: Rc<dyn Roller>, text: &str) -> Result<Vec<PC>, PcError> {
+    parse_party_in(dice, text, Path::new(""))
+}
+
+/// Load every character from a configuration's JSON, finding the files
+/// their `"base"` keys name in the given directory.
+///
+/// A character's `"base"` names a template in the party's `"templates"`
+/// object, or else another character config file. The base's values are
+/// merged under the character's own, key by key, so that a character
+/// can change one ability or proficiency and keep the rest; a base's
+/// identifier is never inherited. Bases may have bases of their own, up
+/// to `MAX_BASE_DEPTH` deep, and a file's bases are found relative to it.
+/// * `dice` - The dice that the PCs will share to generate randomness.
+/// * `text` - JSON describing one character or a party.
+/// * `dir` - The directory of the file the JSON was read from.
+pub fn parse_party_in(dice: Rc<dyn Roller>, text: &str, dir: &Path) -> Result<Vec<PC>, PcError> {
+    let mut config_data: serde_json::Value = serde_json::from_str(text)?;
+    migrate::migrate(&mut config_data)?;
+
+    let no_templates: serde_json::Map<String, serde_json::Value> = serde_json::Map::new();
+    let templates: &serde_json::Map<String, serde_json::Value> = match config_data.get("templates") {
+        Some(serde_json::Value::Object(templates)) => templates,
+        _ => &no_templates,
+    };
+    let configs: Vec<PcConfig> = match config_data.get("party") {
+        Some(serde_json::Value::Array(party)) => party.iter()
+            .map(|config| PcConfig::deserialize(resolve_base(config.clone(), dir, templates, &mut Vec::new())?).map_err(PcError::from))
+            .collect::<Result<Vec<PcConfig>, PcError>>()?,
+        Some(party) => Vec::<PcConfig>::deserialize(party)?,
+        None => vec![PcConfig::deserialize(resolve_base(config_data.clone(), dir, &no_templates, &mut Vec::new())?)?],
+    };
+
+    let mut pcs: Vec<PC> = Vec::new();
+    for pc_config in &configs {
+        let pc: PC = PC::from_config(Rc::clone(&dice), pc_config)?;
+        if pcs.iter().any(|other: &PC| other.name == pc.name) {
+            return Err(PcError::DuplicateName(pc.name));
+        }
+        if pcs.iter().any(|other: &PC| other.id == pc.id) {
+            return Err(PcError::DuplicateId(pc.id));
+        }
+        pcs.push(pc);
+    }
+    Ok(pcs)
+}
+
+/// The most bases a character's `"base"` chain may go through.
+pub const MAX_BASE_DEPTH: usize = 8;
+
+/// The key naming the template or file a character config is based on.
+const BASE_KEY: &str = "base";
+
+/// Merge a character config's base, and its base's bases, underneath it.
+/// * `config` - The character config's JSON.
+/// * `dir` - The directory of the file the config was read from.
+/// * `templates` - The named templates the config may be based on.
+/// * `chain` - The bases already merged into this config, to find cycles.
+fn resolve_base(config: serde_json::Value, dir: &Path, templates: &serde_json::Map<String, serde_json::Value>, chain: &mut Vec<String>) -> Result<serde_json::Value, PcError> {
+    let mut config: serde_json::Map<String, serde_json::Value> = match config {
+        serde_json::Value::Object(config) => config,
+        other => return Ok(other),
+    };
+    let base: String = match config.remove(BASE_KEY) {
+        None => return Ok(serde_json::Value::Object(config)),
+        Some(serde_json::Value::String(base)) => base,
+        Some(_) => return Err(PcError::Parse(serde::de::Error::custom("\"base\" must name a template or file"))),
+    };
+    if chain.len() >= MAX_BASE_DEPTH {
+        return Err(PcError::BaseTooDeep(base));
+    }
+
+    // A template is named as itself; a file by where it is, so that the
+    // same file reached by two paths is still found to repeat.
+    let no_templates: serde_json::Map<String, serde_json::Value> = serde_json::Map::new();
+    let (key, parent, parent_dir, parent_templates) = match templates.get(&base) {
+        Some(template) => (base, template.clone(), dir.to_path_buf(), templates),
+        None => {
+            let path: std::path::PathBuf = dir.join(&base);
+            let text: String = std::fs::read_to_string(&path)?;
+            let key: String = path.canonicalize().unwrap_or_else(|_| path.clone()).display().to_string();
+            let parent_dir: std::path::PathBuf = path.parent().map(Path::to_path_buf).unwrap_or_default();
+            let mut parent: serde_json::Value = serde_json::from_str(&text)?;
+            migrate::migrate(&mut parent)?;
+            (key, parent, parent_dir, &no_templates)
+        },
+    };
+    if chain.contains(&key) {
+        let mut cycle: Vec<String> = chain.clone();
+        cycle.push(key);
+        return Err(PcError::BaseCycle(cycle));
+    }
+    chain.push(key);
+    let mut parent: serde_json::Value = resolve_base(parent, &parent_dir, parent_templates, chain)?;
+    chain.pop();
+
+    if let serde_json::Value::Object(parent) = &mut parent {
+        parent.remove("id");
+    }
+    Ok(merge_config(parent, serde_json::Value::Object(config)))
+}
+
+/// Merge one config's JSON over another's: objects are merged key by
+/// key, and anything else the child gives replaces the parent's.
+/// * `parent` - The values to merge under.
+/// * `child` - The values which take precedence.
+fn merge_config(parent: serde_json::Value, child: serde_json::Value) -> serde_json::Value {
+    match (parent, child) {
+        (serde_json::Value::Object(mut parent), serde_json::Value::Object(child)) => {
+            for (key, value) in child {
+                let merged: serde_json::Value = match parent.remove(&key) {
+                    Some(inherited) => merge_config(inherited, value),
+                    None => value,
+                };
+                parent.insert(key, merged);
+            }
+            serde_json::Value::Object(parent)
+        },
+        (_, child) => child,
+    }
+}
+
+/// Reload every character from a configuration's JSON after it changes,
+/// returning the names of the characters now loaded.
+///
+/// A character the config still names keeps their identifier and the
+/// conditions, temporary modifiers, and help they gained in play;
+/// everything else, including the conditions the config lists, comes
+/// from the config. If the config cannot be
+/// loaded, the party is left as it was.
+/// * `dice` - The dice that the PCs will share to generate randomness.
+/// * `text` - JSON describing one character or a party.
+/// * `dir` - The directory of the file the JSON was read from.
+/// * `pcs` - The characters loaded before, to replace.
+pub fn reload_party(dice: Rc<dyn Roller>, text: &str, dir: &Path, pcs: &mut Vec<PC>) -> Result<Vec<String>, PcError> {
+    let mut reloaded: Vec<PC> = parse_party_in(dice, text, dir)?;
+    for pc in &mut reloaded {
+        if let Some(index) = pcs.iter().position(|previous| previous.name == pc.name) {
+            pc.keep_play_state(pcs.swap_remove(index));
+        }
+    }
+    *pcs = reloaded;
+    Ok(pcs.iter().map(|pc| pc.name.clone()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice::{Dice, FixedRoller, RollMode};
+    use crate::environment::{EnvironmentRules, LightSource, NoiseLevel};
+    use std::path::PathBuf;
+
+    /// Build a character from a config, with dice which roll the given faces.
+    /// * `json` - The character's config.
+    /// * `faces` - The faces the character's dice roll, in order.
+    fn pc(json: &str, faces: &[u16]) -> PC {
+        let config: PcConfig = PcConfig::parse(json, true).unwrap();
+        PC::from_config(Rc::new(FixedRoller::new(faces)), &config).unwrap()
+    }
+
+    #[test]
+    fn check_adds_proficiency_by_level() {
+        for (level, total) in [(0, 12), (1, 15), (2, 18)] {
+            let mut pc: PC = pc(&format!(r#"{{"abilities":{{"wisdom":14}},"proficiencies":{{"perception":{}}},"proficiency_bonus":3}}"#, level), &[10]);
+            let result: CheckResult = pc.check(Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap();
+            assert_eq!(result.total(), total, "proficiency level {}", level);
+            assert_eq!(result.ability_modifier(), 2);
+        }
+    }
+
+    #[test]
+    fn lucky_rerolls_natural_1s() {
+        let mut pc: PC = pc(r#"{"lucky":true}"#, &[1, 12]);
+        let result: CheckResult = pc.check(Ability::Wisdom, Proficiency::Insight, Advantage::None).unwrap();
+        assert_eq!(result.total(), 12);
+        assert_eq!(result.roll().rerolled_from(), Some(1));
+    }
+
+    #[test]
+    fn low_scores_give_negative_totals() {
+        let mut pc: PC = pc(r#"{"abilities":{"strength":1}}"#, &[1]);
+        assert_eq!(pc.ability_check(Ability::Strength, Advantage::None).unwrap().total(), -4);
+    }
+
+    #[test]
+    fn check_probability_uses_the_check_modifier() {
+        let pc: PC = pc(r#"{"abilities":{"wisdom":14},"proficiencies":{"perception":1},"proficiency_bonus":3}"#, &[]);
+        assert!((pc.check_probability(Ability::Wisdom, Proficiency::Perception, Advantage::None, 15) - 0.55).abs() < 1e-9);
+    }
+
+    #[test]
+    fn elven_accuracy_rolls_three_dice_for_finesse_abilities() {
+        let json: &str = r#"{"elven_accuracy":true}"#;
+        let mut accurate: PC = pc(json, &[3, 19, 7]);
+        let result: CheckResult = accurate.check(Ability::Dexterity, Proficiency::Stealth, Advantage::Advantage).unwrap();
+        assert_eq!((result.advantage(), result.faces().len(), result.natural()), (Advantage::SuperAdvantage, 3, 19));
+        let mut strong: PC = pc(json, &[3, 19]);
+        let result: CheckResult = strong.check(Ability::Strength, Proficiency::Athletics, Advantage::Advantage).unwrap();
+        assert_eq!((result.advantage(), result.faces().len()), (Advantage::Advantage, 2));
+    }
+
+    #[test]
+    fn every_skill_parses_from_its_name() {
+        for skill in Proficiency::ALL {
+            let name: String = skill.to_string();
+            assert_eq!(Proficiency::from_str(&name), Ok(skill));
+            assert_eq!(Proficiency::from_str(&name.to_lowercase().replace(' ', "_")), Ok(skill));
+            assert_eq!(Proficiency::from_str(&name.replace(' ', "")), Ok(skill));
+        }
+        assert_eq!(Proficiency::from_str("lockpicking"), Err(()));
+    }
+
+    #[test]
+    fn every_skill_has_its_srd_ability() {
+        let table: [(Proficiency, Ability); 18] = [
+            (Proficiency::Acrobatics, Ability::Dexterity),
+            (Proficiency::AnimalHandling, Ability::Wisdom),
+            (Proficiency::Arcana, Ability::Intelligence),
+            (Proficiency::Athletics, Ability::Strength),
+            (Proficiency::Deception, Ability::Charisma),
+            (Proficiency::History, Ability::Intelligence),
+            (Proficiency::Insight, Ability::Wisdom),
+            (Proficiency::Intimidation, Ability::Charisma),
+            (Proficiency::Investigation, Ability::Intelligence),
+            (Proficiency::Medicine, Ability::Wisdom),
+            (Proficiency::Nature, Ability::Intelligence),
+            (Proficiency::Perception, Ability::Wisdom),
+            (Proficiency::Performance, Ability::Charisma),
+            (Proficiency::Persuasion, Ability::Charisma),
+            (Proficiency::Religion, Ability::Intelligence),
+            (Proficiency::SleightOfHand, Ability::Dexterity),
+            (Proficiency::Stealth, Ability::Dexterity),
+            (Proficiency::Survival, Ability::Wisdom),
+        ];
+        for (skill, ability) in table {
+            assert_eq!(skill.default_ability(), ability, "{}", skill);
+        }
+    }
+
+    #[test]
+    fn configs_list_any_skill_and_skill_checks_use_its_ability() {
+        let skills: Vec<String> = Proficiency::ALL.iter().map(|skill| format!(r#""{}":1"#, skill.to_string().to_lowercase())).collect();
+        let json: String = format!(r#"{{"abilities":{{"dexterity":16}},"proficiencies":{{{}}},"proficiency_bonus":2}}"#, skills.join(","));
+        let mut pc: PC = pc(&json, &[10]);
+        assert_eq!(pc.skill_check(Proficiency::Stealth, Advantage::None).unwrap().total(), 15);
+    }
+
+    #[test]
+    fn saving_throws_add_proficiency_only_where_proficient() {
+        let mut pc: PC = pc(r#"{"abilities":{"dexterity":16,"wisdom":14},"saving_throws":{"dexterity":1},"proficiency_bonus":2}"#, &[10, 10]);
+        assert_eq!(pc.saving_throw(Ability::Dexterity, Advantage::None).unwrap().total(), 15);
+        assert_eq!(pc.saving_throw(Ability::Wisdom, Advantage::None).unwrap().total(), 12);
+    }
+
+    #[test]
+    fn passive_scores_are_10_plus_the_modifier_and_5_for_advantage() {
+        let pc: PC = pc(r#"{"abilities":{"wisdom":14},"proficiencies":{"perception":2},"proficiency_bonus":2}"#, &[]);
+        assert_eq!(pc.passive_perception(), 16);
+        assert_eq!(pc.passive_score_with(Ability::Wisdom, Proficiency::Perception, Advantage::Advantage), Some(21));
+        assert_eq!(pc.passive_score_with(Ability::Wisdom, Proficiency::Perception, Advantage::Disadvantage), Some(11));
+        assert_eq!(pc.passive_perception_in(Advantage::None, Lighting::Dim, None), Some(11));
+        assert_eq!(pc.passive_perception_in(Advantage::None, Lighting::Dark, None), None);
+    }
+
+    #[test]
+    fn conditions_change_advantage_until_removed() {
+        let mut pc: PC = pc(r#"{"abilities":{"wisdom":14},"proficiencies":{"perception":2},"proficiency_bonus":2,"conditions":["poisoned"]}"#, &[15, 3, 15, 3, 15]);
+        assert!(pc.has_condition(Condition::Poisoned));
+        assert_eq!(pc.check(Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap().advantage(), Advantage::Disadvantage);
+        assert_eq!(pc.check(Ability::Wisdom, Proficiency::Perception, Advantage::Advantage).unwrap().advantage(), Advantage::Canceled);
+        assert_eq!(pc.passive_perception_in(Advantage::None, Lighting::Dim, None), Some(11));
+        pc.add_condition(Condition::Blinded);
+        assert_eq!(pc.passive_perception_in(Advantage::None, Lighting::Light, None), None);
+        pc.remove_condition(Condition::Poisoned);
+        pc.remove_condition(Condition::Blinded);
+        assert_eq!(pc.check(Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap().advantage(), Advantage::None);
+    }
+
+    #[test]
+    fn exhaustion_gives_disadvantage_and_then_death() {
+        let mut pc: PC = pc(r#"{"abilities":{"wisdom":14},"proficiencies":{"perception":2},"proficiency_bonus":2,"exhaustion":1}"#, &[15, 3, 15, 3, 15]);
+        assert_eq!(pc.exhaustion(), 1);
+        assert_eq!(pc.check(Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap().advantage(), Advantage::Disadvantage);
+        assert_eq!(pc.check(Ability::Wisdom, Proficiency::Perception, Advantage::Advantage).unwrap().advantage(), Advantage::Canceled);
+        pc.set_exhaustion(0);
+        assert_eq!(pc.check(Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap().advantage(), Advantage::None);
+        pc.set_exhaustion(9);
+        assert_eq!(pc.exhaustion(), MAX_EXHAUSTION);
+        assert!(pc.check(Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap().roll().is_automatic_failure());
+    }
+
+    #[test]
+    fn darkvision_sees_in_the_dark_within_its_range() {
+        for (darkvision, near, far) in [("true", Some(11), None), ("120", Some(11), Some(11)), ("false", None, None)] {
+            let pc: PC = pc(&format!(r#"{{"abilities":{{"wisdom":14}},"proficiencies":{{"perception":2}},"proficiency_bonus":2,"darkvision":{}}}"#, darkvision), &[]);
+            assert_eq!(pc.passive_perception_in(Advantage::None, Lighting::Dark, Some(50)), near, "darkvision {}", darkvision);
+            assert_eq!(pc.passive_perception_in(Advantage::None, Lighting::Dark, Some(100)), far, "darkvision {}", darkvision);
+        }
+    }
+
+    #[test]
+    fn bad_configs_name_the_offending_field() {
+        let cases: [(&str, &str); 4] = [
+            (r#"{"abilities":{"wisdom":40}}"#, "abilities.wisdom"),
+            (r#"{"exhaustion":7}"#, "exhaustion"),
+            (r#"{"proficiencies":{"percepton":1}}"#, "percepton"),
+            (r#"{"abilities":"#, "parse"),
+        ];
+        for (json, field) in cases {
+            let error: PcError = PcConfig::parse(json, false)
+                .and_then(|config| PC::from_config(Rc::new(FixedRoller::new(&[])), &config).map(|_| ()))
+                .unwrap_err();
+            assert!(error.to_string().contains(field), "{} should mention {}", error, field);
+        }
+    }
+
+    #[test]
+    fn names_come_from_the_config_then_the_file() {
+        let dir: PathBuf = std::env::temp_dir().join(format!("rollforgrue-names-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path: PathBuf = dir.join("aria.json");
+        std::fs::write(&path, "{}").unwrap();
+        assert_eq!(PC::open(Rc::new(FixedRoller::new(&[])), &path).unwrap().name(), "aria");
+        std::fs::write(&path, r#"{"name":"Aria Vell"}"#).unwrap();
+        assert_eq!(PC::open(Rc::new(FixedRoller::new(&[])), &path).unwrap().name(), "Aria Vell");
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(pc("{}", &[]).name(), UNNAMED);
+    }
+
+    #[test]
+    fn parties_load_in_order_with_unique_valid_members() {
+        let dice = || -> Rc<dyn Roller> { Rc::new(FixedRoller::new(&[])) };
+        let one: &str = r#"{"name":"A"}"#;
+        let two: &str = r#"{"name":"B"}"#;
+        assert_eq!(parse_party(dice(), one).unwrap().len(), 1);
+        let party: Vec<PC> = parse_party(dice(), &format!(r#"{{"party":[{},{}]}}"#, two, one)).unwrap();
+        assert_eq!(party.iter().map(|pc| pc.name()).collect::<Vec<&str>>(), vec!["B", "A"]);
+        let duplicate: PcError = parse_party(dice(), &format!(r#"{{"party":[{},{}]}}"#, one, one)).err().unwrap();
+        assert!(matches!(duplicate, PcError::DuplicateName(ref name) if name == "A"));
+        let invalid: PcError = parse_party(dice(), &format!(r#"{{"party":[{},{{"abilities":{{"wisdom":99}}}}]}}"#, one)).err().unwrap();
+        assert!(matches!(invalid, PcError::OutOfRange(..)));
+    }
+
+    #[test]
+    fn group_checks_pass_when_half_the_party_succeeds() {
+        let dice: Rc<FixedRoller> = Rc::new(FixedRoller::new(&[20, 1, 15, 2]));
+        let names: [&str; 4] = ["a", "b", "c", "d"];
+        let members: Vec<PC> = names.iter().map(|name| {
+            let config: PcConfig = PcConfig::parse(&format!(r#"{{"name":"{}"}}"#, name), true).unwrap();
+            PC::from_config(dice.clone(), &config).unwrap()
+        }).collect();
+        let mut party: Party = Party::new(members);
+        let result: GroupCheckResult = party.group_check(Ability::Wisdom, Proficiency::Perception, 10, &HashMap::new()).unwrap();
+        assert!(result.passed());
+        assert_eq!(result.members().len(), 4);
+        let failing: HashMap<String, Advantage> = names.iter().map(|name| (name.to_string(), Advantage::Fail)).collect();
+        assert!(!party.group_check(Ability::Wisdom, Proficiency::Perception, 10, &failing).unwrap().passed());
+        assert_eq!(dice.remaining(), 0);
+        assert!(!Party::new(Vec::new()).group_check(Ability::Wisdom, Proficiency::Perception, 10, &HashMap::new()).unwrap().passed());
+    }
+
+    #[test]
+    fn proficiency_bonus_rises_every_four_levels() {
+        let bonuses: Vec<u8> = (1..=MAX_LEVEL).map(proficiency_bonus_for_level).collect();
+        assert_eq!(bonuses, vec![2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 6, 6, 6, 6]);
+        assert_eq!(proficiency_bonus_for_level(0), MIN_PROFICIENCY_BONUS);
+        assert_eq!(proficiency_bonus_for_level(u8::MAX), MAX_PROFICIENCY_BONUS);
+    }
+
+    #[test]
+    fn levels_set_the_proficiency_bonus() {
+        for (level, bonus) in [(1, 2), (4, 2), (5, 3), (17, 6), (20, 6)] {
+            let pc: PC = pc(&format!(r#"{{"proficiencies":{{"perception":1}},"level":{}}}"#, level), &[]);
+            assert_eq!(pc.level(), Some(level));
+            assert_eq!(pc.passive_perception(), 10 + bonus, "level {}", level);
+        }
+        let conflict: PcConfig = PcConfig::parse(r#"{"level":5,"proficiency_bonus":2}"#, true).unwrap();
+        assert!(matches!(PC::from_config(Rc::new(FixedRoller::new(&[])), &conflict), Err(PcError::Conflict {..})));
+        for level in [0, 21] {
+            let config: PcConfig = PcConfig::parse(&format!(r#"{{"level":{}}}"#, level), true).unwrap();
+            assert!(matches!(PC::from_config(Rc::new(FixedRoller::new(&[])), &config), Err(PcError::OutOfRange(..))), "level {}", level);
+        }
+    }
+
+    #[test]
+    fn every_out_of_range_value_is_reported_at_once() {
+        let config: PcConfig = PcConfig::parse(r#"{"abilities":{"wisdom":250,"strength":0},"proficiencies":{"stealth":7},"proficiency_bonus":9,"darkvision":5000}"#, true).unwrap();
+        match PC::from_config(Rc::new(FixedRoller::new(&[])), &config) {
+            Err(PcError::OutOfRange(issues)) => {
+                let fields: Vec<&str> = issues.iter().map(|issue| issue.field.as_str()).collect();
+                assert_eq!(fields, vec!["abilities.strength", "abilities.wisdom", "darkvision", "proficiencies.stealth", "proficiency_bonus"]);
+            },
+            Err(error) => panic!("unexpected error: {}", error),
+            Ok(_) => panic!("config should not validate"),
+        }
+    }
+
+    #[test]
+    fn missing_config_sections_default_to_nothing() {
+        let config: PcConfig = PcConfig::parse(r#"{"abilities":{"wisdon":14}}"#, false).unwrap();
+        let misspelt: PC = PC::from_config(Rc::new(FixedRoller::new(&[])), &config).unwrap();
+        assert_eq!(misspelt.ability_modifier(Ability::Wisdom), 0);
+        assert_eq!(misspelt.proficiency_modifier(Proficiency::Stealth), 0);
+        assert_eq!(misspelt.proficiency_modifier(Proficiency::Perception), 0);
+        assert_eq!(pc("{}", &[]).passive_perception(), 10);
+    }
+
+    #[test]
+    fn jack_of_all_trades_adds_half_proficiency_where_not_proficient() {
+        let pc: PC = pc(r#"{"proficiencies":{"perception":1,"stealth":2,"insight":0},"proficiency_bonus":3,"jack_of_all_trades":true}"#, &[]);
+        assert_eq!(pc.proficiency_modifier(Proficiency::Insight), 1);
+        assert_eq!(pc.proficiency_modifier(Proficiency::Arcana), 1);
+        assert_eq!(pc.proficiency_modifier(Proficiency::Perception), 3);
+        assert_eq!(pc.proficiency_modifier(Proficiency::Stealth), 6);
+    }
+
+    #[test]
+    fn proficiency_levels_parse_from_names_or_numbers() {
+        let pc: PC = pc(r#"{"proficiencies":{"perception":"proficient","stealth":"expertise","insight":1,"arcana":2,"history":"none"},"proficiency_bonus":3}"#, &[]);
+        assert_eq!(pc.proficiency_modifier(Proficiency::Perception), 3);
+        assert_eq!(pc.proficiency_modifier(Proficiency::Stealth), 6);
+        assert_eq!(pc.proficiency_modifier(Proficiency::Insight), 3);
+        assert_eq!(pc.proficiency_modifier(Proficiency::Arcana), 6);
+        assert_eq!(pc.proficiency_modifier(Proficiency::History), 0);
+        let numbered: PcConfig = PcConfig::parse(r#"{"proficiencies":{"perception":5}}"#, true).unwrap();
+        assert!(matches!(PC::from_config(Rc::new(FixedRoller::new(&[])), &numbered), Err(PcError::OutOfRange(_))));
+        let named: PcConfig = PcConfig::parse(r#"{"proficiencies":{"perception":"master"}}"#, true).unwrap();
+        assert!(matches!(PC::from_config(Rc::new(FixedRoller::new(&[])), &named), Err(PcError::UnknownProficiencyLevel(_))));
+    }
+
+    #[test]
+    fn temporary_modifiers_stack_by_source_and_scope() {
+        let mut pc: PC = pc(r#"{"proficiency_bonus":2}"#, &[10, 10, 10]);
+        pc.add_modifier("cloak", ModifierScope::Skill(Proficiency::Stealth), 2);
+        pc.add_modifier("curse", ModifierScope::AllChecks, -3);
+        pc.add_modifier("curse", ModifierScope::AllChecks, -1);
+        assert_eq!(pc.active_modifiers().len(), 2);
+        assert_eq!(pc.skill_check(Proficiency::Stealth, Advantage::None).unwrap().total(), 11);
+        assert_eq!(pc.skill_check(Proficiency::Arcana, Advantage::None).unwrap().total(), 9);
+        pc.remove_modifier("curse");
+        assert_eq!(pc.skill_check(Proficiency::Arcana, Advantage::None).unwrap().total(), 10);
+    }
+
+    #[test]
+    fn check_results_break_down_the_total() {
+        let mut pc: PC = pc(r#"{"name":"Aria","abilities":{"wisdom":14},"proficiencies":{"perception":"expertise"},"proficiency_bonus":3,"conditions":["poisoned"]}"#, &[15, 7, 12]);
+        pc.add_modifier("cloak", ModifierScope::AllChecks, 1);
+        let result: CheckResult = pc.check(Ability::Wisdom, Proficiency::Perception, Advantage::Advantage).unwrap();
+        assert_eq!((result.name(), result.ability(), result.proficiency()), ("Aria", Ability::Wisdom, Some(Proficiency::Perception)));
+        assert_eq!((result.ability_modifier(), result.proficiency_modifier(), result.modifiers().len()), (2, 6, 1));
+        assert_eq!(result.advantage(), Advantage::Canceled);
+        assert_eq!(result.advantage_sources().reasons(), vec!["the situation", "poisoned"]);
+        assert_eq!((result.faces(), result.total()), (&[15][..], 24));
+        let result: CheckResult = pc.perception_check(Advantage::None, Lighting::Dim, None).unwrap();
+        assert_eq!(result.advantage(), Advantage::Disadvantage);
+        assert_eq!((result.faces(), result.total()), (&[7, 12][..], 16));
+    }
+
+    #[test]
+    fn abilities_and_skills_display_as_they_parse() {
+        for ability in Ability::ALL {
+            assert_eq!(Ability::from_str(&ability.to_string()), Ok(ability));
+            assert_eq!(ability.abbrev().len(), 3);
+        }
+        assert_eq!(Ability::Wisdom.to_string(), "Wisdom");
+        assert_eq!(Proficiency::SleightOfHand.to_string(), "Sleight of Hand");
+        assert_eq!(Proficiency::AnimalHandling.to_string(), "Animal Handling");
+    }
+
+    #[test]
+    fn contests_go_to_the_higher_total_or_the_tie_winner() {
+        let dice: Rc<FixedRoller> = Rc::new(FixedRoller::new(&[10, 10, 5]));
+        let mut sneak: PC = PC::from_config(dice.clone(), &PcConfig::parse(r#"{"name":"A","proficiency_bonus":2}"#, true).unwrap()).unwrap();
+        let mut watch: PC = PC::from_config(dice.clone(), &PcConfig::parse(r#"{"name":"B","proficiency_bonus":2}"#, true).unwrap()).unwrap();
+        let stealth = || ContestSide::skill(Proficiency::Stealth, Advantage::None);
+        let perception = || ContestSide::skill(Proficiency::Perception, Advantage::None);
+        let tied: ContestResult = contest(&mut sneak, stealth(), &mut watch, perception(), Lighting::Light, ContestWinner::default()).unwrap();
+        assert_eq!(tied.winner(), ContestWinner::Target);
+        // The watcher cannot see in the dark, so fails without rolling however high their bonus.
+        watch.add_modifier("lantern", ModifierScope::AllChecks, 20);
+        let dark: ContestResult = contest(&mut sneak, stealth(), &mut watch, perception(), Lighting::Dark, ContestWinner::Target).unwrap();
+        assert_eq!(dark.winner(), ContestWinner::Initiator);
+        assert_eq!(dark.initiator().total(), 5);
+        assert!(dark.target().roll().is_automatic_failure());
+        assert_eq!(dice.remaining(), 0);
+    }
+
+    #[test]
+    fn initiative_breaks_ties_by_dexterity_then_a_roll_off() {
+        let dice: Rc<FixedRoller> = Rc::new(FixedRoller::new(&[5, 10, 10, 9, 8, 3, 3, 4, 12]));
+        let member = |json: &str| PC::from_config(dice.clone(), &PcConfig::parse(json, true).unwrap()).unwrap();
+        let mut party: Party = Party::new(vec![
+            member(r#"{"name":"A","abilities":{"dexterity":14},"alert":true}"#),
+            member(r#"{"name":"B","abilities":{"dexterity":14}}"#),
+            member(r#"{"name":"C","abilities":{"dexterity":12},"feral_instinct":true}"#),
+            member(r#"{"name":"D","abilities":{"dexterity":14}}"#),
+        ]);
+        assert_eq!(party.get("A").unwrap().initiative_modifier(), 7);
+        // A and B both total 12 with the same Dexterity, so roll off;
+        // they tie on 3 and roll again, and B wins 12 to 4.
+        let order: Vec<InitiativeRoll> = party.roll_initiative().unwrap();
+        let totals: Vec<(&str, i32)> = order.iter().map(|entry| (entry.name.as_str(), entry.result.total())).collect();
+        assert_eq!(totals, vec![("B", 12), ("A", 12), ("C", 11), ("D", 10)]);
+        assert_eq!(dice.remaining(), 0);
+    }
+
+    #[test]
+    fn inspiration_grants_advantage_once() {
+        let mut pc: PC = pc("{}", &[4, 15, 9]);
+        assert_eq!(pc.check_with_inspiration(Ability::Wisdom, Proficiency::Insight, Advantage::None).unwrap_err(), InspirationError::NotHeld);
+        pc.grant_inspiration();
+        let result: CheckResult = pc.check_with_inspiration(Ability::Wisdom, Proficiency::Insight, Advantage::None).unwrap();
+        assert!(result.inspiration_spent());
+        assert_eq!(result.total(), 15);
+        assert!(!pc.has_inspiration());
+        pc.grant_inspiration();
+        let result: CheckResult = pc.check_with_inspiration(Ability::Wisdom, Proficiency::Insight, Advantage::Disadvantage).unwrap();
+        assert_eq!((result.advantage(), result.faces()), (Advantage::Canceled, &[9][..]));
+    }
+
+    #[test]
+    fn keen_senses_grants_perception_without_stacking() {
+        let keen: PC = pc(r#"{"proficiency_bonus":2,"traits":["keen_senses","wings"]}"#, &[]);
+        assert_eq!(keen.proficiency_modifier(Proficiency::Perception), 2);
+        assert_eq!(keen.traits()[1].to_string(), "wings");
+        let expert: PC = pc(r#"{"proficiency_bonus":2,"proficiencies":{"perception":2},"traits":["keen_senses"]}"#, &[]);
+        assert_eq!(expert.proficiency_modifier(Proficiency::Perception), 4);
+    }
+
+    #[test]
+    fn custom_skills_roll_by_name() {
+        let mut pc: PC = pc(r#"{"abilities":{"intelligence":16},"proficiency_bonus":3,"custom_skills":{"Lore (Grues)":{"ability":"intelligence","level":"expertise"}}}"#, &[10, 10]);
+        assert_eq!(pc.skill_modifier(&Skill::Custom(String::from("Lore (Grues)"))), 6);
+        let result: CheckResult = pc.skill_check_by_name("Lore (Grues)", Advantage::None).unwrap();
+        assert_eq!((result.total(), result.proficiency()), (19, None));
+        assert_eq!(pc.skill_check_by_name("Lore (Gnomes)", Advantage::None).unwrap_err(), SkillError::Unknown(String::from("Lore (Gnomes)")));
+        assert_eq!(pc.skill_check_by_name("arcana", Advantage::None).unwrap().total(), 13);
+    }
+
+    #[test]
+    fn ability_checks_leave_out_skill_modifiers() {
+        let mut pc: PC = pc(r#"{"abilities":{"strength":16},"proficiency_bonus":2}"#, &[10, 10]);
+        pc.add_modifier("belt", ModifierScope::Ability(Ability::Strength), 1);
+        pc.add_modifier("boots", ModifierScope::Skill(Proficiency::Athletics), 5);
+        let result: CheckResult = pc.ability_check(Ability::Strength, Advantage::None).unwrap();
+        assert_eq!(result.total(), 14);
+        assert!(result.skill().is_none());
+        assert_eq!(pc.check(Ability::Strength, Proficiency::Athletics, Advantage::None).unwrap().total(), 19);
+    }
+
+    #[test]
+    fn perception_in_the_dark_without_darkvision_fails_without_rolling() {
+        let mut pc: PC = pc("{}", &[]);
+        let result: CheckResult = pc.perception_check(Advantage::Advantage, Lighting::Dark, None).unwrap();
+        assert!(result.roll().is_automatic_failure());
+        assert_eq!(result.advantage(), Advantage::Fail);
+    }
+
+    #[test]
+    fn characters_share_one_set_of_dice() {
+        let dice: Rc<Dice> = Rc::new(Dice::scripted(vec![3, 17]));
+        let mut first: PC = PC::from_config(dice.clone(), &PcConfig::parse(r#"{"name":"A"}"#, true).unwrap()).unwrap();
+        let mut second: PC = PC::from_config(dice.clone(), &PcConfig::parse(r#"{"name":"B"}"#, true).unwrap()).unwrap();
+        assert_eq!(first.ability_check(Ability::Wisdom, Advantage::None).unwrap().total(), 3);
+        assert_eq!(second.ability_check(Ability::Wisdom, Advantage::None).unwrap().total(), 17);
+        assert_eq!(dice.history().len(), 2);
+    }
+
+    #[test]
+    fn skills_can_use_another_ability() {
+        let mut pc: PC = pc(r#"{"abilities":{"strength":18,"charisma":8},"proficiencies":{"intimidation":1},"proficiency_bonus":3}"#, &[10, 10]);
+        let usual: CheckResult = pc.skill_check(Proficiency::Intimidation, Advantage::None).unwrap();
+        let strong: CheckResult = pc.skill_check_with(Ability::Strength, Proficiency::Intimidation, Advantage::None).unwrap();
+        assert_eq!(usual.proficiency_modifier(), strong.proficiency_modifier());
+        assert_eq!((usual.ability(), strong.ability()), (Ability::Charisma, Ability::Strength));
+        assert_eq!((usual.total(), strong.total()), (12, 17));
+    }
+
+    #[test]
+    fn help_grants_advantage_on_the_next_check_that_rolls() {
+        let strict: PC = pc(r#"{"name":"S","house_rules":{"strict_help":true}}"#, &[]);
+        assert!(matches!(strict.help(Proficiency::Perception), Err(HelpError::NotProficient {..})));
+        let helper: PC = pc(r#"{"name":"H"}"#, &[]);
+        let mut helped: PC = pc(r#"{"name":"A"}"#, &[12, 12, 5]);
+        helped.accept_help(helper.help(Proficiency::Perception).unwrap()).unwrap();
+        // A check which fails to roll leaves the help waiting.
+        helped.add_modifier("overflow", ModifierScope::AllChecks, i32::MAX - 5);
+        assert_eq!(helped.check(Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap_err(), DiceError::Overflow);
+        assert!(helped.pending_help().is_some());
+        helped.remove_modifier("overflow");
+        let result: CheckResult = helped.perception_check(Advantage::None, Lighting::Dim, None).unwrap();
+        assert_eq!(result.advantage(), Advantage::Canceled);
+        assert!(result.advantage_sources().reasons().contains(&"help from H"));
+        assert!(helped.pending_help().is_none());
+        let result: CheckResult = helped.perception_check(Advantage::None, Lighting::Dim, None).unwrap();
+        assert_eq!(result.advantage(), Advantage::Disadvantage);
+    }
+
+    #[test]
+    fn previews_explain_a_check_without_rolling() {
+        let mut pc: PC = pc(r#"{"abilities":{"wisdom":18},"proficiencies":{"perception":2},"proficiency_bonus":3}"#, &[]);
+        pc.add_modifier("item", ModifierScope::AllChecks, 2);
+        let preview: CheckPreview = pc.preview_check(Ability::Wisdom, Proficiency::Perception, Advantage::None, Lighting::Dim, Some(15));
+        assert_eq!(preview.to_string(), "d20 + 4 (Wisdom) + 6 (Perception) + 2 (item), Disadvantage from dim light without darkvision, 81% to succeed");
+    }
+
+    #[test]
+    fn passives_cover_perception_investigation_and_insight() {
+        let pc: PC = pc(r#"{"abilities":{"wisdom":14,"intelligence":12},"proficiencies":{"insight":"expertise"},"proficiency_bonus":3,"observant":true}"#, &[]);
+        assert_eq!(pc.passives(), vec![(Proficiency::Perception, 17), (Proficiency::Investigation, 16), (Proficiency::Insight, 18)]);
+        assert_eq!(pc.passives_in(Lighting::Dim), vec![(Proficiency::Perception, Some(12)), (Proficiency::Investigation, Some(11)), (Proficiency::Insight, Some(18))]);
+        assert_eq!(pc.passives_in(Lighting::Dark), vec![(Proficiency::Perception, None), (Proficiency::Investigation, None), (Proficiency::Insight, Some(18))]);
+    }
+
+    #[test]
+    fn items_add_bonuses_advantage_and_senses() {
+        let mut pc: PC = pc(r#"{"name":"A","items":[
+            {"name":"Stone of Good Luck","effects":[{"type":"check_bonus","amount":1}]},
+            {"name":"Eyes of the Eagle","effects":[{"type":"skill_advantage","skill":"perception"},{"type":"skill_bonus","skill":"perception","amount":2}]},
+            {"name":"Goggles of Night","effects":[{"type":"darkvision"}]}]}"#, &[5, 14, 8]);
+        let result: CheckResult = pc.perception_check(Advantage::None, Lighting::Light, None).unwrap();
+        assert_eq!(result.total(), 17);
+        assert_eq!(result.modifiers().iter().map(|modifier| modifier.name.as_str()).collect::<Vec<&str>>(), vec!["Stone of Good Luck", "Eyes of the Eagle"]);
+        assert!(result.advantage_sources().reasons().contains(&"Eyes of the Eagle"));
+        // The goggles see in the dark as if it were dim, which the eyes cancel out.
+        let result: CheckResult = pc.perception_check(Advantage::None, Lighting::Dark, None).unwrap();
+        assert_eq!(result.advantage(), Advantage::Canceled);
+        assert_eq!(pc.passive_perception(), 13);
+    }
+
+    #[test]
+    fn context_checks_fail_sight_skills_in_the_dark_only() {
+        let mut pc: PC = pc("{}", &[11]);
+        let dark: CheckContext = CheckContext::new(Lighting::Dark);
+        assert!(pc.check_in(&dark, Ability::Intelligence, Proficiency::Investigation, Advantage::None).unwrap().roll().is_automatic_failure());
+        assert_eq!(pc.check_in(&dark, Ability::Wisdom, Proficiency::Insight, Advantage::None).unwrap().total(), 11);
+    }
+
+    #[test]
+    fn tool_checks_and_languages_ignore_case() {
+        let mut pc: PC = pc(r#"{"abilities":{"dexterity":16},"proficiency_bonus":2,"tools":{"Thieves' Tools":"expertise"},"languages":["Common","Elvish"]}"#, &[10, 10]);
+        assert_eq!(pc.tool_check("thieves' tools", Ability::Dexterity, Advantage::None).unwrap().total(), 17);
+        assert_eq!(pc.tool_proficiency("herbalism kit"), ProficiencyLevel::None);
+        assert_eq!(pc.tool_check("herbalism kit", Ability::Dexterity, Advantage::None).unwrap().total(), 13);
+        assert!(pc.knows_language("elvish"));
+        assert!(!pc.knows_language("Dwarvish"));
+    }
+
+    #[test]
+    fn saved_characters_load_back_the_same() {
+        let dir: PathBuf = std::env::temp_dir().join(format!("rollforgrue-save-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path: PathBuf = dir.join("a.json");
+        let mut pc: PC = pc(r#"{"name":"A","level":5,"abilities":{"wisdom":14},"proficiencies":{"perception":"expertise"},"tools":{"Thieves' Tools":1},"languages":["Common"],"traits":["keen_senses","lucky"],"inspiration":true,"darkvision":30,
+            "items":[{"name":"Goggles","effects":[{"type":"darkvision"}]},{"name":"Eyes","effects":[{"type":"skill_bonus","skill":"animal handling","amount":2}]}],"house_rules":{"max_total":25}}"#, &[10]);
+        pc.add_condition(Condition::Poisoned);
+        pc.set_exhaustion(2);
+        pc.add_modifier("belt", ModifierScope::Ability(Ability::Strength), 1);
+        pc.add_modifier("bless", ModifierScope::AllChecks, 1);
+        pc.check_with_inspiration(Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap();
+        pc.save(&path).unwrap();
+        let back: PC = PC::new(Rc::new(FixedRoller::new(&[])), &mut File::open(&path).unwrap()).unwrap();
+        assert_eq!(back.to_json(), pc.to_json());
+        assert!(!back.has_inspiration() && back.has_condition(Condition::Poisoned));
+        assert_eq!(back.active_modifiers(), pc.active_modifiers());
+        assert_eq!(back.tool_proficiency("thieves' tools"), ProficiencyLevel::Proficient);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reloading_keeps_play_state_over_the_edited_config() {
+        let dice: Rc<dyn Roller> = Rc::new(FixedRoller::new(&[]));
+        let mut pcs: Vec<PC> = parse_party(Rc::clone(&dice), r#"{"party":[{"name":"A","abilities":{"wisdom":12}},{"name":"B"}]}"#).unwrap();
+        let helper: PC = pc(r#"{"name":"H"}"#, &[]);
+        pcs[0].add_condition(Condition::Poisoned);
+        pcs[0].add_modifier("bless", ModifierScope::AllChecks, 1);
+        pcs[0].accept_help(helper.help(Proficiency::Perception).unwrap()).unwrap();
+        assert!(reload_party(Rc::clone(&dice), r#"{"party":[{"name":"A","abilities":{"wisdom":18"#, Path::new(""), &mut pcs).is_err());
+        assert_eq!(pcs[0].ability_modifier(Ability::Wisdom), 1);
+        let names: Vec<String> = reload_party(Rc::clone(&dice), r#"{"party":[{"name":"A","abilities":{"wisdom":18},"modifiers":[{"name":"bless","amount":2}]},{"name":"C"}]}"#, Path::new(""), &mut pcs).unwrap();
+        assert_eq!(names, vec!["A", "C"]);
+        assert_eq!(pcs[0].ability_modifier(Ability::Wisdom), 4);
+        assert!(pcs[0].has_condition(Condition::Poisoned));
+        assert_eq!(pcs[0].active_modifiers()[0].amount, 2);
+        assert!(pcs[0].pending_help().is_some());
+    }
+
+    #[test]
+    fn reloading_clears_conditions_removed_from_the_config() {
+        let dice: Rc<dyn Roller> = Rc::new(FixedRoller::new(&[]));
+        let mut pcs: Vec<PC> = parse_party(Rc::clone(&dice), r#"{"name":"A","conditions":["blinded","deafened"]}"#).unwrap();
+        pcs[0].remove_condition(Condition::Deafened);
+        pcs[0].add_condition(Condition::Unconscious);
+        reload_party(Rc::clone(&dice), r#"{"name":"A","conditions":["deafened","poisoned"]}"#, Path::new(""), &mut pcs).unwrap();
+        assert!(!pcs[0].has_condition(Condition::Blinded));
+        assert!(!pcs[0].has_condition(Condition::Deafened));
+        assert!(pcs[0].has_condition(Condition::Poisoned));
+        assert!(pcs[0].has_condition(Condition::Unconscious));
+        reload_party(Rc::clone(&dice), r#"{"name":"A"}"#, Path::new(""), &mut pcs).unwrap();
+        assert!(!pcs[0].has_condition(Condition::Poisoned));
+        assert!(pcs[0].has_condition(Condition::Unconscious));
+    }
+
+    #[test]
+    fn sunlight_sensitivity_hinders_sight_in_sunlight() {
+        let mut pc: PC = pc(r#"{"traits":["sunlight_sensitivity"],"darkvision":120}"#, &[9; 8]);
+        let sun: CheckContext = CheckContext::new(Lighting::Light).with_sunlight(true);
+        assert_eq!(pc.check_in(&sun, Ability::Wisdom, Proficiency::Perception, Advantage::Advantage).unwrap().advantage(), Advantage::Canceled);
+        assert_eq!(pc.check_in(&sun, Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap().advantage(), Advantage::Disadvantage);
+        assert_eq!(pc.check_in(&sun, Ability::Wisdom, Proficiency::Insight, Advantage::None).unwrap().advantage(), Advantage::None);
+        assert_eq!(pc.perception_check(Advantage::None, Lighting::Light, None).unwrap().advantage(), Advantage::None);
+    }
+
+    #[test]
+    fn perception_can_use_hearing_or_smell() {
+        let mut listener: PC = pc("{}", &[12, 4]);
+        let heard: CheckResult = listener.perception_check_with(Sense::Hearing, Advantage::None, Lighting::Dark, None).unwrap();
+        assert_eq!((heard.advantage(), heard.total(), heard.sense()), (Advantage::None, 12, Some(Sense::Hearing)));
+        assert_eq!(listener.perception_check(Advantage::None, Lighting::Light, None).unwrap().sense(), Some(Sense::Sight));
+        listener.add_condition(Condition::Deafened);
+        assert!(listener.perception_check_with(Sense::Hearing, Advantage::None, Lighting::Light, None).unwrap().roll().is_automatic_failure());
+        let mut wolf: PC = pc(r#"{"traits":["keen_hearing_and_smell"]}"#, &[15, 7]);
+        let smelt: CheckResult = wolf.perception_check_with(Sense::Smell, Advantage::None, Lighting::Dark, None).unwrap();
+        assert_eq!((smelt.advantage(), smelt.total()), (Advantage::Advantage, 15));
+    }
+
+    #[test]
+    fn skill_expert_adds_a_proficiency_and_an_expertise() {
+        let mut pc: PC = pc(r#"{"proficiencies":{"stealth":1},"proficiency_bonus":3}"#, &[]);
+        assert_eq!(pc.apply_feat(Feat::SkillExpert {proficiency: Proficiency::Arcana, expertise: Proficiency::History}), Err(FeatError::NotProficient(Proficiency::History)));
+        assert_eq!(pc.apply_feat(Feat::SkillExpert {proficiency: Proficiency::Stealth, expertise: Proficiency::Stealth}), Err(FeatError::SameSkill(Proficiency::Stealth)));
+        assert_eq!(pc.proficiency_modifier(Proficiency::Arcana), 0);
+        pc.apply_feat(Feat::SkillExpert {proficiency: Proficiency::Arcana, expertise: Proficiency::Stealth}).unwrap();
+        assert_eq!((pc.proficiency_modifier(Proficiency::Arcana), pc.proficiency_modifier(Proficiency::Stealth)), (3, 6));
+        assert!(pc.to_json().contains(r#""stealth": "expertise""#));
+    }
+
+    #[test]
+    fn death_saves_count_to_three_with_crits_and_fumbles() {
+        let mut pc: PC = pc(r#"{"abilities":{"constitution":20}}"#, &[12, 3, 15, 10, 1, 9, 20, 5, 1]);
+        let saves: Vec<DeathSaveResult> = (0..4).map(|_| pc.death_save(Advantage::None).unwrap()).collect();
+        assert_eq!(saves[1].outcome(), DeathSaveOutcome::Failure);
+        assert_eq!(saves[3].state(), DeathSaves {successes: 3, failures: 1});
+        assert!(pc.death_save_state().is_stable());
+        pc.reset_death_saves();
+        let fumble: DeathSaveResult = pc.death_save(Advantage::None).unwrap();
+        assert_eq!((fumble.outcome(), fumble.state().failures), (DeathSaveOutcome::DoubleFailure, 2));
+        assert_eq!(pc.death_save(Advantage::Advantage).unwrap().outcome(), DeathSaveOutcome::Revived);
+        assert_eq!(pc.death_save_state(), DeathSaves::default());
+        assert_eq!(pc.death_save(Advantage::None).unwrap().outcome(), DeathSaveOutcome::Failure);
+        let last: DeathSaveResult = pc.death_save(Advantage::None).unwrap();
+        assert_eq!(last.outcome(), DeathSaveOutcome::DoubleFailure);
+        assert!(last.state().is_dead());
+    }
+
+    #[test]
+    fn pending_bonus_dice_join_checks_until_used_or_removed() {
+        let mut pc: PC = pc("{}", &[10, 3, 2, 10, 4, 10, 1, 10]);
+        pc.set_pending_bonus_die("Guidance", DieSize::D4, false);
+        pc.set_pending_bonus_die("Bless", DieSize::D4, true);
+        let result: CheckResult = pc.check(Ability::Wisdom, Proficiency::Insight, Advantage::None).unwrap();
+        assert_eq!(result.total(), 15);
+        assert_eq!(result.roll().bonuses().iter().map(|bonus| bonus.label.as_str()).collect::<Vec<&str>>(), vec!["Guidance", "Bless"]);
+        assert_eq!(pc.pending_bonus_dice().len(), 1);
+        let result: CheckResult = pc.check(Ability::Wisdom, Proficiency::Insight, Advantage::None).unwrap();
+        assert_eq!((result.total(), result.roll().bonuses()[0].label.as_str()), (14, "Bless"));
+        assert_eq!(pc.check(Ability::Wisdom, Proficiency::Insight, Advantage::None).unwrap().total(), 11);
+        pc.remove_bonus_die("Bless");
+        assert_eq!(pc.check(Ability::Wisdom, Proficiency::Insight, Advantage::None).unwrap().total(), 10);
+    }
+
+    #[test]
+    fn bardic_inspiration_is_added_after_seeing_the_roll() {
+        let mut pc: PC = pc(r#"{"abilities":{"charisma":14}}"#, &[8, 5, 9, 6]);
+        {
+            let mut pending: PendingRoll = pc.pending_check(Ability::Charisma, Proficiency::Persuasion, Advantage::None);
+            assert_eq!(pending.spend_inspiration(), Err(dice::InspirationDieError::NotHeld));
+            assert_eq!(pending.accept().total(), 10);
+        }
+        pc.receive_inspiration_die(DieSize::D8);
+        assert_eq!(pc.pending_check(Ability::Charisma, Proficiency::Persuasion, Advantage::None).accept().total(), 7);
+        assert_eq!(pc.inspiration_die(), Some(DieSize::D8));
+        let mut pending: PendingRoll = pc.pending_check(Ability::Charisma, Proficiency::Persuasion, Advantage::None);
+        pending.spend_inspiration().unwrap();
+        let result: RollResult = pending.accept();
+        assert_eq!(result.total(), 9 + 2 + 6);
+        assert_eq!(result.bonuses()[0].label, dice::INSPIRATION_DIE_LABEL);
+        assert_eq!(pc.inspiration_die(), None);
+    }
+
+    #[test]
+    fn ability_modifiers_follow_the_srd_table() {
+        let table: [i32; 30] = [-5, -4, -4, -3, -3, -2, -2, -1, -1, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10];
+        for (score, modifier) in (1..=30).zip(table) {
+            let pc: PC = pc(&format!(r#"{{"abilities":{{"strength":{}}}}}"#, score), &[]);
+            assert_eq!(pc.ability_modifier(Ability::Strength), modifier, "score {}", score);
+        }
+    }
+
+    #[test]
+    fn identifiers_find_characters_and_survive_saving() {
+        let dice: Rc<dyn Roller> = Rc::new(FixedRoller::new(&[15]));
+        let pcs: Vec<PC> = parse_party(Rc::clone(&dice), r#"{"party":[{"name":"A"},{"name":"B","abilities":{"wisdom":18}},{"name":"C"}]}"#).unwrap();
+        let first: PcId = pcs[0].id();
+        let second: PcId = pcs[1].id();
+        let mut party: Party = Party::from(pcs);
+        assert!(party.remove(first).is_some());
+        assert_eq!(party.len(), 2);
+        let found: &mut PC = party.get_by_id_mut(second).unwrap();
+        assert_eq!(found.name(), "B");
+        assert_eq!(found.check(Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap().roll().rolled_total(), Some(19));
+        let saved: PcConfig = serde_json::from_str(&party.get_by_id(second).unwrap().to_json()).unwrap();
+        assert_eq!(PC::from_config(Rc::clone(&dice), &saved).unwrap().id(), second);
+        let given: PC = PC::from_config(Rc::clone(&dice), &PcConfig::parse(r#"{"id":1000}"#, true).unwrap()).unwrap();
+        let next: PC = PC::from_config(Rc::clone(&dice), &PcConfig::default()).unwrap();
+        assert_eq!(given.id().value(), 1000);
+        assert!(next.id().value() > 1000);
+        assert!(parse_party(dice, r#"{"party":[{"name":"X","id":5},{"name":"Y","id":5}]}"#).is_err());
+    }
+
+    #[test]
+    fn hit_points_track_bloodied_and_unconscious() {
+        let mut fighter: PC = pc(r#"{"max_hp":20}"#, &[15]);
+        assert_eq!(fighter.current_hp(), Some(20));
+        fighter.take_damage(11);
+        assert!(fighter.is_bloodied());
+        assert_eq!(fighter.check(Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap().advantage(), Advantage::None);
+        fighter.take_damage(100);
+        assert_eq!(fighter.current_hp(), Some(0));
+        assert!(fighter.has_condition(Condition::Unconscious));
+        assert_eq!(fighter.check(Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap().roll().rolled_total(), None);
+        fighter.heal(50);
+        assert_eq!(fighter.current_hp(), Some(20));
+        assert!(!fighter.has_condition(Condition::Unconscious));
+        let mut house: PC = pc(r#"{"max_hp":20,"current_hp":9,"house_rules":{"bloodied_disadvantage":true}}"#, &[15, 15]);
+        assert_eq!(house.check(Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap().advantage(), Advantage::Disadvantage);
+        house.heal(1);
+        assert!(!house.is_bloodied());
+        let overfull: PcConfig = PcConfig::parse(r#"{"max_hp":5,"current_hp":9}"#, true).unwrap();
+        assert!(PC::from_config(Rc::new(FixedRoller::new(&[])), &overfull).is_err());
+        let down: PC = pc(r#"{"max_hp":5,"current_hp":0}"#, &[]);
+        assert!(down.has_condition(Condition::Unconscious));
+        assert_eq!(down.to_config().current_hp, Some(0));
+    }
+
+    #[test]
+    fn spell_effects_apply_until_they_lapse_or_are_removed() {
+        let mut caster: PC = pc("{}", &[5, 15, 5, 15, 10, 10]);
+        caster.add_effect(ActiveEffect::EnhanceAbility(Ability::Wisdom), Some(2));
+        assert_eq!(caster.check(Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap().advantage(), Advantage::Advantage);
+        assert_eq!(caster.check(Ability::Dexterity, Proficiency::Stealth, Advantage::None).unwrap().advantage(), Advantage::None);
+        caster.add_effect(ActiveEffect::PassWithoutTrace, None);
+        let hidden: CheckResult = caster.check(Ability::Dexterity, Proficiency::Stealth, Advantage::None).unwrap();
+        assert_eq!(hidden.roll().rolled_total(), Some(25));
+        assert_eq!(hidden.modifiers()[0].name, "Pass Without Trace");
+        assert!(caster.tick_round().is_empty());
+        assert_eq!(caster.tick_round(), vec![ActiveEffect::EnhanceAbility(Ability::Wisdom)]);
+        assert_eq!(caster.check(Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap().advantage(), Advantage::None);
+        assert_eq!(caster.active_effects().len(), 1);
+        caster.remove_effect(ActiveEffect::PassWithoutTrace);
+        assert!(caster.active_effects().is_empty());
+    }
+
+    #[test]
+    fn rests_end_effects_and_restore_what_they_should() {
+        let json: &str = r#"{"name":"Bat","max_hp":30,"current_hp":1,"exhaustion":3}"#;
+        let mut bat: PC = pc(json, &[]);
+        bat.add_effect(ActiveEffect::EnhanceAbility(Ability::Wisdom), Some(600));
+        bat.add_effect(ActiveEffect::PassWithoutTrace, None);
+        bat.receive_inspiration_die(DieSize::D8);
+        bat.set_pending_bonus_die("Guidance", DieSize::D4, false);
+        bat.set_pending_bonus_die("Bless", DieSize::D4, true);
+        assert_eq!(bat.short_rest(), vec![
+            RestChange::EffectEnded(ActiveEffect::EnhanceAbility(Ability::Wisdom)),
+            RestChange::BonusDieLapsed(String::from("Guidance")),
+            RestChange::InspirationDieLapsed(DieSize::D8),
+        ]);
+        assert_eq!((bat.current_hp(), bat.exhaustion()), (Some(1), 3));
+        assert_eq!(bat.long_rest(), vec![
+            RestChange::EffectEnded(ActiveEffect::PassWithoutTrace),
+            RestChange::ExhaustionReduced(2),
+            RestChange::HitPointsRestored(29),
+        ]);
+        assert!(bat.active_effects().is_empty());
+        assert_eq!(bat.pending_bonus_dice().len(), 1);
+        let mut party: Party = Party::from(vec![pc(json, &[])]);
+        party.members_mut()[0].take_damage(5);
+        let changes: Vec<(String, Vec<RestChange>)> = party.long_rest();
+        assert_eq!(changes[0].0, "Bat");
+        assert!(changes[0].1.is_empty());
+    }
+
+    #[test]
+    fn missing_ability_scores_default_to_10() {
+        let mut sparse: PC = pc(r#"{"abilities":{"wisdom":16}}"#, &[12]);
+        assert_eq!(sparse.ability_score(Ability::Intelligence), DEFAULT_ABILITY_SCORE);
+        assert_eq!(sparse.ability_modifier(Ability::Intelligence), 0);
+        assert_eq!(sparse.check(Ability::Intelligence, Proficiency::Investigation, Advantage::None).unwrap().roll().rolled_total(), Some(12));
+    }
+
+    #[test]
+    fn skills_left_out_of_the_config_are_not_proficient() {
+        let mut bard: PC = pc(r#"{"proficiencies":{},"level":5,"jack_of_all_trades":true}"#, &[12, 12, 12]);
+        for (ability, skill) in [(Ability::Wisdom, Proficiency::Perception), (Ability::Intelligence, Proficiency::Investigation), (Ability::Wisdom, Proficiency::Insight)] {
+            assert_eq!(bard.check(ability, skill, Advantage::None).unwrap().roll().rolled_total(), Some(13));
+            let breakdown: ProficiencyModifier = bard.proficiency_breakdown(skill);
+            assert_eq!(breakdown.level, ProficiencyLevel::None);
+            assert!(breakdown.jack_of_all_trades);
+            assert_eq!(breakdown.total(), 1);
+        }
+    }
+
+    #[test]
+    fn every_check_path_resolves_advantage_the_same_way() {
+        let helper: PC = pc(r#"{"name":"H"}"#, &[]);
+        let mut helped: PC = pc(r#"{"name":"A","abilities":{"wisdom":14},"proficiencies":{"perception":1}}"#, &[5, 15]);
+        helped.accept_help(helper.help(Proficiency::Perception).unwrap()).unwrap();
+        let preview: CheckPreview = helped.preview_check(Ability::Wisdom, Proficiency::Perception, Advantage::None, Lighting::Light, Some(15));
+        assert_eq!(preview.advantage, Advantage::Advantage);
+        assert_eq!(preview.success_probability, Some(helped.check_probability(Ability::Wisdom, Proficiency::Perception, Advantage::None, 15)));
+        assert_eq!(helped.passive_perception_in(Advantage::None, Lighting::Light, None), Some(helped.passive_perception() + 5));
+        // A pending check takes the helped advantage, and uses up the help.
+        let natural: u16 = helped.pending_check(Ability::Wisdom, Proficiency::Perception, Advantage::None).result().natural();
+        assert_eq!(natural, 15);
+        assert!(helped.pending_help().is_none());
+    }
+
+    #[test]
+    fn stat_blocks_agree_with_previewed_checks() {
+        let mut sneak: PC = pc(r#"{"name":"S","level":5,"abilities":{"wisdom":15,"dexterity":18},"proficiencies":{"stealth":"expertise","perception":1},"jack_of_all_trades":true,"conditions":["poisoned","blinded"],"max_hp":12}"#, &[]);
+        sneak.add_effect(ActiveEffect::PassWithoutTrace, None);
+        let block: StatBlock = sneak.stat_block();
+        for line in &block.skills {
+            let preview: CheckPreview = sneak.preview_check(line.ability, line.proficiency, Advantage::None, Lighting::Light, None);
+            assert_eq!(preview.total_modifier, line.modifier, "{:?}", line.proficiency);
+        }
+        let stealth: &SkillLine = block.skills.iter().find(|line| line.proficiency == Proficiency::Stealth).unwrap();
+        assert_eq!(stealth.modifier, 4 + 6 + 10);
+        assert_eq!(block.abilities[4].modifier, 2);
+        assert_eq!(block.conditions, vec![Condition::Blinded, Condition::Poisoned]);
+        assert_eq!(block.hit_points, Some((12, 12)));
+    }
+
+    #[test]
+    fn configured_skill_advantages_join_the_situation() {
+        let mut scout: PC = pc(r#"{"skill_advantages":{"perception":"advantage","insight":"disadvantage"}}"#, &[12, 12, 12]);
+        let result: CheckResult = scout.perception_check(Advantage::None, Lighting::Dim, None).unwrap();
+        assert_eq!(result.advantage(), Advantage::Canceled);
+        assert!(result.advantage_sources().reasons().contains(&"character config"));
+        let result: CheckResult = scout.check(Ability::Wisdom, Proficiency::Insight, Advantage::None).unwrap();
+        assert_eq!(result.advantage(), Advantage::Disadvantage);
+        assert_eq!(scout.to_config().skill_advantages.get("perception").map(String::as_str), Some("advantage"));
+        let config: PcConfig = PcConfig::parse(r#"{"skill_advantages":{"perception":"lots"}}"#, true).unwrap();
+        assert!(PC::from_config(Rc::new(FixedRoller::new(&[])), &config).is_err());
+    }
+
+    #[test]
+    fn obscurement_stacks_with_lighting() {
+        for config in [r#"{"senses":{"darkvision":60}}"#, "{}"] {
+            let mut watcher: PC = pc(config, &[12; 18]);
+            let darkvision: bool = watcher.darkvision.is_some();
+            for lighting in [Lighting::Light, Lighting::Dim, Lighting::Dark] {
+                for obscurement in [Obscurement::Clear, Obscurement::LightlyObscured, Obscurement::HeavilyObscured] {
+                    let context: CheckContext = CheckContext::new(lighting).with_obscurement(obscurement);
+                    let result: CheckResult = watcher.check_in(&context, Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap();
+                    let blind: bool = obscurement == Obscurement::HeavilyObscured || (lighting == Lighting::Dark && !darkvision);
+                    let hindered: bool = obscurement == Obscurement::LightlyObscured || lighting == Lighting::Dark || (lighting == Lighting::Dim && !darkvision);
+                    let expected: Advantage = match (blind, hindered) {
+                        (true, _) => Advantage::Fail,
+                        (false, true) => Advantage::Disadvantage,
+                        (false, false) => Advantage::None,
+                    };
+                    assert_eq!(result.advantage(), expected, "{} {:?} {}", darkvision, lighting, obscurement);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn weather_hinders_the_senses_it_affects() {
+        let mut lookout: PC = pc("{}", &[12; 6]);
+        let context: CheckContext = CheckContext::new(Lighting::Light).with_sense(Sense::Hearing).with_weather(Weather::Rain);
+        let result: CheckResult = lookout.check_in(&context, Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap();
+        assert_eq!(result.advantage(), Advantage::Disadvantage);
+        assert_eq!(result.advantage_sources().reasons(), vec!["rain"]);
+        let context: CheckContext = CheckContext::new(Lighting::Light).with_weather(Weather::Fog);
+        assert_eq!(lookout.check_in(&context, Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap().advantage(), Advantage::Fail);
+        let context: CheckContext = CheckContext::new(Lighting::Light);
+        assert!(lookout.check_in(&context, Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap().advantage_sources().sources().is_empty());
+        let context: CheckContext = CheckContext::new(Lighting::Light).with_distance(100).with_weather(Weather::Snowstorm);
+        assert_eq!(lookout.check_in(&context, Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap().advantage(), Advantage::Fail);
+        let context: CheckContext = CheckContext::new(Lighting::Light).with_sense(Sense::Smell).with_weather(Weather::Rain);
+        assert_eq!(lookout.check_in(&context, Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap().advantage(), Advantage::None);
+    }
+
+    #[test]
+    fn noise_drowns_out_hearing() {
+        let mut hound: PC = pc(r#"{"traits":["keen_hearing_and_smell"]}"#, &[12; 4]);
+        let context: CheckContext = CheckContext::new(Lighting::Light).with_sense(Sense::Hearing).with_noise(NoiseLevel::Loud);
+        assert_eq!(hound.check_in(&context, Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap().advantage(), Advantage::Canceled);
+        let context: CheckContext = CheckContext::new(Lighting::Light).with_noise(NoiseLevel::Deafening);
+        assert_eq!(hound.check_in(&context, Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap().advantage(), Advantage::None);
+        let context: CheckContext = context.with_sense(Sense::Hearing);
+        assert_eq!(hound.check_in(&context, Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap().advantage(), Advantage::Fail);
+    }
+
+    #[test]
+    fn distance_bands_perception_and_limits_darkvision() {
+        let mut owl: PC = pc(r#"{"senses":{"darkvision":60}}"#, &[12; 12]);
+        let mut check = |context: &CheckContext| owl.check_in(context, Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap().advantage();
+        assert_eq!(check(&CheckContext::new(Lighting::Dark).with_distance(30)), Advantage::Disadvantage);
+        assert_eq!(check(&CheckContext::new(Lighting::Dark).with_distance(90)), Advantage::Fail);
+        // An unknown distance is neither banded nor beyond darkvision.
+        assert_eq!(check(&CheckContext::new(Lighting::Dark)), Advantage::Disadvantage);
+        assert_eq!(check(&CheckContext::new(Lighting::Light)), Advantage::None);
+        let context: CheckContext = CheckContext::new(Lighting::Light).with_distance(90);
+        assert_eq!(check(&context), Advantage::Disadvantage);
+        let context: CheckContext = context.with_distance(400);
+        assert_eq!(check(&context), Advantage::Fail);
+        let context: CheckContext = context.with_rules(EnvironmentRules {clear_range: 500, horizon: 1000});
+        assert_eq!(check(&context), Advantage::None);
+        assert_eq!(owl.passive_perception_in(Advantage::None, Lighting::Dark, Some(90)), None);
+        assert_eq!(owl.passive_perception_in(Advantage::None, Lighting::Dark, Some(30)), Some(owl.passive_perception() - 5));
+    }
+
+    #[test]
+    fn checks_are_made_in_the_light_the_sources_give() {
+        let mut explorer: PC = pc("{}", &[12, 12]);
+        let context: CheckContext = CheckContext::new(Lighting::Dark).with_light_source(LightSource::torch(), 25);
+        assert_eq!(explorer.check_in(&context, Ability::Wisdom, Proficiency::Perception, Advantage::None).unwrap().advantage(), Advantage::Disadvantage);
+    }
+
+    #[test]
+    fn only_truesight_and_devils_sight_see_in_magical_darkness() {
+        let mut elf: PC = pc(r#"{"senses":{"darkvision":60}}"#, &[]);
+        assert_eq!(elf.perception_check(Advantage::None, Lighting::MagicalDark, None).unwrap().advantage(), Advantage::Fail);
+        let mut seer: PC = pc(r#"{"senses":{"truesight":120}}"#, &[12]);
+        assert_eq!(seer.perception_check(Advantage::None, Lighting::MagicalDark, None).unwrap().advantage(), Advantage::None);
+        assert_eq!(seer.perception_check(Advantage::None, Lighting::MagicalDark, Some(200)).unwrap().advantage(), Advantage::Fail);
+        assert_eq!(seer.to_config().senses.truesight, Some(120));
+        let mut warlock: PC = pc(r#"{"traits":["devils_sight"]}"#, &[12]);
+        assert_eq!(warlock.perception_check(Advantage::None, Lighting::Dark, None).unwrap().advantage(), Advantage::None);
+    }
+
+    #[test]
+    fn initiative_ties_fall_back_to_party_order_when_roll_offs_match() {
+        let dice: Rc<Dice> = Rc::new(Dice::seeded(1));
+        dice.set_mode(RollMode::Average {round_up: false});
+        let member = |json: &str| PC::from_config(dice.clone(), &PcConfig::parse(json, true).unwrap()).unwrap();
+        let mut party: Party = Party::new(vec![
+            member(r#"{"name":"A","abilities":{"dexterity":14}}"#),
+            member(r#"{"name":"B","abilities":{"dexterity":14}}"#),
+        ]);
+        let order: Vec<InitiativeRoll> = party.roll_initiative().unwrap();
+        let names: Vec<&str> = order.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, vec!["A", "B"]);
+        assert_eq!(order[0].tiebreak, order[1].tiebreak);
+    }
+
+    #[test]
+    fn group_checks_roll_as_one_batch_like_single_checks() {
+        let party_of = |dice: &Rc<Dice>| -> Party {
+            let configs: [&str; 3] = [r#"{"name":"a","lucky":true}"#, r#"{"name":"b","abilities":{"wisdom":16}}"#, r#"{"name":"c","proficiencies":{"perception":1}}"#];
+            Party::new(configs.iter().map(|json| PC::from_config(dice.clone(), &PcConfig::parse(json, true).unwrap()).unwrap()).collect())
+        };
+        let batched: Rc<Dice> = Rc::new(Dice::seeded(3));
+        let single: Rc<Dice> = Rc::new(Dice::seeded(3));
+        let mut group: Party = party_of(&batched);
+        let mut each: Party = party_of(&single);
+        let advantage: HashMap<String, Advantage> = HashMap::from([(String::from("b"), Advantage::Advantage)]);
+        for _ in 0..20 {
+            let result: GroupCheckResult = group.group_check(Ability::Wisdom, Proficiency::Perception, 12, &advantage).unwrap();
+            for (checked, member) in result.members().iter().zip(each.members_mut()) {
+                let own: Advantage = advantage.get(&member.name).copied().unwrap_or(Advantage::None);
+                assert_eq!(checked.result.roll(), member.check(Ability::Wisdom, Proficiency::Perception, own).unwrap().roll());
+            }
+        }
+        // A member whose roll cannot be made stops the batch before any dice roll.
+        let roller: Rc<FixedRoller> = Rc::new(FixedRoller::new(&[10]));
+        let mut overflowing: PC = PC::from_config(roller.clone(), &PcConfig::parse(r#"{"name":"x"}"#, true).unwrap()).unwrap();
+        overflowing.add_modifier("overflow", ModifierScope::AllChecks, i32::MAX);
+        let steady: PC = PC::from_config(roller.clone(), &PcConfig::parse(r#"{"name":"y"}"#, true).unwrap()).unwrap();
+        let mut party: Party = Party::new(vec![steady, overflowing]);
+        assert_eq!(party.group_check(Ability::Wisdom, Proficiency::Perception, 10, &HashMap::new()).unwrap_err(), DiceError::Overflow);
+        assert_eq!(roller.remaining(), 1);
+    }
+
+    #[test]
+    fn parties_reload_their_members_in_place() {
+        let dice: Rc<dyn Roller> = Rc::new(FixedRoller::new(&[]));
+        let mut party: Party = Party::new(parse_party(Rc::clone(&dice), r#"{"party":[{"name":"A"},{"name":"B"}]}"#).unwrap());
+        let id: PcId = party.get("A").unwrap().id();
+        party.get_mut("A").unwrap().add_condition(Condition::Poisoned);
+        let names: Vec<String> = party.reload(Rc::clone(&dice), r#"{"party":[{"name":"A","abilities":{"wisdom":16}}]}"#, Path::new("")).unwrap();
+        assert_eq!(names, vec!["A"]);
+        assert_eq!(party.len(), 1);
+        let reloaded: &PC = party.get_by_id(id).unwrap();
+        assert_eq!(reloaded.ability_modifier(Ability::Wisdom), 3);
+        assert!(reloaded.has_condition(Condition::Poisoned));
+    }
+
+    #[test]
+    fn pending_checks_add_and_use_up_bonus_dice() {
+        let mut cleric: PC = pc("{}", &[10, 3, 2, 12, 4]);
+        cleric.set_pending_bonus_die("guidance", DieSize::D4, false);
+        cleric.set_pending_bonus_die("bless", DieSize::D4, true);
+        let result: RollResult = cleric.pending_check(Ability::Wisdom, Proficiency::Insight, Advantage::None).accept();
+        assert_eq!(result.total(), 10 + 3 + 2);
+        // Guidance is spent, but bless lasts.
+        let result: RollResult = cleric.pending_check(Ability::Wisdom, Proficiency::Insight, Advantage::None).accept();
+        assert_eq!(result.total(), 12 + 4);
+    }
+
+    #[test]
+    fn previews_leave_the_seeded_sequence_alone() {
+        let previewed: Rc<Dice> = Rc::new(Dice::seeded(11));
+        let untouched: Dice = Dice::seeded(11);
+        let mut sage: PC = PC::from_config(previewed.clone(), &PcConfig::parse(r#"{"abilities":{"wisdom":14}}"#, true).unwrap()).unwrap();
+        sage.set_pending_bonus_die("guidance", DieSize::D4, false);
+        for lighting in [Lighting::Light, Lighting::Dim, Lighting::Dark] {
+            sage.preview_check(Ability::Wisdom, Proficiency::Perception, Advantage::Advantage, lighting, Some(12));
+        }
+        assert_eq!(previewed.d(DieSize::D20, 0, Advantage::None), untouched.d(DieSize::D20, 0, Advantage::None));
+    }
+}
@@ -0,0 +1,274 @@
+//! Importing characters from other tools' formats.
+//!
+//! Each importer maps what this crate models into a `PcConfig`, and
+//! ignores the rest. Anything it cannot map with confidence is noted
+//! as a warning rather than refused.
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::Read;
+use std::str::FromStr;
+
+use super::{Ability, PcConfig, Proficiency, ProficiencyLevelConfig, Trait};
+
+/// Reasons a character cannot be imported.
+#[derive(Debug)]
+pub enum ImportError {
+    /// The export could not be read.
+    Io(std::io::Error),
+    /// The export is not JSON of the expected shape.
+    Parse(serde_json::Error),
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ImportError::Io(error) => write!(f, "cannot read character export: {}", error),
+            ImportError::Parse(error) => write!(f, "cannot parse character export: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImportError::Io(error) => Some(error),
+            ImportError::Parse(error) => Some(error),
+        }
+    }
+}
+
+impl From<std::io::Error> for ImportError {
+    fn from(error: std::io::Error) -> ImportError {
+        ImportError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for ImportError {
+    fn from(error: serde_json::Error) -> ImportError {
+        ImportError::Parse(error)
+    }
+}
+
+/// Something in an export which was not carried over as written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImportWarning {
+    /// An ability score was overridden by hand; holds the ability and
+    /// the score it was overridden to, which is the one imported.
+    OverriddenScore(Ability, u8),
+    /// A bonus this crate does not model, such as a flat bonus to one
+    /// skill; holds what it applies to, as the export names it.
+    UnmodeledBonus(String),
+    /// A proficiency or value the player entered by hand; holds its name.
+    CustomEntry(String),
+}
+
+impl std::fmt::Display for ImportWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ImportWarning::OverriddenScore(ability, score) => write!(f, "{} is overridden to {}", ability, score),
+            ImportWarning::UnmodeledBonus(target) => write!(f, "ignoring bonus to \"{}\"", target),
+            ImportWarning::CustomEntry(name) => write!(f, "ignoring custom entry \"{}\"", name),
+        }
+    }
+}
+
+/// A character imported from another tool.
+#[derive(Clone, Debug)]
+pub struct Imported {
+    /// The character's attributes, ready for `PC::from_config`.
+    pub config: PcConfig,
+    /// What was not carried over as written, in the order found.
+    pub warnings: Vec<ImportWarning>,
+}
+
+/// A D&D Beyond character export, which may be wrapped in `"data"`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DndBeyondExport {
+    Wrapped {
+        data: DndBeyondCharacter,
+    },
+    Bare(DndBeyondCharacter),
+}
+
+/// The parts of a D&D Beyond character this crate models.
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct DndBeyondCharacter {
+    name: Option<String>,
+    stats: Vec<DndBeyondStat>,
+    bonus_stats: Vec<DndBeyondStat>,
+    override_stats: Vec<DndBeyondStat>,
+    race: Option<DndBeyondRace>,
+    classes: Vec<DndBeyondClass>,
+    /// Modifiers by where they come from: "race", "class", "feat", and so on.
+    modifiers: HashMap<String, Vec<DndBeyondModifier>>,
+    custom_proficiencies: Vec<DndBeyondNamed>,
+    character_values: Vec<serde_json::Value>,
+}
+
+/// An ability score entry; D&D Beyond numbers the abilities 1 to 6.
+#[derive(Deserialize)]
+struct DndBeyondStat {
+    id: u8,
+    value: Option<u8>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DndBeyondRace {
+    #[serde(default)]
+    racial_traits: Vec<DndBeyondTrait>,
+}
+
+#[derive(Deserialize)]
+struct DndBeyondTrait {
+    definition: DndBeyondNamed,
+}
+
+#[derive(Deserialize)]
+struct DndBeyondClass {
+    level: u8,
+}
+
+#[derive(Deserialize)]
+struct DndBeyondNamed {
+    name: String,
+}
+
+/// A modifier, such as `{"type": "proficiency", "subType": "stealth"}`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DndBeyondModifier {
+    #[serde(rename = "type")]
+    kind: String,
+    sub_type: String,
+    value: Option<i64>,
+    friendly_subtype_name: Option<String>,
+}
+
+/// The ability D&D Beyond numbers with an ID.
+/// * `id` - The ability's ID, from 1 for Strength to 6 for Charisma.
+fn dndbeyond_ability(id: u8) -> Option<Ability> {
+    Ability::ALL.get(usize::from(id).checked_sub(1)?).copied()
+}
+
+/// Whether a proficiency's sub-type names a tool, such as "thieves-tools".
+/// * `sub_type` - The sub-type as D&D Beyond writes it.
+fn is_tool(sub_type: &str) -> bool {
+    ["-tools", "-kit", "-supplies", "-utensils", "-set"].iter().any(|suffix| sub_type.ends_with(suffix))
+}
+
+/// Import a character from a D&D Beyond JSON export.
+///
+/// Ability scores include racial and other bonuses, and a score
+/// overridden by hand is taken as overridden. The level is the sum of
+/// the character's class levels, so the proficiency bonus follows from
+/// it. Skill, saving throw, and tool proficiencies, expertise,
+/// languages, darkvision, and the racial traits this crate knows are
+/// imported; everything else is ignored.
+/// * `reader` - The export to read.
+pub fn dndbeyond(reader: impl Read) -> Result<Imported, ImportError> {
+    let export: DndBeyondExport = serde_json::from_reader(reader)?;
+    let character: DndBeyondCharacter = match export {
+        DndBeyondExport::Wrapped {data} => data,
+        DndBeyondExport::Bare(character) => character,
+    };
+    let mut warnings: Vec<ImportWarning> = Vec::new();
+    let mut config: PcConfig = PcConfig {version: Some(super::CONFIG_VERSION), name: character.name.clone(), ..Default::default()};
+
+    // Start from the base scores, with any flat bonuses the sheet adds.
+    let mut scores: HashMap<Ability, i64> = HashMap::new();
+    for stat in character.stats.iter().chain(&character.bonus_stats) {
+        if let (Some(ability), Some(value)) = (dndbeyond_ability(stat.id), stat.value) {
+            *scores.entry(ability).or_default() += i64::from(value);
+        }
+    }
+
+    // Sort the modifiers by source so the import is the same every time.
+    let mut sources: Vec<&String> = character.modifiers.keys().collect();
+    sources.sort_unstable();
+    let mut darkvision: Option<u16> = None;
+    for source in sources {
+        for modifier in &character.modifiers[source] {
+            let sub_type: &str = modifier.sub_type.as_str();
+            let named: String = sub_type.replace('-', " ");
+            match modifier.kind.as_str() {
+                "bonus" => {
+                    let score: Option<Ability> = sub_type.strip_suffix("-score").and_then(|name| Ability::from_str(name).ok());
+                    match score {
+                        Some(ability) => *scores.entry(ability).or_default() += modifier.value.unwrap_or_default(),
+                        None => warnings.push(ImportWarning::UnmodeledBonus(String::from(sub_type))),
+                    }
+                },
+                "proficiency" | "expertise" => {
+                    let level: &str = if modifier.kind == "expertise" { "expertise" } else { "proficient" };
+                    if Proficiency::from_str(&named).is_ok() {
+                        // Expertise is never downgraded by a later proficiency.
+                        let existing: Option<&ProficiencyLevelConfig> = config.proficiencies.get(&named);
+                        if !matches!(existing, Some(ProficiencyLevelConfig::Name(name)) if name == "expertise") {
+                            config.proficiencies.insert(named, ProficiencyLevelConfig::Name(String::from(level)));
+                        }
+                    } else if let Some(ability) = sub_type.strip_suffix("-saving-throws").and_then(|name| Ability::from_str(name).ok()) {
+                        config.saving_throws.insert(ability.to_string().to_lowercase(), ProficiencyLevelConfig::Name(String::from(level)));
+                    } else if is_tool(sub_type) {
+                        let tool: String = modifier.friendly_subtype_name.clone().unwrap_or(named);
+                        config.tools.insert(tool, ProficiencyLevelConfig::Name(String::from(level)));
+                    }
+                },
+                "half-proficiency" if sub_type == "ability-checks" => config.jack_of_all_trades = true,
+                "language" => config.languages.push(modifier.friendly_subtype_name.clone().unwrap_or(named)),
+                "set-base" if sub_type == "darkvision" => {
+                    let feet: u16 = modifier.value.and_then(|value| u16::try_from(value).ok()).unwrap_or(super::DEFAULT_DARKVISION_RANGE);
+                    darkvision = darkvision.max(Some(feet));
+                },
+                _ => (),
+            }
+        }
+    }
+
+    // A score overridden by hand replaces everything else.
+    for stat in &character.override_stats {
+        if let (Some(ability), Some(value)) = (dndbeyond_ability(stat.id), stat.value) {
+            warnings.push(ImportWarning::OverriddenScore(ability, value));
+            scores.insert(ability, i64::from(value));
+        }
+    }
+    for (ability, score) in scores {
+        // Out-of-range scores are kept as close as they fit, for
+        // `PC::from_config` to report.
+        let score: u8 = u8::try_from(score.max(0)).unwrap_or(u8::MAX);
+        config.abilities.insert(ability.to_string().to_lowercase(), score);
+    }
+
+    let level: u32 = character.classes.iter().map(|class| u32::from(class.level)).sum();
+    if level > 0 {
+        config.level = Some(u8::try_from(level).unwrap_or(u8::MAX));
+    }
+
+    // Darkvision a race gives is usually also a modifier, but the trait
+    // alone means the usual range.
+    let racial_traits: Vec<String> = character.race.iter()
+        .flat_map(|race| race.racial_traits.iter())
+        .map(|racial_trait| racial_trait.definition.name.to_lowercase().replace(' ', "_"))
+        .collect();
+    if darkvision.is_none() && racial_traits.iter().any(|name| name == "darkvision") {
+        darkvision = Some(super::DEFAULT_DARKVISION_RANGE);
+    }
+    config.senses.darkvision = darkvision;
+    config.traits = racial_traits.into_iter()
+        .filter(|name| !matches!(Trait::from(name.as_str()), Trait::Unknown(_)))
+        .collect();
+
+    for custom in &character.custom_proficiencies {
+        warnings.push(ImportWarning::CustomEntry(custom.name.clone()));
+    }
+    if !character.character_values.is_empty() {
+        warnings.push(ImportWarning::CustomEntry(String::from("characterValues")));
+    }
+
+    for warning in &warnings {
+        log::warn!("Importing {}: {}", config.name.as_deref().unwrap_or(super::UNNAMED), warning);
+    }
+    Ok(Imported {config, warnings})
+}
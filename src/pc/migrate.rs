@@ -0,0 +1,89 @@
+//! Upgrading configs written for older versions of the format.
+//!
+//! Each migration rewrites a config's JSON from one version to the
+//! next, before it is parsed, so that the rest of the crate only ever
+//! sees the current version.
+use serde_json::{Map, Value};
+
+use super::{PcError, DEFAULT_DARKVISION_RANGE, UNNAMED};
+
+/// The version of the config format this crate writes.
+pub const CONFIG_VERSION: u64 = 2;
+
+/// The key giving a config's version.
+const VERSION_KEY: &str = "version";
+
+/// Upgrade a config document, for one character or a party, to
+/// `CONFIG_VERSION`, logging each migration applied.
+///
+/// A party's version is given beside its `"party"` array, and covers
+/// every character and template in the document.
+/// * `document` - The config's JSON.
+pub(crate) fn migrate(document: &mut Value) -> Result<(), PcError> {
+    let object: &mut Map<String, Value> = match document {
+        Value::Object(object) => object,
+        _ => return Ok(()),
+    };
+    let version: u64 = match object.get(VERSION_KEY) {
+        None => 1,
+        Some(version) => version.as_u64().ok_or_else(|| PcError::Parse(serde::de::Error::custom("\"version\" must be a whole number")))?,
+    };
+    if version > CONFIG_VERSION {
+        return Err(PcError::NewerVersion(version));
+    }
+    if version == CONFIG_VERSION {
+        return Ok(());
+    }
+
+    if object.contains_key("party") {
+        let mut characters: Vec<&mut Map<String, Value>> = Vec::new();
+        for (key, value) in object.iter_mut() {
+            match (key.as_str(), value) {
+                ("party", Value::Array(party)) => characters.extend(party.iter_mut().filter_map(Value::as_object_mut)),
+                ("templates", Value::Object(templates)) => characters.extend(templates.values_mut().filter_map(Value::as_object_mut)),
+                _ => (),
+            }
+        }
+        for character in characters {
+            migrate_character(character, version);
+        }
+    } else {
+        migrate_character(object, version);
+    }
+    object.insert(String::from(VERSION_KEY), Value::from(CONFIG_VERSION));
+    Ok(())
+}
+
+/// Upgrade one character's config from its version to `CONFIG_VERSION`.
+/// * `character` - The character's config.
+/// * `version` - The version it was written for.
+fn migrate_character(character: &mut Map<String, Value>, version: u64) {
+    if version < 2 {
+        darkvision_to_senses(character);
+    }
+}
+
+/// Version 2 moved darkvision under `"senses"`, always as a range:
+/// `"darkvision": true` becomes `"senses": {"darkvision": 60}`.
+/// * `character` - The character's config.
+fn darkvision_to_senses(character: &mut Map<String, Value>) {
+    let feet: Option<u64> = match character.get("darkvision") {
+        Some(Value::Bool(true)) => Some(u64::from(DEFAULT_DARKVISION_RANGE)),
+        Some(Value::Bool(false)) => None,
+        Some(Value::Number(feet)) => match feet.as_u64() {
+            Some(feet) => Some(feet),
+            // Left in place to be reported as not understood.
+            None => return,
+        },
+        _ => return,
+    };
+    let darkvision: Option<Value> = character.remove("darkvision");
+    let name: String = character.get("name").and_then(Value::as_str).unwrap_or(UNNAMED).to_string();
+    log::info!("Migrating {} to config version 2: \"darkvision\": {} is now under \"senses\"", name, darkvision.unwrap_or_default());
+    if let Some(feet) = feet {
+        let senses: &mut Value = character.entry("senses").or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(senses) = senses {
+            senses.entry("darkvision").or_insert(Value::from(feet));
+        }
+    }
+}
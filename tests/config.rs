@@ -0,0 +1,101 @@
+//! Checks that the sample configs in `tests/fixtures` load.
+
+use rollforgrue::dice::{Advantage, Dice, Roller};
+use rollforgrue::pc::{self, Ability, PcConfig, PcError, Proficiency, PC};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+/// The path of a config in `tests/fixtures`.
+/// * `name` - The file name of the config.
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join(name)
+}
+
+/// Load a character from a config in `tests/fixtures`.
+/// * `name` - The file name of the config.
+/// * `faces` - The faces the character's dice roll, in order.
+fn open(name: &str, faces: Vec<u16>) -> PC {
+    let dice: Rc<dyn Roller> = Rc::new(Dice::scripted(faces));
+    PC::open(dice, &fixture(name)).unwrap()
+}
+
+#[test]
+fn the_original_config_format_still_loads() {
+    let mut pc: PC = open("config_original.json", vec![10]);
+    assert_eq!(pc.name(), "config_original");
+    assert_eq!(pc.passive_perception(), 14);
+    assert_eq!(pc.check(Ability::Dexterity, Proficiency::Insight, Advantage::None).unwrap().total(), 11);
+}
+
+#[test]
+fn versioned_configs_load() {
+    for name in ["config_v1.json", "config_v2.json"] {
+        let pc: PC = open(name, Vec::new());
+        assert_eq!(pc.name(), "Brindle", "{}", name);
+        assert_eq!(pc.passive_perception(), 15, "{}", name);
+    }
+}
+
+#[test]
+fn every_fixture_parses_strictly() {
+    for name in ["config_original.json", "config_v1.json", "config_v2.json"] {
+        let text: String = std::fs::read_to_string(fixture(name)).unwrap();
+        let config: PcConfig = PcConfig::parse(&text, true).unwrap();
+        assert!(config.unknown_keys().is_empty(), "{}", name);
+    }
+}
+
+#[test]
+fn misspelt_keys_are_reported() {
+    let text: &str = r#"{"abilities":{"wisdom":14},"proficiencies":{"perception":1},"darkvison":true}"#;
+    assert_eq!(PcConfig::parse(text, false).unwrap().unknown_keys(), vec!["darkvison"]);
+    let error: String = PcConfig::parse(text, true).unwrap_err().to_string();
+    assert!(error.contains("darkvison"), "{}", error);
+}
+
+#[test]
+fn characters_inherit_from_templates_and_files() {
+    let dir: PathBuf = fixture("bases");
+    let text: String = std::fs::read_to_string(dir.join("party.json")).unwrap();
+    let dice: Rc<dyn Roller> = Rc::new(Dice::scripted(Vec::new()));
+    let pcs: Vec<PC> = pc::parse_party_in(dice, &text, &dir).unwrap();
+    assert_eq!(pcs.iter().map(|pc| pc.name()).collect::<Vec<&str>>(), vec!["Lookout One", "Lookout Two"]);
+    assert_eq!([Ability::Wisdom, Ability::Strength, Ability::Dexterity].map(|ability| pcs[0].ability_modifier(ability)), [3, 2, 2]);
+    assert_eq!(pcs[1].ability_modifier(Ability::Dexterity), 0);
+    assert_eq!(pcs[1].passive_perception(), 15);
+    assert!(pcs.iter().all(|pc| pc.id().value() != 77));
+}
+
+#[test]
+fn bases_which_name_each_other_are_refused() {
+    let dir: PathBuf = fixture("bases");
+    let text: String = std::fs::read_to_string(dir.join("cycle_a.json")).unwrap();
+    let dice: Rc<dyn Roller> = Rc::new(Dice::scripted(Vec::new()));
+    let error: PcError = pc::parse_party_in(Rc::clone(&dice), &text, &dir).err().unwrap();
+    assert!(matches!(error, PcError::BaseCycle(ref chain) if chain.len() == 3), "{}", error);
+    let looped: &str = r#"{"templates":{"t":{"base":"t"}},"party":[{"base":"t"}]}"#;
+    assert!(matches!(pc::parse_party_in(dice, looped, &dir), Err(PcError::BaseCycle(_))));
+}
+
+#[test]
+fn version_1_and_version_2_configs_give_the_same_character() {
+    let mut configs: Vec<PcConfig> = ["config_v1.json", "config_v2.json"].map(|name| open(name, Vec::new()).to_config()).into();
+    // Each load is given a fresh identifier.
+    for config in &mut configs {
+        config.id = None;
+    }
+    let [v1, v2]: [serde_json::Value; 2] = [&configs[0], &configs[1]].map(|config| serde_json::to_value(config).unwrap());
+    assert_eq!(v1, v2);
+    assert_eq!(configs[0].senses.darkvision, Some(60));
+    assert_eq!(v1["version"], 2);
+}
+
+#[test]
+fn newer_and_mixed_versions_are_refused() {
+    assert!(matches!(PcConfig::parse(r#"{"version":3}"#, true), Err(PcError::NewerVersion(3))));
+    assert!(PcConfig::parse(r#"{"version":2,"darkvision":true}"#, true).is_err());
+    let dice: Rc<dyn Roller> = Rc::new(Dice::scripted(Vec::new()));
+    let pcs: Vec<PC> = pc::parse_party(dice, r#"{"party":[{"name":"A","darkvision":120},{"name":"B","darkvision":false}]}"#).unwrap();
+    assert_eq!(pcs[0].to_config().senses.darkvision, Some(120));
+    assert_eq!(pcs[1].to_config().senses.darkvision, None);
+}
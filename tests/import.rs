@@ -0,0 +1,49 @@
+//! Checks importing the D&D Beyond export in `tests/fixtures`.
+
+use rollforgrue::dice::{Dice, Roller};
+use rollforgrue::pc::import::{dndbeyond, ImportWarning, Imported};
+use rollforgrue::pc::{Ability, PcConfig, Proficiency, ProficiencyLevel, Trait, PC};
+use std::fs::File;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+/// Import the D&D Beyond export in `tests/fixtures`.
+fn import() -> Imported {
+    let path: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join("dndbeyond_export.json");
+    dndbeyond(File::open(path).unwrap()).unwrap()
+}
+
+#[test]
+fn the_export_imports_with_warnings_for_what_is_left_out() {
+    let imported: Imported = import();
+    assert_eq!(imported.warnings, vec![
+        ImportWarning::UnmodeledBonus(String::from("perception")),
+        ImportWarning::OverriddenScore(Ability::Charisma, 11),
+        ImportWarning::CustomEntry(String::from("Disguise Kit (borrowed)")),
+    ]);
+}
+
+#[test]
+fn the_imported_character_matches_the_sheet() {
+    let dice: Rc<dyn Roller> = Rc::new(Dice::scripted(Vec::new()));
+    let pc: PC = PC::from_config(dice, &import().config).unwrap();
+    assert_eq!(pc.name(), "Nim Thistlefoot");
+    assert_eq!(pc.level(), Some(5));
+    let modifiers: [i32; 4] = [Ability::Strength, Ability::Dexterity, Ability::Intelligence, Ability::Charisma].map(|ability| pc.ability_modifier(ability));
+    assert_eq!(modifiers, [-1, 3, 1, 0]);
+    assert_eq!(pc.proficiency_modifier(Proficiency::Stealth), 6);
+    assert_eq!(pc.proficiency_modifier(Proficiency::SleightOfHand), 3);
+    assert_eq!(pc.proficiency_modifier(Proficiency::Arcana), 1);
+    assert_eq!(pc.tool_proficiency("thieves' tools"), ProficiencyLevel::Expertise);
+    assert_eq!(pc.tool_proficiency("dice set"), ProficiencyLevel::Proficient);
+    assert!(pc.knows_language("thieves' cant"));
+    assert_eq!(pc.traits(), &[Trait::Lucky]);
+}
+
+#[test]
+fn the_imported_config_saves_and_loads_strictly() {
+    let text: String = serde_json::to_string(&import().config).unwrap();
+    let config: PcConfig = PcConfig::parse(&text, true).unwrap();
+    let dice: Rc<dyn Roller> = Rc::new(Dice::scripted(Vec::new()));
+    assert_eq!(PC::from_config(dice, &config).unwrap().name(), "Nim Thistlefoot");
+}
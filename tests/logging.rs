@@ -0,0 +1,40 @@
+//! Checks that each roll is logged once, rather than once per die.
+
+use rollforgrue::dice::{Advantage, Dice};
+use rollforgrue::pc::{Ability, PcConfig, Proficiency, PC};
+use std::rc::Rc;
+use std::sync::Mutex;
+
+/// The messages logged so far.
+static LINES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// A logger which keeps info-level messages in `LINES`.
+struct Capture;
+
+impl log::Log for Capture {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Info
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            LINES.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[test]
+fn a_check_with_advantage_logs_one_labelled_record() {
+    log::set_logger(&Capture).unwrap();
+    log::set_max_level(log::LevelFilter::Info);
+    let config: PcConfig = PcConfig::parse(r#"{"name":"Aria","abilities":{"wisdom":14},"proficiencies":{"perception":1}}"#, true).unwrap();
+    let mut pc: PC = PC::from_config(Rc::new(Dice::scripted(vec![17, 4])), &config).unwrap();
+    LINES.lock().unwrap().clear();
+    pc.check(Ability::Wisdom, Proficiency::Perception, Advantage::Advantage).unwrap();
+    let lines: Vec<String> = LINES.lock().unwrap().clone();
+    assert_eq!(lines.len(), 1, "{:?}", lines);
+    assert!(lines[0].contains("Perception"), "{}", lines[0]);
+    assert!(lines[0].contains("17") && lines[0].contains('4'), "{}", lines[0]);
+}